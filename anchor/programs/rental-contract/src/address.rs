@@ -0,0 +1,206 @@
+use crate::Address;
+
+/// 縣市行政區域 (內政部行政區域代碼)
+pub struct County {
+    /// 縣市代碼 (5 碼)
+    pub code: u32,
+    pub name: &'static str,
+    /// 轄下鄉鎮市區數，鄉鎮市區代碼為縣市代碼後接 010、020 ... 流水號
+    pub district_count: u32,
+    /// 郵遞區號前三碼範圍
+    pub postal_ranges: &'static [(u16, u16)],
+}
+
+pub const TAIWAN_COUNTIES: [County; 22] = [
+    County { code: 63000, name: "臺北市", district_count: 12, postal_ranges: &[(100, 116)] },
+    County { code: 64000, name: "高雄市", district_count: 38, postal_ranges: &[(800, 852)] },
+    County { code: 65000, name: "新北市", district_count: 29, postal_ranges: &[(207, 208), (220, 253)] },
+    County { code: 66000, name: "臺中市", district_count: 29, postal_ranges: &[(400, 439)] },
+    County { code: 67000, name: "臺南市", district_count: 37, postal_ranges: &[(700, 745)] },
+    County { code: 68000, name: "桃園市", district_count: 13, postal_ranges: &[(320, 338)] },
+    County { code: 10002, name: "宜蘭縣", district_count: 12, postal_ranges: &[(260, 272)] },
+    County { code: 10004, name: "新竹縣", district_count: 13, postal_ranges: &[(302, 315)] },
+    County { code: 10005, name: "苗栗縣", district_count: 18, postal_ranges: &[(350, 369)] },
+    County { code: 10007, name: "彰化縣", district_count: 26, postal_ranges: &[(500, 530)] },
+    County { code: 10008, name: "南投縣", district_count: 13, postal_ranges: &[(540, 558)] },
+    County { code: 10009, name: "雲林縣", district_count: 20, postal_ranges: &[(630, 655)] },
+    County { code: 10010, name: "嘉義縣", district_count: 18, postal_ranges: &[(602, 625)] },
+    County { code: 10013, name: "屏東縣", district_count: 33, postal_ranges: &[(900, 947)] },
+    County { code: 10014, name: "臺東縣", district_count: 16, postal_ranges: &[(950, 966)] },
+    County { code: 10015, name: "花蓮縣", district_count: 13, postal_ranges: &[(970, 983)] },
+    County { code: 10016, name: "澎湖縣", district_count: 6, postal_ranges: &[(880, 885)] },
+    County { code: 10017, name: "基隆市", district_count: 7, postal_ranges: &[(200, 206)] },
+    County { code: 10018, name: "新竹市", district_count: 3, postal_ranges: &[(300, 300)] },
+    County { code: 10020, name: "嘉義市", district_count: 2, postal_ranges: &[(600, 600)] },
+    County { code: 9020, name: "金門縣", district_count: 6, postal_ranges: &[(890, 896)] },
+    County { code: 9007, name: "連江縣", district_count: 4, postal_ranges: &[(209, 212)] },
+];
+
+/// 地址欄位長度上限 (bytes)，需與 `Address` 的 `max_len` 一致
+pub const MAX_DISTRICT_NAME_LEN: usize = 15;
+pub const MAX_STREET_LEN: usize = 64;
+pub const MAX_NUMBER_LEN: usize = 24;
+pub const MAX_UNIT_LEN: usize = 16;
+pub const MAX_POSTAL_CODE_LEN: usize = 6;
+
+/// 依代碼查詢縣市
+pub fn find_county(code: u32) -> Option<&'static County> {
+    TAIWAN_COUNTIES.iter().find(|county| county.code == code)
+}
+
+impl County {
+    /// 檢查鄉鎮市區代碼是否屬於此縣市
+    pub fn contains_district(&self, district_code: u32) -> bool {
+        let (index, remainder) = ((district_code % 1000) / 10, district_code % 10);
+        district_code / 1000 == self.code
+            && remainder == 0
+            && index >= 1
+            && index <= self.district_count
+    }
+
+    /// 檢查郵遞區號 (3、5 或 6 碼) 是否落在此縣市範圍
+    pub fn contains_postal_code(&self, postal_code: &str) -> bool {
+        if !matches!(postal_code.len(), 3 | 5 | 6) || !postal_code.bytes().all(|b| b.is_ascii_digit()) {
+            return false;
+        }
+        let prefix: u16 = match postal_code[..3].parse() {
+            Ok(prefix) => prefix,
+            Err(_) => return false,
+        };
+        self.postal_ranges
+            .iter()
+            .any(|(low, high)| prefix >= *low && prefix <= *high)
+    }
+}
+
+impl Address {
+    /// 驗證地址是否符合行政區域代碼與欄位格式
+    pub fn is_valid(&self) -> bool {
+        let county = match find_county(self.county_code) {
+            Some(county) => county,
+            None => return false,
+        };
+
+        let district_suffix_valid = ["區", "鄉", "鎮", "市"]
+            .iter()
+            .any(|suffix| self.district_name.ends_with(suffix));
+
+        county.contains_district(self.district_code)
+            && county.contains_postal_code(&self.postal_code)
+            && district_suffix_valid
+            && self.district_name.len() <= MAX_DISTRICT_NAME_LEN
+            && !self.street.trim().is_empty()
+            && self.street.len() <= MAX_STREET_LEN
+            && !self.number.trim().is_empty()
+            && self.number.len() <= MAX_NUMBER_LEN
+            && !matches!(
+                &self.unit,
+                Some(unit) if unit.trim().is_empty() || unit.len() > MAX_UNIT_LEN
+            )
+    }
+
+    /// 格式化為完整地址字串，例如「110 臺北市信義區信義路五段7號12樓之3」
+    pub fn format(&self) -> String {
+        let county_name = find_county(self.county_code).map_or("", |county| county.name);
+        let mut formatted = format!(
+            "{} {}{}{}{}",
+            self.postal_code, county_name, self.district_name, self.street, self.number
+        );
+        if self.floor > 0 {
+            formatted.push_str(&format!("{}樓", self.floor));
+        }
+        if let Some(unit) = &self.unit {
+            formatted.push_str(unit);
+        }
+        formatted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xinyi_address() -> Address {
+        Address {
+            county_code: 63000,
+            district_code: 63000020,
+            district_name: "信義區".to_string(),
+            street: "信義路五段".to_string(),
+            number: "7號".to_string(),
+            floor: 12,
+            unit: Some("之3".to_string()),
+            postal_code: "110".to_string(),
+        }
+    }
+
+    #[test]
+    fn counties_cover_all_districts() {
+        let total: u32 = TAIWAN_COUNTIES.iter().map(|county| county.district_count).sum();
+        assert_eq!(total, 368);
+    }
+
+    #[test]
+    fn accepts_valid_addresses() {
+        assert!(xinyi_address().is_valid());
+
+        // 縣轄鄉鎮與 6 碼郵遞區號
+        let township = Address {
+            county_code: 10002,
+            district_code: 10002020,
+            district_name: "羅東鎮".to_string(),
+            street: "中正路".to_string(),
+            number: "1號".to_string(),
+            floor: 3,
+            unit: None,
+            postal_code: "265001".to_string(),
+        };
+        assert!(township.is_valid());
+    }
+
+    #[test]
+    fn rejects_unknown_codes() {
+        let mut address = xinyi_address();
+        address.county_code = 63001;
+        assert!(!address.is_valid());
+
+        // 臺北市只有 12 個行政區
+        let mut address = xinyi_address();
+        address.district_code = 63000130;
+        assert!(!address.is_valid());
+
+        // 鄉鎮市區代碼不屬於該縣市
+        let mut address = xinyi_address();
+        address.district_code = 65000020;
+        assert!(!address.is_valid());
+    }
+
+    #[test]
+    fn rejects_mismatched_postal_code() {
+        let mut address = xinyi_address();
+        address.postal_code = "220".to_string();
+        assert!(!address.is_valid());
+
+        address.postal_code = "11O".to_string();
+        assert!(!address.is_valid());
+    }
+
+    #[test]
+    fn rejects_malformed_fields() {
+        let mut address = xinyi_address();
+        address.district_name = "信義".to_string();
+        assert!(!address.is_valid());
+
+        let mut address = xinyi_address();
+        address.street = " ".to_string();
+        assert!(!address.is_valid());
+
+        let mut address = xinyi_address();
+        address.street = "路".repeat(22);
+        assert!(!address.is_valid());
+    }
+
+    #[test]
+    fn formats_address() {
+        assert_eq!(xinyi_address().format(), "110 臺北市信義區信義路五段7號12樓之3");
+    }
+}
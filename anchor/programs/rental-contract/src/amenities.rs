@@ -0,0 +1,64 @@
+use crate::Amenities;
+
+/// 自訂標籤數量上限，需與 `Listing.custom_tags` 的 `max_len` 一致
+pub const MAX_CUSTOM_TAGS: usize = 4;
+/// 單一自訂標籤長度上限 (bytes)
+pub const MAX_CUSTOM_TAG_LEN: usize = 16;
+/// `Listing.amenities` 在帳戶資料中的位置 (discriminator + authority + bump + id)，供 memcmp 查詢
+pub const LISTING_AMENITIES_OFFSET: usize = 8 + 32 + 1 + 8;
+
+impl Amenities {
+    pub const AIR_CONDITIONING: u32 = 1 << 0;
+    pub const WASHER: u32 = 1 << 1;
+    pub const DRYER: u32 = 1 << 2;
+    pub const REFRIGERATOR: u32 = 1 << 3;
+    pub const WATER_HEATER: u32 = 1 << 4;
+    pub const INTERNET: u32 = 1 << 5;
+    pub const TV: u32 = 1 << 6;
+    pub const FURNISHED: u32 = 1 << 7;
+    pub const ELEVATOR: u32 = 1 << 8;
+    pub const PARKING: u32 = 1 << 9;
+    pub const PETS_ALLOWED: u32 = 1 << 10;
+    pub const BALCONY: u32 = 1 << 11;
+    pub const COOKING_ALLOWED: u32 = 1 << 12;
+    pub const SECURITY: u32 = 1 << 13;
+    pub const ACCESSIBLE: u32 = 1 << 14;
+    pub const NEAR_MRT: u32 = 1 << 15;
+
+    /// 所有已定義設施的位元
+    pub const ALL: u32 = (1 << 16) - 1;
+
+    /// 是否包含指定設施 (可同時指定多項)
+    pub fn contains(&self, flags: u32) -> bool {
+        self.bits & flags == flags
+    }
+
+    /// 是否只使用已定義的位元
+    pub fn is_valid(&self) -> bool {
+        self.bits & !Self::ALL == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_flags() {
+        let amenities = Amenities {
+            bits: Amenities::AIR_CONDITIONING | Amenities::WASHER | Amenities::ELEVATOR,
+        };
+        assert!(amenities.is_valid());
+        assert!(amenities.contains(Amenities::WASHER));
+        assert!(amenities.contains(Amenities::AIR_CONDITIONING | Amenities::ELEVATOR));
+        assert!(!amenities.contains(Amenities::WASHER | Amenities::PARKING));
+        assert!(Amenities::default().contains(0));
+    }
+
+    #[test]
+    fn rejects_undefined_flags() {
+        assert!(Amenities { bits: Amenities::ALL }.is_valid());
+        assert!(!Amenities { bits: 1 << 16 }.is_valid());
+        assert!(!Amenities { bits: u32::MAX }.is_valid());
+    }
+}
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+
+use crate::{Bill, BillStatus, RentalError};
+
+impl Bill {
+    /// 檢查帳單金額大於 0 且繳費期限不早於開立時間
+    pub fn is_valid_terms(amount: u64, due_date: i64, now: i64) -> bool {
+        amount > 0 && due_date >= now
+    }
+
+    /// 租客繳費：待繳或爭議中的帳單皆可繳納 (繳納即視為接受帳單)
+    pub fn mark_paid(&mut self, now: i64) -> Result<()> {
+        require!(
+            matches!(self.status, BillStatus::Open | BillStatus::Disputed),
+            RentalError::InvalidBillStatus
+        );
+        self.status = BillStatus::Paid;
+        self.settled_at = now;
+        Ok(())
+    }
+
+    /// 租客對待繳帳單提出爭議
+    pub fn dispute(&mut self) -> Result<()> {
+        require!(self.status == BillStatus::Open, RentalError::InvalidBillStatus);
+        self.status = BillStatus::Disputed;
+        Ok(())
+    }
+
+    /// 房東方免除尚未繳納的帳單
+    pub fn waive(&mut self, now: i64) -> Result<()> {
+        require!(
+            matches!(self.status, BillStatus::Open | BillStatus::Disputed),
+            RentalError::InvalidBillStatus
+        );
+        self.status = BillStatus::Waived;
+        self.settled_at = now;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BillCategory;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn bill() -> Bill {
+        Bill {
+            rental: Pubkey::new_unique(),
+            bill_id: 0,
+            category: BillCategory::Electricity,
+            amount: 1_200,
+            due_date: NOW + 14 * 24 * 60 * 60,
+            evidence_hash: [7; 32],
+            status: BillStatus::Open,
+            issued_at: NOW,
+            settled_at: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn validates_bill_terms() {
+        assert!(Bill::is_valid_terms(1_200, NOW, NOW));
+        assert!(!Bill::is_valid_terms(0, NOW + 60, NOW));
+        assert!(!Bill::is_valid_terms(1_200, NOW - 1, NOW));
+    }
+
+    #[test]
+    fn tracks_bill_status() {
+        let invalid = Err(RentalError::InvalidBillStatus.into());
+
+        let mut paid = bill();
+        paid.dispute().unwrap();
+        assert_eq!(paid.dispute(), invalid);
+        paid.mark_paid(NOW + 60).unwrap();
+        assert!(paid.status == BillStatus::Paid);
+        assert_eq!(paid.settled_at, NOW + 60);
+        assert_eq!(paid.mark_paid(NOW + 120), invalid);
+        assert_eq!(paid.waive(NOW + 120), invalid);
+
+        let mut waived = bill();
+        waived.waive(NOW).unwrap();
+        assert!(waived.status == BillStatus::Waived);
+        assert_eq!(waived.mark_paid(NOW), invalid);
+        assert_eq!(waived.dispute(), invalid);
+    }
+}
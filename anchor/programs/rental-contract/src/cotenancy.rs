@@ -0,0 +1,232 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::{Rental, RentalError, RentShare};
+
+/// 每份租約最多租客人數 (含主要租客)
+pub const MAX_CO_TENANTS: usize = 5;
+/// 分攤比例總和 (基點)
+pub const TOTAL_SHARE_BPS: u16 = 10_000;
+
+/// 檢查分攤設定：比例皆大於 0 且總和為 10,000 基點、不得重複，且須包含主要租客
+pub fn validate_shares(shares: &[RentShare], primary: &Pubkey) -> Result<()> {
+    require!(
+        (2..=MAX_CO_TENANTS).contains(&shares.len()),
+        RentalError::InvalidRentShares
+    );
+    require!(
+        shares.iter().any(|share| share.tenant == *primary),
+        RentalError::InvalidRentShares
+    );
+    let mut total: u32 = 0;
+    for (index, share) in shares.iter().enumerate() {
+        require!(share.share_bps > 0, RentalError::InvalidRentShares);
+        require!(
+            shares[..index].iter().all(|other| other.tenant != share.tenant),
+            RentalError::InvalidRentShares
+        );
+        total += share.share_bps as u32;
+    }
+    require!(total == TOTAL_SHARE_BPS as u32, RentalError::InvalidRentShares);
+    Ok(())
+}
+
+impl Rental {
+    /// 是否為多人合租 (租金須由各租客分攤)
+    pub fn is_shared(&self) -> bool {
+        !self.co_tenants.is_empty()
+    }
+
+    /// 變更分攤設定須簽署同意的合租租客：新加入、分攤比例變更或被移除者 (主要租客另行簽署)
+    pub fn consenting_tenants(&self, shares: &[RentShare]) -> Vec<Pubkey> {
+        let changed = shares.iter().filter(|share| {
+            !self
+                .co_tenants
+                .iter()
+                .any(|existing| existing.tenant == share.tenant && existing.share_bps == share.share_bps)
+        });
+        let removed = self
+            .co_tenants
+            .iter()
+            .filter(|existing| shares.iter().all(|share| share.tenant != existing.tenant));
+        changed
+            .chain(removed)
+            .map(|share| share.tenant)
+            .filter(|tenant| *tenant != self.tenant)
+            .collect()
+    }
+
+    /// 本期已有租客付款但尚未收齊
+    pub fn is_collecting(&self) -> bool {
+        self.co_tenants_paid != 0
+    }
+
+    /// 指定租客於本期應分攤的金額，最後一位負擔捨入差額使總和等於應付金額
+    pub fn share_amount(&self, index: usize, amount_due: u64) -> u64 {
+        let portion = |share: &RentShare| {
+            (amount_due as u128 * share.share_bps as u128 / TOTAL_SHARE_BPS as u128) as u64
+        };
+        if index + 1 == self.co_tenants.len() {
+            let others: u64 = self.co_tenants[..index].iter().map(portion).sum();
+            amount_due - others
+        } else {
+            portion(&self.co_tenants[index])
+        }
+    }
+
+    /// 記錄一位租客本期的分攤付款
+    ///
+    /// 本期第一筆付款時鎖定應付金額 (法幣計價租約不受後續匯率變動影響)，
+    /// 回傳 (本次應付金額, 是否已收齊)。
+    pub fn record_contribution(&mut self, payer: &Pubkey, amount_due: u64) -> Result<(u64, bool)> {
+        let index = self
+            .co_tenants
+            .iter()
+            .position(|share| share.tenant == *payer)
+            .ok_or(RentalError::NotCoTenant)?;
+        require!(
+            self.co_tenants_paid & (1 << index) == 0,
+            RentalError::ContributionAlreadyPaid
+        );
+        if !self.is_collecting() {
+            self.rent_period_due = amount_due;
+        }
+
+        let contribution = self.share_amount(index, self.rent_period_due);
+        self.co_tenants_paid |= 1 << index;
+        self.rent_collected += contribution;
+        Ok((contribution, self.co_tenants_paid.count_ones() as usize == self.co_tenants.len()))
+    }
+
+    /// 本期收齊並轉交房東後重設收取狀態
+    pub fn reset_collection(&mut self) {
+        self.rent_collected = 0;
+        self.rent_period_due = 0;
+        self.co_tenants_paid = 0;
+    }
+}
+
+/// 以租約 PDA 簽署，將託管的 USDT 分攤租金轉交房東
+pub fn forward_usdt<'info>(
+    rental: &Account<'info, Rental>,
+    rent_escrow: &Account<'info, TokenAccount>,
+    landlord_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let seeds: &[&[u8]] = &[
+        b"rental",
+        rental.listing.as_ref(),
+        rental.tenant.as_ref(),
+        &[rental.bump],
+    ];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: rent_escrow.to_account_info(),
+                to: landlord_token_account.to_account_info(),
+                authority: rental.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share(tenant: Pubkey, share_bps: u16) -> RentShare {
+        RentShare { tenant, share_bps }
+    }
+
+    fn rental(co_tenants: Vec<RentShare>) -> Rental {
+        Rental {
+            tenant: co_tenants.first().map(|share| share.tenant).unwrap_or_default(),
+            co_tenants,
+            ..Rental::test_fixture()
+        }
+    }
+
+    #[test]
+    fn validates_share_sets() {
+        let (primary, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(validate_shares(&[share(primary, 6_000), share(other, 4_000)], &primary).is_ok());
+
+        let invalid = Err(RentalError::InvalidRentShares.into());
+        assert_eq!(validate_shares(&[share(primary, 10_000)], &primary), invalid);
+        assert_eq!(validate_shares(&[share(primary, 6_000), share(other, 3_000)], &primary), invalid);
+        assert_eq!(validate_shares(&[share(primary, 10_000), share(other, 0)], &primary), invalid);
+        assert_eq!(validate_shares(&[share(primary, 5_000), share(primary, 5_000)], &primary), invalid);
+        assert_eq!(
+            validate_shares(&[share(other, 5_000), share(Pubkey::new_unique(), 5_000)], &primary),
+            invalid
+        );
+        let too_many: Vec<RentShare> = (0..=MAX_CO_TENANTS)
+            .map(|index| share(if index == 0 { primary } else { Pubkey::new_unique() }, 1_000))
+            .collect();
+        assert_eq!(validate_shares(&too_many, &primary), invalid);
+    }
+
+    #[test]
+    fn requires_consent_from_affected_co_tenants() {
+        let (primary, roommate, newcomer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let single = Rental { tenant: primary, ..Rental::test_fixture() };
+        assert_eq!(
+            single.consenting_tenants(&[share(primary, 6_000), share(roommate, 4_000)]),
+            vec![roommate]
+        );
+
+        let shared = rental(vec![share(primary, 6_000), share(roommate, 4_000)]);
+        // 比例未變更者不需再次簽署
+        assert!(shared.consenting_tenants(&[share(primary, 6_000), share(roommate, 4_000)]).is_empty());
+        // 未經簽署不得調整既有合租租客的分攤比例
+        assert_eq!(
+            shared.consenting_tenants(&[share(primary, 7_000), share(roommate, 3_000)]),
+            vec![roommate]
+        );
+        // 被移除或取代的合租租客亦須簽署
+        assert_eq!(shared.consenting_tenants(&[]), vec![roommate]);
+        assert_eq!(
+            shared.consenting_tenants(&[share(primary, 6_000), share(newcomer, 4_000)]),
+            vec![newcomer, roommate]
+        );
+    }
+
+    #[test]
+    fn splits_rent_with_rounding_on_last_share() {
+        let rental = rental(vec![
+            share(Pubkey::new_unique(), 3_333),
+            share(Pubkey::new_unique(), 3_333),
+            share(Pubkey::new_unique(), 3_334),
+        ]);
+        let amounts: Vec<u64> = (0..3).map(|index| rental.share_amount(index, 1_000)).collect();
+        assert_eq!(amounts, vec![333, 333, 334]);
+    }
+
+    #[test]
+    fn collects_contributions_until_complete() {
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut rental = rental(vec![share(first, 6_000), share(second, 4_000)]);
+
+        assert_eq!(rental.record_contribution(&first, 1_000).unwrap(), (600, false));
+        assert_eq!(
+            rental.record_contribution(&first, 1_000),
+            Err(RentalError::ContributionAlreadyPaid.into())
+        );
+        assert_eq!(
+            rental.record_contribution(&Pubkey::new_unique(), 1_000),
+            Err(RentalError::NotCoTenant.into())
+        );
+
+        // 本期應付金額於第一筆付款時鎖定
+        assert_eq!(rental.record_contribution(&second, 2_000).unwrap(), (400, true));
+        assert_eq!(rental.rent_collected, 1_000);
+
+        rental.reset_collection();
+        assert!(!rental.is_collecting());
+        assert_eq!(rental.co_tenants_paid, 0);
+    }
+}
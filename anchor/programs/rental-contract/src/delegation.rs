@@ -0,0 +1,162 @@
+use anchor_lang::prelude::*;
+
+use crate::{Delegation, Listing, RentalError};
+
+/// 管理費上限 (基點)
+pub const MAX_MANAGEMENT_FEE_BPS: u16 = 3_000;
+
+impl Delegation {
+    /// 更新房源資訊、中繼資料、租客條件、要約與房源文件
+    pub const UPDATE_LISTING: u8 = 1 << 0;
+    /// 提出租約、審核申請、設定合租、調整與終止租約
+    pub const MANAGE_TENANTS: u8 = 1 << 1;
+    /// 結算押金與扣除
+    pub const SETTLE_DEPOSITS: u8 = 1 << 2;
+    /// 自租金抽取管理費
+    pub const COLLECT_FEE: u8 = 1 << 3;
+    pub const ALL: u8 = (1 << 4) - 1;
+
+    /// 檢查權限與管理費設定是否有效
+    pub fn is_valid_grant(permissions: u8, fee_bps: u16, expires_at: i64, now: i64) -> bool {
+        permissions != 0
+            && permissions & !Self::ALL == 0
+            && fee_bps <= MAX_MANAGEMENT_FEE_BPS
+            && (fee_bps == 0 || permissions & Self::COLLECT_FEE != 0)
+            && expires_at > now
+    }
+
+    /// 檢查簽署者為房東本人，或持有該房源未過期且具指定權限的代管授權
+    pub fn authorize(
+        listing: &Pubkey,
+        authority: &Pubkey,
+        signer: &Pubkey,
+        delegation: Option<&Delegation>,
+        permission: u8,
+        now: i64,
+    ) -> Result<()> {
+        if signer == authority {
+            return Ok(());
+        }
+        let delegation = delegation.ok_or(RentalError::Unauthorized)?;
+        require!(
+            delegation.listing == *listing
+                && delegation.authority == *authority
+                && delegation.manager == *signer,
+            RentalError::Unauthorized
+        );
+        require!(delegation.expires_at > now, RentalError::DelegationExpired);
+        require!(
+            delegation.permissions & permission == permission,
+            RentalError::PermissionDenied
+        );
+        Ok(())
+    }
+
+    /// 計算代管人應得的管理費，授權不屬於此房源、已過期或未授權抽取時為 0
+    pub fn management_fee(&self, listing: &Pubkey, authority: &Pubkey, amount: u64, now: i64) -> u64 {
+        if self.listing != *listing
+            || self.authority != *authority
+            || self.expires_at <= now
+            || self.permissions & Self::COLLECT_FEE == 0
+        {
+            return 0;
+        }
+        (amount as u128 * self.fee_bps as u128 / 10_000) as u64
+    }
+}
+
+impl Listing {
+    /// 本期租金應付代管人的管理費；房源設有收費代管授權時須附上該授權
+    pub fn management_fee(
+        &self,
+        listing_key: &Pubkey,
+        delegation: Option<&Delegation>,
+        amount: u64,
+        now: i64,
+    ) -> Result<u64> {
+        if self.fee_delegation == Pubkey::default() {
+            return Ok(0);
+        }
+        let delegation = delegation.ok_or(RentalError::InvalidDelegation)?;
+        Ok(delegation.management_fee(listing_key, &self.authority, amount, now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn delegation(permissions: u8) -> Delegation {
+        Delegation {
+            listing: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            manager: Pubkey::new_unique(),
+            permissions,
+            fee_bps: 800,
+            expires_at: NOW + 86_400,
+            created_at: NOW,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn validates_grants() {
+        assert!(Delegation::is_valid_grant(Delegation::ALL, 800, NOW + 1, NOW));
+        assert!(Delegation::is_valid_grant(Delegation::UPDATE_LISTING, 0, NOW + 1, NOW));
+        assert!(!Delegation::is_valid_grant(0, 0, NOW + 1, NOW));
+        assert!(!Delegation::is_valid_grant(1 << 4, 0, NOW + 1, NOW));
+        assert!(!Delegation::is_valid_grant(Delegation::UPDATE_LISTING, 800, NOW + 1, NOW));
+        assert!(!Delegation::is_valid_grant(Delegation::ALL, MAX_MANAGEMENT_FEE_BPS + 1, NOW + 1, NOW));
+        assert!(!Delegation::is_valid_grant(Delegation::ALL, 800, NOW, NOW));
+    }
+
+    #[test]
+    fn authorizes_owner_and_scoped_manager() {
+        let grant = delegation(Delegation::UPDATE_LISTING | Delegation::MANAGE_TENANTS);
+        let (listing, authority, manager) = (grant.listing, grant.authority, grant.manager);
+
+        assert!(Delegation::authorize(&listing, &authority, &authority, None, Delegation::ALL, NOW).is_ok());
+        assert!(Delegation::authorize(&listing, &authority, &manager, Some(&grant), Delegation::MANAGE_TENANTS, NOW).is_ok());
+        assert_eq!(
+            Delegation::authorize(&listing, &authority, &manager, None, Delegation::UPDATE_LISTING, NOW),
+            Err(RentalError::Unauthorized.into())
+        );
+        assert_eq!(
+            Delegation::authorize(&listing, &authority, &manager, Some(&grant), Delegation::SETTLE_DEPOSITS, NOW),
+            Err(RentalError::PermissionDenied.into())
+        );
+        assert_eq!(
+            Delegation::authorize(&listing, &authority, &manager, Some(&grant), Delegation::UPDATE_LISTING, NOW + 86_400),
+            Err(RentalError::DelegationExpired.into())
+        );
+
+        // 授權不可跨房源或由他人使用，房東變更後亦失效
+        assert_eq!(
+            Delegation::authorize(&Pubkey::new_unique(), &authority, &manager, Some(&grant), Delegation::UPDATE_LISTING, NOW),
+            Err(RentalError::Unauthorized.into())
+        );
+        assert_eq!(
+            Delegation::authorize(&listing, &Pubkey::new_unique(), &manager, Some(&grant), Delegation::UPDATE_LISTING, NOW),
+            Err(RentalError::Unauthorized.into())
+        );
+        assert_eq!(
+            Delegation::authorize(&listing, &authority, &Pubkey::new_unique(), Some(&grant), Delegation::UPDATE_LISTING, NOW),
+            Err(RentalError::Unauthorized.into())
+        );
+    }
+
+    #[test]
+    fn computes_management_fee() {
+        let grant = delegation(Delegation::COLLECT_FEE);
+        let (listing, authority) = (grant.listing, grant.authority);
+        assert_eq!(grant.management_fee(&listing, &authority, 25_000, NOW), 2_000);
+        assert_eq!(grant.management_fee(&listing, &authority, 25_000, NOW + 86_400), 0);
+        assert_eq!(grant.management_fee(&Pubkey::new_unique(), &authority, 25_000, NOW), 0);
+
+        let mut without_fee = grant.clone();
+        without_fee.permissions = Delegation::UPDATE_LISTING;
+        assert_eq!(without_fee.management_fee(&listing, &authority, 25_000, NOW), 0);
+    }
+}
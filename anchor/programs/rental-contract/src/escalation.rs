@@ -0,0 +1,209 @@
+use anchor_lang::prelude::*;
+
+use crate::oracle::OracleError;
+use crate::price_feed::PriceFeedValidator;
+use crate::{EscalationKind, Rental, RentalError, RentEscalated, RentEscalation};
+
+/// 單期調漲比例上限 (基點)
+pub const MAX_ESCALATION_BPS: u16 = 2_000;
+/// CPI 指數報價最長有效秒數 (指數按月公布)
+pub const CPI_MAX_AGE: u64 = 45 * 24 * 60 * 60;
+/// 一個計租月的秒數
+const SECONDS_PER_MONTH: i64 = 30 * 24 * 60 * 60;
+
+/// 讀取 CPI 指數預言機 (Pyth 格式價格帳戶) 的最新指數
+pub fn load_cpi_index(account: &AccountInfo, expected_feed: &Pubkey, current_time: i64) -> Result<i64> {
+    require!(account.key == expected_feed, OracleError::InvalidPriceFeed);
+    let data = account
+        .try_borrow_data()
+        .map_err(|_| error!(OracleError::InvalidPriceFeed))?;
+    let quote = PriceFeedValidator::parse_price_account(&data)?;
+    require!(current_time.abs_diff(quote.publish_time) <= CPI_MAX_AGE, OracleError::StalePrice);
+    Ok(quote.price)
+}
+
+/// 付款時套用本期應生效的調漲，有新調漲生效時發出 `RentEscalated`
+pub fn apply(rental: &mut Account<Rental>, cpi_feed: Option<&AccountInfo>, current_time: i64) -> Result<()> {
+    let cpi_index = match rental.escalation_due() {
+        Some(escalation) if matches!(escalation.kind, EscalationKind::CpiIndexed { .. }) => {
+            let feed = cpi_feed.ok_or(OracleError::PriceFeedNotConfigured)?;
+            Some(load_cpi_index(feed, &escalation.cpi_feed, current_time)?)
+        }
+        _ => None,
+    };
+    if let Some(old_price) = rental.escalate(cpi_index)? {
+        emit!(RentEscalated {
+            rental: rental.key(),
+            tier: rental.escalation.map(|escalation| escalation.tier).unwrap_or_default(),
+            old_price,
+            new_price: rental.price,
+            fiat_price: rental.fiat_price,
+            timestamp: current_time,
+        });
+    }
+    Ok(())
+}
+
+/// 依基點調漲金額
+fn raise(amount: u64, bps: u64) -> Result<u64> {
+    let raised = amount as u128 * (10_000 + bps as u128) / 10_000;
+    u64::try_from(raised).map_err(|_| error!(RentalError::InvalidEscalation))
+}
+
+impl RentEscalation {
+    /// 檢查調漲條款：間隔至少一個月、比例不超過 `MAX_ESCALATION_BPS`；法幣計價租約不可調漲固定金額
+    pub fn is_valid(&self, fiat_priced: bool) -> bool {
+        self.interval_months > 0
+            && match self.kind {
+                EscalationKind::Percent { bps } => bps > 0 && bps <= MAX_ESCALATION_BPS,
+                EscalationKind::Amount { amount } => amount > 0 && !fiat_priced,
+                EscalationKind::CpiIndexed { cap_bps } => {
+                    cap_bps > 0 && cap_bps <= MAX_ESCALATION_BPS && self.cpi_feed != Pubkey::default()
+                }
+            }
+    }
+
+    /// 起租後至指定付款期應已生效的調漲次數
+    pub fn due_tier(&self, start_date: i64, period_due: i64) -> u16 {
+        let interval = self.interval_months as i64 * SECONDS_PER_MONTH;
+        ((period_due - start_date).max(0) / interval).min(u16::MAX as i64) as u16
+    }
+}
+
+impl Rental {
+    /// 本期付款是否有尚未生效的調漲 (CPI 連動時須提供指數預言機)
+    pub fn escalation_due(&self) -> Option<RentEscalation> {
+        self.escalation
+            .filter(|escalation| escalation.due_tier(self.start_date, self.next_payment_date) > escalation.tier)
+    }
+
+    /// 套用至本期應生效的調漲，回傳調漲前的租金；無新調漲時回傳 None
+    ///
+    /// 比例與 CPI 調漲同時調整法幣計價金額；CPI 漲幅依累計期數設上限且租金不因指數下跌而調降。
+    pub fn escalate(&mut self, cpi_index: Option<i64>) -> Result<Option<u64>> {
+        let Some(mut escalation) = self.escalation_due() else {
+            return Ok(None);
+        };
+        let due = escalation.due_tier(self.start_date, self.next_payment_date);
+        let tiers = (due - escalation.tier) as u64;
+        let old_price = self.price;
+
+        let raise_bps = match escalation.kind {
+            EscalationKind::Percent { bps } => {
+                // 多期一併生效時依期數複利計算
+                let factor = (0..tiers).fold(10_000u128, |factor, _| factor * (10_000 + bps as u128) / 10_000);
+                u64::try_from(factor - 10_000).map_err(|_| error!(RentalError::InvalidEscalation))?
+            }
+            EscalationKind::Amount { amount } => {
+                self.price = amount
+                    .checked_mul(tiers)
+                    .and_then(|raise| self.price.checked_add(raise))
+                    .ok_or(RentalError::InvalidEscalation)?;
+                0
+            }
+            EscalationKind::CpiIndexed { cap_bps } => {
+                let index = cpi_index.ok_or(RentalError::InvalidEscalation)?;
+                require!(index > 0 && escalation.cpi_index > 0, RentalError::InvalidEscalation);
+                let change = (index - escalation.cpi_index).max(0) as u128 * 10_000 / escalation.cpi_index as u128;
+                let cap = cap_bps as u128 * tiers as u128;
+                escalation.cpi_index = escalation.cpi_index.max(index);
+                change.min(cap) as u64
+            }
+        };
+        if raise_bps > 0 {
+            self.price = raise(self.price, raise_bps)?;
+            if let Some(fiat_price) = self.fiat_price.as_mut() {
+                fiat_price.amount = raise(fiat_price.amount, raise_bps)?;
+            }
+        }
+
+        escalation.tier = due;
+        self.escalation = Some(escalation);
+        Ok(Some(old_price))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FiatCurrency, FiatPrice};
+
+    const NOW: i64 = 1_700_000_000;
+    const MONTH: i64 = SECONDS_PER_MONTH;
+
+    fn escalating_rental(kind: EscalationKind) -> Rental {
+        Rental {
+            price: 10_000,
+            contract_length: 36,
+            start_date: NOW,
+            end_date: NOW + 36 * MONTH,
+            next_payment_date: NOW,
+            escalation: Some(RentEscalation {
+                kind,
+                interval_months: 12,
+                cpi_feed: Pubkey::new_unique(),
+                cpi_index: 1_000,
+                tier: 0,
+            }),
+            ..Rental::test_fixture()
+        }
+    }
+
+    #[test]
+    fn validates_escalation_terms() {
+        let terms = escalating_rental(EscalationKind::Percent { bps: 300 }).escalation.unwrap();
+        assert!(terms.is_valid(true));
+        assert!(!RentEscalation { interval_months: 0, ..terms }.is_valid(false));
+        assert!(!RentEscalation { kind: EscalationKind::Percent { bps: 2_001 }, ..terms }.is_valid(false));
+
+        let amount = RentEscalation { kind: EscalationKind::Amount { amount: 500 }, ..terms };
+        assert!(amount.is_valid(false));
+        assert!(!amount.is_valid(true));
+
+        let cpi = RentEscalation { kind: EscalationKind::CpiIndexed { cap_bps: 500 }, ..terms };
+        assert!(cpi.is_valid(true));
+        assert!(!RentEscalation { cpi_feed: Pubkey::default(), ..cpi }.is_valid(true));
+    }
+
+    #[test]
+    fn applies_percent_and_amount_tiers() {
+        let mut rental = escalating_rental(EscalationKind::Percent { bps: 300 });
+        rental.fiat_price = Some(FiatPrice { amount: 50_000, currency: FiatCurrency::Twd });
+
+        // 第一年租金不變
+        rental.next_payment_date = NOW + 11 * MONTH;
+        assert_eq!(rental.escalate(None), Ok(None));
+
+        rental.next_payment_date = NOW + 12 * MONTH;
+        assert_eq!(rental.escalate(None), Ok(Some(10_000)));
+        assert_eq!(rental.price, 10_300);
+        assert_eq!(rental.fiat_price.unwrap().amount, 51_500);
+        // 同一期重複呼叫不會再次調漲
+        assert_eq!(rental.escalate(None), Ok(None));
+
+        let mut rental = escalating_rental(EscalationKind::Amount { amount: 500 });
+        // 逾期多期未付時一次補上所有調漲
+        rental.next_payment_date = NOW + 24 * MONTH;
+        assert_eq!(rental.escalate(None), Ok(Some(10_000)));
+        assert_eq!(rental.price, 11_000);
+        assert_eq!(rental.escalation.unwrap().tier, 2);
+    }
+
+    #[test]
+    fn caps_cpi_indexed_escalation() {
+        let mut rental = escalating_rental(EscalationKind::CpiIndexed { cap_bps: 500 });
+        rental.next_payment_date = NOW + 12 * MONTH;
+        assert_eq!(rental.escalate(None), Err(RentalError::InvalidEscalation.into()));
+
+        // 指數上漲 8%，以 5% 為上限
+        assert_eq!(rental.escalate(Some(1_080)), Ok(Some(10_000)));
+        assert_eq!(rental.price, 10_500);
+        assert_eq!(rental.escalation.unwrap().cpi_index, 1_080);
+
+        // 指數下跌時租金維持不變
+        rental.next_payment_date = NOW + 24 * MONTH;
+        assert_eq!(rental.escalate(Some(1_050)), Ok(Some(10_500)));
+        assert_eq!(rental.price, 10_500);
+        assert_eq!(rental.escalation.unwrap().cpi_index, 1_080);
+    }
+}
@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::TokenAccount;
+
+use crate::RentalError;
+
+/// 自程式擁有的帳戶 (申請、租約等) 直接轉出託管的 lamports
+pub fn release_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+/// 關閉程式擁有的帳戶並將全部 lamports 退還給指定帳戶
+pub fn close_account(info: &AccountInfo, destination: &AccountInfo) -> Result<()> {
+    release_lamports(info, destination, info.lamports())?;
+    info.assign(&system_program::ID);
+    info.realloc(0, false)?;
+    Ok(())
+}
+
+/// 取得由指定錢包持有的 USDT 帳戶：未提供時回傳 `InvalidEscrowAccount`，持有人不符回傳 `Unauthorized`
+pub fn token_account_of<'a, 'info>(
+    account: Option<&'a Account<'info, TokenAccount>>,
+    owner: &Pubkey,
+) -> Result<&'a Account<'info, TokenAccount>> {
+    let account = account.ok_or(RentalError::InvalidEscrowAccount)?;
+    require!(account.owner == *owner, RentalError::Unauthorized);
+    Ok(account)
+}
+
+/// 將應退款項拆分為 (由託管支付, 由房東支付)
+pub fn split_refund(escrow_balance: u64, refund_amount: u64) -> (u64, u64) {
+    let from_escrow = escrow_balance.min(refund_amount);
+    (from_escrow, refund_amount - from_escrow)
+}
+
+/// 將申請保證金拆分為 (轉入租約託管的押金, 尚未收取的押金)
+///
+/// 房源押金於申請後調降時，超出押金的保證金不轉入託管，隨申請帳戶關閉退還租客。
+pub fn split_hold(hold_amount: u64, deposit: u64) -> (u64, u64) {
+    let escrowed = hold_amount.min(deposit);
+    (escrowed, deposit - escrowed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_refund_between_escrow_and_landlord() {
+        assert_eq!(split_refund(0, 100), (0, 100));
+        assert_eq!(split_refund(40, 100), (40, 60));
+        assert_eq!(split_refund(100, 100), (100, 0));
+        assert_eq!(split_refund(150, 100), (100, 0));
+    }
+
+    #[test]
+    fn caps_hold_at_lowered_deposit() {
+        assert_eq!(split_hold(500, 2_000), (500, 1_500));
+        assert_eq!(split_hold(2_000, 2_000), (2_000, 0));
+        // 申請後房源押金調降至低於保證金
+        assert_eq!(split_hold(2_000, 1_200), (1_200, 0));
+    }
+
+    #[test]
+    fn releases_lamports_between_accounts() {
+        let owner = crate::ID;
+        let (from_key, to_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut from_lamports, mut to_lamports) = (1_000u64, 10u64);
+        let (mut from_data, mut to_data) = (vec![0u8; 8], vec![]);
+        let from = AccountInfo::new(&from_key, false, true, &mut from_lamports, &mut from_data, &owner, false, 0);
+        let to = AccountInfo::new(&to_key, false, true, &mut to_lamports, &mut to_data, &owner, false, 0);
+
+        release_lamports(&from, &to, 400).unwrap();
+        assert_eq!((from.lamports(), to.lamports()), (600, 410));
+        assert!(release_lamports(&from, &to, 601).is_err());
+        assert_eq!((from.lamports(), to.lamports()), (600, 410));
+    }
+
+    #[test]
+    fn rejects_token_account_of_another_wallet() {
+        use anchor_lang::solana_program::program_pack::Pack;
+        use anchor_spl::token::spl_token::{self, state::Account as SplAccount, state::AccountState};
+
+        let (key, tenant) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = vec![0u8; SplAccount::LEN];
+        SplAccount::pack(
+            SplAccount {
+                mint: Pubkey::new_unique(),
+                owner: tenant,
+                state: AccountState::Initialized,
+                ..Default::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        let mut lamports = 1_000u64;
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &spl_token::ID, false, 0);
+        let account = Account::<TokenAccount>::try_from(&info).unwrap();
+
+        assert!(token_account_of(Some(&account), &tenant).is_ok());
+        // 房東方帶入他人 (例如自己) 的帳戶作為退款對象
+        assert_eq!(
+            token_account_of(Some(&account), &Pubkey::new_unique()).map(|account| account.owner),
+            Err(RentalError::Unauthorized.into())
+        );
+        assert_eq!(
+            token_account_of(None, &tenant).map(|account| account.owner),
+            Err(RentalError::InvalidEscrowAccount.into())
+        );
+    }
+}
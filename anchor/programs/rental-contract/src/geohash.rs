@@ -0,0 +1,80 @@
+use crate::Coordinates;
+
+/// 房源儲存的 geohash 精度 (7 碼約 150 公尺見方)
+pub const GEOHASH_PRECISION: usize = 7;
+/// 索引 PDA 種子使用的 geohash 前綴長度 (5 碼約 5 公里見方)
+pub const GEOHASH_INDEX_PRECISION: usize = 5;
+
+const BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+const MICRO_DEGREES: i64 = 1_000_000;
+
+impl Coordinates {
+    /// 計算座標的 geohash
+    ///
+    /// 以整數運算求出經緯度在 2^n 等分格網中的索引後交錯位元，結果與標準 geohash 一致。
+    pub fn geohash(&self) -> [u8; GEOHASH_PRECISION] {
+        let total_bits = (GEOHASH_PRECISION * 5) as u32;
+        let latitude_bits = total_bits / 2;
+        let longitude_bits = total_bits - latitude_bits;
+
+        let longitude_cell = cell_index(self.longitude as i64, 180, longitude_bits);
+        let latitude_cell = cell_index(self.latitude as i64, 90, latitude_bits);
+
+        // 偶數位元取經度、奇數位元取緯度，皆由最高位開始
+        let mut bits: u64 = 0;
+        for i in 0..total_bits {
+            let bit = if i % 2 == 0 {
+                (longitude_cell >> (longitude_bits - 1 - i / 2)) & 1
+            } else {
+                (latitude_cell >> (latitude_bits - 1 - i / 2)) & 1
+            };
+            bits = (bits << 1) | bit;
+        }
+
+        let mut geohash = [0u8; GEOHASH_PRECISION];
+        for (i, byte) in geohash.iter_mut().enumerate() {
+            let shift = total_bits - 5 * (i as u32 + 1);
+            *byte = BASE32[((bits >> shift) & 0x1f) as usize];
+        }
+        geohash
+    }
+}
+
+/// 計算 [-range, range] 區間 (微度) 等分為 2^bits 格後所在的格子索引
+fn cell_index(value: i64, range: i64, bits: u32) -> u64 {
+    let span = 2 * range * MICRO_DEGREES;
+    let offset = (value + range * MICRO_DEGREES).clamp(0, span) as u128;
+    let index = (offset << bits) / span as u128;
+    index.min((1u128 << bits) - 1) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_reference_points() {
+        // geohash.org 參考值
+        let jutland = Coordinates { latitude: 57_649_110, longitude: 10_407_440 };
+        assert_eq!(&jutland.geohash(), b"u4pruyd");
+
+        let leon = Coordinates { latitude: 42_600_000, longitude: -5_600_000 };
+        assert_eq!(&leon.geohash()[..5], b"ezs42");
+    }
+
+    #[test]
+    fn encodes_taipei() {
+        // 台北 101
+        let taipei_101 = Coordinates { latitude: 25_033_964, longitude: 121_564_472 };
+        assert_eq!(&taipei_101.geohash(), b"wsqqqm2");
+    }
+
+    #[test]
+    fn clamps_extreme_coordinates() {
+        let north_east = Coordinates { latitude: 90_000_000, longitude: 180_000_000 };
+        assert_eq!(&north_east.geohash(), b"zzzzzzz");
+
+        let south_west = Coordinates { latitude: -90_000_000, longitude: -180_000_000 };
+        assert_eq!(&south_west.geohash(), b"0000000");
+    }
+}
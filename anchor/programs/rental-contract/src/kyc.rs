@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+
+use crate::{KycAttestation, KycPolicy, RentalError};
+
+/// 最高 KYC 等級
+pub const MAX_KYC_LEVEL: u8 = 3;
+
+impl KycAttestation {
+    /// 建立新的認證，等級須為 1 ~ `MAX_KYC_LEVEL` 且到期時間晚於現在
+    pub fn new(
+        issuer: Pubkey,
+        level: u8,
+        expires_at: i64,
+        evidence_hash: [u8; 32],
+        now: i64,
+    ) -> Result<Self> {
+        require!(
+            (1..=MAX_KYC_LEVEL).contains(&level) && expires_at > now,
+            RentalError::InvalidKycAttestation
+        );
+        Ok(Self {
+            issuer,
+            level,
+            issued_at: now,
+            expires_at,
+            evidence_hash,
+        })
+    }
+
+    /// 檢查認證是否達到指定等級且尚未過期
+    pub fn check(&self, min_level: u8, now: i64) -> Result<()> {
+        require!(self.expires_at > now, RentalError::KycExpired);
+        require!(self.level >= min_level, RentalError::KycLevelTooLow);
+        Ok(())
+    }
+}
+
+impl KycPolicy {
+    /// 依平台設定檢查一方的 KYC 認證，未要求時直接通過
+    pub fn enforce(
+        &self,
+        required: bool,
+        attestation: Option<&KycAttestation>,
+        now: i64,
+    ) -> Result<()> {
+        if !required {
+            return Ok(());
+        }
+        attestation
+            .ok_or(RentalError::KycRequired)?
+            .check(self.min_level, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn attestation(level: u8, expires_at: i64) -> KycAttestation {
+        KycAttestation {
+            issuer: Pubkey::new_unique(),
+            level,
+            issued_at: NOW - 86_400,
+            expires_at,
+            evidence_hash: [7u8; 32],
+        }
+    }
+
+    fn policy(min_level: u8) -> KycPolicy {
+        KycPolicy { require_landlord: true, require_tenant: false, min_level }
+    }
+
+    #[test]
+    fn validates_new_attestation() {
+        let issuer = Pubkey::new_unique();
+        let attestation = KycAttestation::new(issuer, 1, NOW + 1, [1u8; 32], NOW).unwrap();
+        assert_eq!(attestation.issued_at, NOW);
+        assert_eq!(
+            KycAttestation::new(issuer, 0, NOW + 1, [1u8; 32], NOW),
+            Err(RentalError::InvalidKycAttestation.into())
+        );
+        assert_eq!(
+            KycAttestation::new(issuer, MAX_KYC_LEVEL + 1, NOW + 1, [1u8; 32], NOW),
+            Err(RentalError::InvalidKycAttestation.into())
+        );
+        assert_eq!(
+            KycAttestation::new(issuer, 1, NOW, [1u8; 32], NOW),
+            Err(RentalError::InvalidKycAttestation.into())
+        );
+    }
+
+    #[test]
+    fn accepts_valid_attestation() {
+        let attestation = attestation(2, NOW + 1);
+        assert!(policy(2).enforce(true, Some(&attestation), NOW).is_ok());
+        assert!(policy(1).enforce(true, Some(&attestation), NOW).is_ok());
+    }
+
+    #[test]
+    fn rejects_missing_expired_or_low_level() {
+        assert_eq!(policy(1).enforce(true, None, NOW), Err(RentalError::KycRequired.into()));
+        assert_eq!(
+            policy(1).enforce(true, Some(&attestation(2, NOW)), NOW),
+            Err(RentalError::KycExpired.into())
+        );
+        assert_eq!(
+            policy(3).enforce(true, Some(&attestation(2, NOW + 1)), NOW),
+            Err(RentalError::KycLevelTooLow.into())
+        );
+    }
+
+    #[test]
+    fn skips_when_not_required() {
+        assert!(policy(3).enforce(false, None, NOW).is_ok());
+    }
+}
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+
+use crate::{LeaseAgreement, Rental, RentalError};
+
+impl LeaseAgreement {
+    /// 雙方是否皆已簽署
+    pub fn is_fully_signed(&self) -> bool {
+        self.landlord_signed_at > 0 && self.tenant_signed_at > 0
+    }
+
+    /// 租客簽署，須確認簽署的文件雜湊與版本與房東提出的一致
+    pub fn sign_as_tenant(&mut self, document_hash: &[u8; 32], version: u16, now: i64) -> Result<()> {
+        require!(self.tenant_signed_at == 0, RentalError::LeaseAlreadySigned);
+        require!(
+            self.document_hash == *document_hash && self.version == version,
+            RentalError::LeaseMismatch
+        );
+        self.tenant_signed_at = now;
+        Ok(())
+    }
+
+    /// 檢查租約可用於建立指定租客的租約，且尚未被使用
+    pub fn check_usable(&self, listing: &Pubkey, tenant: &Pubkey) -> Result<()> {
+        require!(
+            self.listing == *listing && self.tenant == *tenant,
+            RentalError::LeaseMismatch
+        );
+        require!(self.is_fully_signed(), RentalError::LeaseNotSigned);
+        require!(self.rental == Pubkey::default(), RentalError::LeaseAlreadyUsed);
+        Ok(())
+    }
+}
+
+impl Rental {
+    /// 將雙方簽署的租約文件記錄於租約，作為契約內容的鏈上證明
+    pub fn record_lease(&mut self, lease: &LeaseAgreement) {
+        self.lease_document_hash = lease.document_hash;
+        self.lease_version = lease.version;
+        self.lease_signed_at = lease.landlord_signed_at.max(lease.tenant_signed_at);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn proposed() -> LeaseAgreement {
+        LeaseAgreement {
+            listing: Pubkey::new_unique(),
+            landlord: Pubkey::new_unique(),
+            tenant: Pubkey::new_unique(),
+            document_hash: [9u8; 32],
+            version: 2,
+            landlord_signed_at: NOW - 60,
+            tenant_signed_at: 0,
+            rental: Pubkey::default(),
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn tenant_signs_matching_document() {
+        let mut lease = proposed();
+        assert!(!lease.is_fully_signed());
+        lease.sign_as_tenant(&[9u8; 32], 2, NOW).unwrap();
+        assert!(lease.is_fully_signed());
+        assert_eq!(
+            lease.sign_as_tenant(&[9u8; 32], 2, NOW),
+            Err(RentalError::LeaseAlreadySigned.into())
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_document() {
+        let mut lease = proposed();
+        assert_eq!(
+            lease.sign_as_tenant(&[8u8; 32], 2, NOW),
+            Err(RentalError::LeaseMismatch.into())
+        );
+        assert_eq!(
+            lease.sign_as_tenant(&[9u8; 32], 1, NOW),
+            Err(RentalError::LeaseMismatch.into())
+        );
+    }
+
+    #[test]
+    fn checks_usable_lease() {
+        let mut lease = proposed();
+        let (listing, tenant) = (lease.listing, lease.tenant);
+        assert_eq!(lease.check_usable(&listing, &tenant), Err(RentalError::LeaseNotSigned.into()));
+
+        lease.sign_as_tenant(&[9u8; 32], 2, NOW).unwrap();
+        assert!(lease.check_usable(&listing, &tenant).is_ok());
+        assert_eq!(
+            lease.check_usable(&listing, &Pubkey::new_unique()),
+            Err(RentalError::LeaseMismatch.into())
+        );
+
+        lease.rental = Pubkey::new_unique();
+        assert_eq!(lease.check_usable(&listing, &tenant), Err(RentalError::LeaseAlreadyUsed.into()));
+    }
+}
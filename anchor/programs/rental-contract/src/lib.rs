@@ -5,6 +5,59 @@ use anchor_spl::associated_token::AssociatedToken;
 mod oracle;
 use oracle::{OracleValidator, OracleError};
 
+mod price_feed;
+use price_feed::PriceFeedValidator;
+
+mod address;
+
+mod geohash;
+use geohash::{GEOHASH_INDEX_PRECISION, GEOHASH_PRECISION};
+
+mod listing_index;
+
+pub mod amenities;
+
+pub mod validation;
+use validation::InputValidator;
+
+mod kyc;
+use kyc::MAX_KYC_LEVEL;
+
+pub mod reputation;
+use reputation::INITIAL_REPUTATION_SCORE;
+
+mod escrow;
+
+mod lease;
+
+mod offer;
+
+mod cotenancy;
+
+mod ownership;
+
+mod delegation;
+
+mod property;
+
+mod rooms;
+
+mod stay;
+use stay::{day_of, CALENDAR_BYTES, SECONDS_PER_DAY};
+
+mod reservation;
+
+mod pending;
+
+mod escalation;
+
+mod bill;
+
+mod maintenance;
+use maintenance::MAX_ABATEMENT_BPS;
+
+pub mod metadata;
+
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
 #[program]
@@ -15,540 +68,4677 @@ pub mod rental_contract {
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
         let platform = &mut ctx.accounts.platform;
         platform.authority = ctx.accounts.authority.key();
+        platform.verifier = ctx.accounts.authority.key();
         platform.bump = *ctx.bumps.get("platform").unwrap();
         platform.total_listings = 0;
         platform.total_rentals = 0;
         platform.total_volume = 0;
+        platform.sol_usd_price_feed = Pubkey::default();
+        platform.usd_twd_price_feed = Pubkey::default();
+        platform.max_price_age = DEFAULT_MAX_PRICE_AGE;
+        platform.max_price_conf_bps = DEFAULT_MAX_PRICE_CONF_BPS;
+        platform.kyc_policy = KycPolicy::default();
         
         msg!("租屋平台已初始化");
         Ok(())
     }
 
+    /// 設定 Pyth 價格預言機
+    pub fn configure_price_feeds(
+        ctx: Context<ConfigurePriceFeeds>,
+        sol_usd_price_feed: Pubkey,
+        usd_twd_price_feed: Pubkey,
+        max_price_age: u64,
+        max_price_conf_bps: u16,
+    ) -> Result<()> {
+        require!(
+            PriceFeedValidator::is_valid_limits(max_price_age, max_price_conf_bps),
+            OracleError::InvalidPriceFeedConfig
+        );
+        let platform = &mut ctx.accounts.platform;
+        
+        platform.sol_usd_price_feed = sol_usd_price_feed;
+        platform.usd_twd_price_feed = usd_twd_price_feed;
+        platform.max_price_age = max_price_age;
+        platform.max_price_conf_bps = max_price_conf_bps;
+        
+        msg!("價格預言機已設定，SOL/USD: {}", sol_usd_price_feed);
+        Ok(())
+    }
+
+    /// 設定驗證者
+    pub fn set_verifier(ctx: Context<SetVerifier>, verifier: Pubkey) -> Result<()> {
+        let platform = &mut ctx.accounts.platform;
+        platform.verifier = verifier;
+        
+        msg!("平台驗證者已設定: {}", verifier);
+        Ok(())
+    }
+
+    /// 建立地區租金區間
+    pub fn create_price_band(
+        ctx: Context<CreatePriceBand>,
+        region_code: u32,
+        currency: FiatCurrency,
+        min_price_per_ping: u64,
+        max_price_per_ping: u64,
+    ) -> Result<()> {
+        require!(
+            min_price_per_ping <= max_price_per_ping,
+            OracleError::InvalidPriceBand
+        );
+        
+        let price_band = &mut ctx.accounts.price_band;
+        price_band.region_code = region_code;
+        price_band.currency = currency;
+        price_band.min_price_per_ping = min_price_per_ping;
+        price_band.max_price_per_ping = max_price_per_ping;
+        price_band.updated_by = ctx.accounts.verifier.key();
+        price_band.updated_at = Clock::get()?.unix_timestamp;
+        price_band.bump = ctx.bumps.price_band;
+        
+        msg!("地區租金區間已建立: {}", region_code);
+        Ok(())
+    }
+
+    /// 更新地區租金區間
+    pub fn update_price_band(
+        ctx: Context<UpdatePriceBand>,
+        currency: FiatCurrency,
+        min_price_per_ping: u64,
+        max_price_per_ping: u64,
+    ) -> Result<()> {
+        require!(
+            min_price_per_ping <= max_price_per_ping,
+            OracleError::InvalidPriceBand
+        );
+        
+        let price_band = &mut ctx.accounts.price_band;
+        price_band.currency = currency;
+        price_band.min_price_per_ping = min_price_per_ping;
+        price_band.max_price_per_ping = max_price_per_ping;
+        price_band.updated_by = ctx.accounts.verifier.key();
+        price_band.updated_at = Clock::get()?.unix_timestamp;
+        
+        msg!("地區租金區間已更新: {}", price_band.region_code);
+        Ok(())
+    }
+
+    /// 刪除地區租金區間
+    pub fn close_price_band(ctx: Context<ClosePriceBand>) -> Result<()> {
+        msg!("地區租金區間已刪除: {}", ctx.accounts.price_band.region_code);
+        Ok(())
+    }
+
+    /// 設定 KYC 要求 (僅限平台管理員)
+    pub fn configure_kyc_policy(
+        ctx: Context<ConfigureKycPolicy>,
+        require_landlord: bool,
+        require_tenant: bool,
+        min_level: u8,
+    ) -> Result<()> {
+        require!(min_level <= MAX_KYC_LEVEL, RentalError::InvalidKycAttestation);
+        
+        ctx.accounts.platform.kyc_policy = KycPolicy {
+            require_landlord,
+            require_tenant,
+            min_level,
+        };
+        
+        msg!("KYC 要求已更新，房東: {}，租客: {}，最低等級: {}", require_landlord, require_tenant, min_level);
+        Ok(())
+    }
+
+    /// 新增認證機構 (僅限平台管理員)
+    pub fn add_attester(ctx: Context<AddAttester>, attester_key: Pubkey) -> Result<()> {
+        let attester = &mut ctx.accounts.attester;
+        attester.attester = attester_key;
+        attester.added_by = ctx.accounts.authority.key();
+        attester.added_at = Clock::get()?.unix_timestamp;
+        attester.bump = ctx.bumps.attester;
+        
+        msg!("認證機構已新增: {}", attester_key);
+        Ok(())
+    }
+
+    /// 移除認證機構 (已簽發的認證於到期前仍有效，可另行撤銷)
+    pub fn remove_attester(ctx: Context<RemoveAttester>) -> Result<()> {
+        msg!("認證機構已移除: {}", ctx.accounts.attester.attester);
+        Ok(())
+    }
+
+    /// 建立房東身分檔案
+    pub fn create_landlord_profile(ctx: Context<CreateLandlordProfile>) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        let current_time = Clock::get()?.unix_timestamp;
+        profile.wallet = ctx.accounts.wallet.key();
+        profile.attestation = None;
+        profile.created_at = current_time;
+        profile.updated_at = current_time;
+        profile.bump = ctx.bumps.profile;
+        
+        msg!("房東身分檔案已建立: {}", profile.wallet);
+        Ok(())
+    }
+
+    /// 建立租客身分檔案
+    pub fn create_tenant_profile(ctx: Context<CreateTenantProfile>) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        let current_time = Clock::get()?.unix_timestamp;
+        profile.wallet = ctx.accounts.wallet.key();
+        profile.reputation_score = INITIAL_REPUTATION_SCORE;
+        profile.attestation = None;
+        profile.on_time_payments = 0;
+        profile.late_payments = 0;
+        profile.tenant_terminations = 0;
+        profile.landlord_terminations = 0;
+        profile.deposits_settled = 0;
+        profile.deposit_deduction_bps = 0;
+        profile.created_at = current_time;
+        profile.updated_at = current_time;
+        profile.bump = ctx.bumps.profile;
+        
+        msg!("租客身分檔案已建立: {}", profile.wallet);
+        Ok(())
+    }
+
+    /// 認證機構簽發房東 KYC 認證
+    pub fn attest_landlord(
+        ctx: Context<AttestLandlord>,
+        level: u8,
+        expires_at: i64,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let profile = &mut ctx.accounts.profile;
+        profile.attestation = Some(KycAttestation::new(
+            ctx.accounts.issuer.key(),
+            level,
+            expires_at,
+            evidence_hash,
+            current_time,
+        )?);
+        profile.updated_at = current_time;
+        
+        msg!("房東 KYC 認證已簽發: {}，等級: {}", profile.wallet, level);
+        Ok(())
+    }
+
+    /// 認證機構簽發租客 KYC 認證
+    pub fn attest_tenant(
+        ctx: Context<AttestTenant>,
+        level: u8,
+        expires_at: i64,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let profile = &mut ctx.accounts.profile;
+        profile.attestation = Some(KycAttestation::new(
+            ctx.accounts.issuer.key(),
+            level,
+            expires_at,
+            evidence_hash,
+            current_time,
+        )?);
+        profile.updated_at = current_time;
+        
+        msg!("租客 KYC 認證已簽發: {}，等級: {}", profile.wallet, level);
+        Ok(())
+    }
+
+    /// 撤銷房東 KYC 認證 (簽發機構或平台管理員)
+    pub fn revoke_landlord_attestation(ctx: Context<RevokeLandlordAttestation>) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        let authority = ctx.accounts.authority.key();
+        require!(
+            authority == ctx.accounts.platform.authority
+                || matches!(&profile.attestation, Some(attestation) if attestation.issuer == authority),
+            RentalError::Unauthorized
+        );
+        
+        profile.attestation = None;
+        profile.updated_at = Clock::get()?.unix_timestamp;
+        
+        msg!("房東 KYC 認證已撤銷: {}", profile.wallet);
+        Ok(())
+    }
+
+    /// 撤銷租客 KYC 認證 (簽發機構或平台管理員)
+    pub fn revoke_tenant_attestation(ctx: Context<RevokeTenantAttestation>) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        let authority = ctx.accounts.authority.key();
+        require!(
+            authority == ctx.accounts.platform.authority
+                || matches!(&profile.attestation, Some(attestation) if attestation.issuer == authority),
+            RentalError::Unauthorized
+        );
+        
+        profile.attestation = None;
+        profile.updated_at = Clock::get()?.unix_timestamp;
+        
+        msg!("租客 KYC 認證已撤銷: {}", profile.wallet);
+        Ok(())
+    }
+
+    /// 建立多單位建物，各單位以 `create_listing` 帶入建物帳戶上架並共用地址與公共設施
+    pub fn create_property(
+        ctx: Context<CreateProperty>,
+        property_id: u64,
+        name: String,
+        address: Address,
+        coordinates: Coordinates,
+        total_floors: u8,
+        amenities: Amenities,
+    ) -> Result<()> {
+        InputValidator::validate_listing_text(&name, "")?;
+        InputValidator::validate_address(&address)?;
+        InputValidator::validate_amenities(&amenities, &[])?;
+        
+        require!(
+            OracleValidator::verify_address(&address),
+            OracleError::LocationVerificationFailed
+        );
+        require!(
+            OracleValidator::verify_coordinates(&coordinates),
+            OracleError::CoordinatesVerificationFailed
+        );
+        require!(
+            total_floors > 0 && total_floors <= 100,
+            OracleError::ListingInfoVerificationFailed
+        );
+        
+        let current_time = Clock::get()?.unix_timestamp;
+        let property = &mut ctx.accounts.property;
+        property.authority = ctx.accounts.authority.key();
+        property.property_id = property_id;
+        property.name = name;
+        // 建物地址不含樓層與單位，由各單位房源記錄
+        property.address = Address {
+            floor: 0,
+            unit: None,
+            ..address
+        };
+        property.coordinates = coordinates;
+        property.total_floors = total_floors;
+        property.amenities = amenities;
+        property.total_units = 0;
+        property.occupied_units = 0;
+        property.top_unit_floor = 0;
+        property.bump = ctx.bumps.property;
+        property.created_at = current_time;
+        property.updated_at = current_time;
+        
+        msg!("建物已建立: {}", property.name);
+        Ok(())
+    }
+
+    /// 更新建物名稱、總樓層與公共設施 (已上架單位的設施不會自動更新)
+    pub fn update_property(
+        ctx: Context<UpdateProperty>,
+        name: String,
+        total_floors: u8,
+        amenities: Amenities,
+    ) -> Result<()> {
+        InputValidator::validate_listing_text(&name, "")?;
+        InputValidator::validate_amenities(&amenities, &[])?;
+        
+        let property = &mut ctx.accounts.property;
+        property.set_total_floors(total_floors)?;
+        property.name = name;
+        property.amenities = amenities;
+        property.updated_at = Clock::get()?.unix_timestamp;
+        
+        msg!("建物已更新: {}，出租率: {} bps", property.name, property.occupancy_bps());
+        Ok(())
+    }
+
     /// 上架房源
     pub fn create_listing(
         ctx: Context<CreateListing>,
         title: String,
         description: String,
-        location: String,
+        metadata: Option<MetadataAnchor>,
+        address: Address,
+        coordinates: Coordinates,
+        payment_method: PaymentMethod,
         price: u64,
         deposit: u64,
         size: u32,
         rooms: u8,
         bathrooms: u8,
-        floor: u8,
         total_floors: u8,
         contract_length: u8,
         move_in_date: i64,
-        amenities: Vec<String>,
+        amenities: Amenities,
+        custom_tags: Vec<String>,
+        fiat_price: Option<FiatPrice>,
     ) -> Result<()> {
         let listing = &mut ctx.accounts.listing;
         let platform = &mut ctx.accounts.platform;
         
+        // 驗證字串參數
+        InputValidator::validate_listing_text(&title, &description)?;
+        InputValidator::validate_metadata(metadata.as_ref())?;
+        InputValidator::validate_address(&address)?;
+        InputValidator::validate_amenities(&amenities, &custom_tags)?;
+        
+        // 依平台設定檢查房東 KYC 認證
+        platform.kyc_policy.enforce(
+            platform.kyc_policy.require_landlord,
+            ctx.accounts
+                .landlord_profile
+                .as_ref()
+                .and_then(|profile| profile.attestation.as_ref()),
+            Clock::get()?.unix_timestamp,
+        )?;
+        
+        // 中繼資料模式下完整描述存放於鏈下文件，鏈上描述須留空
+        if let Some(metadata) = &metadata {
+            require!(
+                metadata.is_valid() && description.is_empty(),
+                RentalError::InvalidMetadata
+            );
+        }
+        
         // 預言機驗證
         require!(
-            OracleValidator::verify_location(&location),
+            OracleValidator::verify_address(&address),
             OracleError::LocationVerificationFailed
         );
         
         require!(
-            OracleValidator::verify_price(price, &location),
-            OracleError::PriceVerificationFailed
+            OracleValidator::verify_coordinates(&coordinates),
+            OracleError::CoordinatesVerificationFailed
         );
         
         require!(
-            OracleValidator::verify_listing_info(size, rooms, bathrooms, floor, total_floors),
+            OracleValidator::verify_listing_info(size, rooms, bathrooms, address.floor, total_floors),
             OracleError::ListingInfoVerificationFailed
         );
         
+        // 建物單位：地址與樓層須與建物一致，並併入建物公共設施
+        let mut amenities = amenities;
+        if let Some(property) = ctx.accounts.property.as_mut() {
+            property.check_unit(&address, total_floors)?;
+            amenities.bits |= property.amenities.bits;
+            property.add_unit(address.floor);
+            property.updated_at = Clock::get()?.unix_timestamp;
+        }
+        
+        // 依地區租金區間驗證每坪租金，價格先換算為區間的計價幣別
+        let price_band = &ctx.accounts.price_band;
+        require!(
+            price_band.region_code == address.district_code
+                || price_band.region_code == address.county_code,
+            OracleError::PriceBandMismatch
+        );
+        
+        let current_time = Clock::get()?.unix_timestamp;
+        let (sol_usd_rate, usd_twd_rate) = PriceFeedValidator::load_rent_quotes(
+            &payment_method,
+            fiat_price.as_ref(),
+            price_band.currency,
+            ctx.accounts.sol_usd_price_feed.as_deref(),
+            ctx.accounts.usd_twd_price_feed.as_deref(),
+            platform,
+            current_time,
+        )?;
+        let monthly_rent = PriceFeedValidator::rent_to_fiat(
+            price,
+            &payment_method,
+            fiat_price.as_ref(),
+            price_band.currency,
+            sol_usd_rate.as_ref(),
+            usd_twd_rate.as_ref(),
+        )?;
+        let monthly_rent_twd = PriceFeedValidator::rent_to_fiat(
+            price,
+            &payment_method,
+            fiat_price.as_ref(),
+            FiatCurrency::Twd,
+            sol_usd_rate.as_ref(),
+            usd_twd_rate.as_ref(),
+        )?;
+        
+        require!(
+            OracleValidator::verify_price(monthly_rent, size, price_band),
+            OracleError::PriceVerificationFailed
+        );
+        
         listing.authority = ctx.accounts.authority.key();
         listing.bump = *ctx.bumps.get("listing").unwrap();
         listing.title = title;
         listing.description = description;
-        listing.location = location;
+        listing.metadata = metadata;
+        listing.location = address.format();
+        listing.floor = address.floor;
+        listing.address = address;
+        listing.coordinates = coordinates;
+        listing.geohash = coordinates.geohash();
+        listing.payment_method = payment_method;
         listing.price = price;
         listing.deposit = deposit;
         listing.size = size;
         listing.rooms = rooms;
         listing.bathrooms = bathrooms;
-        listing.floor = floor;
         listing.total_floors = total_floors;
         listing.contract_length = contract_length;
         listing.move_in_date = move_in_date;
         listing.amenities = amenities;
+        listing.custom_tags = custom_tags;
+        listing.fiat_price = fiat_price;
+        listing.price_bucket = ListingIndex::price_bucket(monthly_rent_twd);
+        listing.min_tenant_score = 0;
+        listing.requires_application = false;
+        listing.offer_nonce = 0;
+        listing.owners = Vec::new();
+        listing.fee_delegation = Pubkey::default();
+        listing.property = ctx.accounts.property.as_ref().map_or(Pubkey::default(), |property| property.key());
+        listing.room_units = Vec::new();
+        listing.short_stay = None;
+        listing.reservation_fee = 0;
+        listing.reservation_period = 0;
+        listing.reserved_by = Pubkey::default();
+        listing.reserved_until = 0;
+        listing.cancellation_policy = CancellationPolicy::default();
+        listing.maintenance_abatement_bps = 0;
         listing.is_available = true;
         listing.is_verified = false;
         listing.created_at = Clock::get()?.unix_timestamp;
         listing.updated_at = Clock::get()?.unix_timestamp;
         
+        // 建立 geohash 索引，供前端以 memcmp 查詢同一格網內的房源
+        let geo_index = &mut ctx.accounts.geo_index;
+        geo_index.geohash = listing.geohash;
+        geo_index.listing = listing.key();
+        geo_index.coordinates = coordinates;
+        geo_index.bump = ctx.bumps.geo_index;
+        
+        // 加入行政區與價格分桶索引
+        ctx.accounts.district_index.insert(
+            IndexKind::District,
+            listing.address.district_code,
+            listing.key(),
+        )?;
+        ctx.accounts.price_index.insert(
+            IndexKind::PriceBucket,
+            listing.price_bucket,
+            listing.key(),
+        )?;
+        
         platform.total_listings += 1;
         
         msg!("房源已上架並通過預言機驗證: {}", listing.title);
         Ok(())
     }
 
-    /// 租用房源 (SOL 支付)
-    pub fn rent_property_sol(
-        ctx: Context<RentPropertySol>,
-        rental_id: u64,
+    /// 更新房源租金條件與設施配備
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_listing(
+        ctx: Context<UpdateListing>,
+        price: u64,
+        deposit: u64,
+        contract_length: u8,
+        move_in_date: i64,
+        amenities: Amenities,
+        custom_tags: Vec<String>,
+        fiat_price: Option<FiatPrice>,
     ) -> Result<()> {
-        let rental = &mut ctx.accounts.rental;
         let listing = &mut ctx.accounts.listing;
-        let platform = &mut ctx.accounts.platform;
-        
-        // 檢查房源是否可用
-        require!(listing.is_available, RentalError::PropertyNotAvailable);
+        let platform = &ctx.accounts.platform;
+        let price_band = &ctx.accounts.price_band;
+        let current_time = Clock::get()?.unix_timestamp;
         
-        // 檢查租客是否有足夠的 SOL 支付押金和首月租金
-        let total_amount = listing.deposit + listing.price;
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::UPDATE_LISTING,
+            current_time,
+        )?;
+        InputValidator::validate_amenities(&amenities, &custom_tags)?;
+        // 押金不得低於已設定的預留費用 (預留費用抵付押金)
         require!(
-            ctx.accounts.tenant.to_account_info().lamports() >= total_amount,
-            RentalError::InsufficientFunds
+            reservation::is_valid_terms(listing.reservation_fee, listing.reservation_period, deposit),
+            RentalError::InvalidReservation
         );
         
-        // 轉移押金和首月租金到房東
-        let transfer_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.tenant.to_account_info(),
-                to: ctx.accounts.landlord.to_account_info(),
-                authority: ctx.accounts.tenant.to_account_info(),
-            },
+        // 重新依地區租金區間驗證租金
+        require!(
+            price_band.region_code == listing.address.district_code
+                || price_band.region_code == listing.address.county_code,
+            OracleError::PriceBandMismatch
         );
         
-        token::transfer(transfer_ctx, total_amount)?;
+        let (sol_usd_rate, usd_twd_rate) = PriceFeedValidator::load_rent_quotes(
+            &listing.payment_method,
+            fiat_price.as_ref(),
+            price_band.currency,
+            ctx.accounts.sol_usd_price_feed.as_deref(),
+            ctx.accounts.usd_twd_price_feed.as_deref(),
+            platform,
+            current_time,
+        )?;
+        let monthly_rent = PriceFeedValidator::rent_to_fiat(
+            price,
+            &listing.payment_method,
+            fiat_price.as_ref(),
+            price_band.currency,
+            sol_usd_rate.as_ref(),
+            usd_twd_rate.as_ref(),
+        )?;
+        let monthly_rent_twd = PriceFeedValidator::rent_to_fiat(
+            price,
+            &listing.payment_method,
+            fiat_price.as_ref(),
+            FiatCurrency::Twd,
+            sol_usd_rate.as_ref(),
+            usd_twd_rate.as_ref(),
+        )?;
         
-        // 創建租約
-        rental.rental_id = rental_id;
-        rental.listing = ctx.accounts.listing.key();
-        rental.landlord = ctx.accounts.landlord.key();
-        rental.tenant = ctx.accounts.tenant.key();
-        rental.price = listing.price;
-        rental.deposit = listing.deposit;
-        rental.contract_length = listing.contract_length;
-        rental.start_date = Clock::get()?.unix_timestamp;
-        rental.end_date = Clock::get()?.unix_timestamp + (listing.contract_length as i64 * 30 * 24 * 60 * 60);
-        rental.next_payment_date = Clock::get()?.unix_timestamp + (30 * 24 * 60 * 60);
-        rental.status = RentalStatus::Active;
-        rental.payment_method = PaymentMethod::Sol;
-        rental.bump = *ctx.bumps.get("rental").unwrap();
-        rental.created_at = Clock::get()?.unix_timestamp;
+        require!(
+            OracleValidator::verify_price(monthly_rent, listing.size, price_band),
+            OracleError::PriceVerificationFailed
+        );
         
-        // 更新房源狀態
-        listing.is_available = false;
-        listing.updated_at = Clock::get()?.unix_timestamp;
+        // 可租房源的價格分桶改變時，移至新的價格索引
+        let price_bucket = ListingIndex::price_bucket(monthly_rent_twd);
+        if listing.is_available && price_bucket != listing.price_bucket {
+            let price_index = ctx
+                .accounts
+                .price_index
+                .as_mut()
+                .ok_or(RentalError::ListingIndexMismatch)?;
+            price_index.remove(IndexKind::PriceBucket, listing.price_bucket, &listing.key())?;
+            
+            let new_price_index = ctx
+                .accounts
+                .new_price_index
+                .as_mut()
+                .ok_or(RentalError::ListingIndexMismatch)?;
+            new_price_index.insert(IndexKind::PriceBucket, price_bucket, listing.key())?;
+        }
         
-        platform.total_rentals += 1;
-        platform.total_volume += total_amount;
+        listing.price = price;
+        listing.deposit = deposit;
+        listing.contract_length = contract_length;
+        listing.move_in_date = move_in_date;
+        listing.amenities = amenities;
+        listing.custom_tags = custom_tags;
+        listing.fiat_price = fiat_price;
+        listing.price_bucket = price_bucket;
+        listing.updated_at = current_time;
         
-        msg!("租約已創建 (SOL 支付)，租約 ID: {}", rental_id);
+        msg!("房源已更新: {}", listing.title);
         Ok(())
     }
 
-    /// 租用房源 (USDT 支付)
-    pub fn rent_property_usdt(
-        ctx: Context<RentPropertyUsdt>,
-        rental_id: u64,
+    /// 切換或更新房源中繼資料 (帳戶空間依新內容調整)
+    pub fn update_listing_metadata(
+        ctx: Context<UpdateListingMetadata>,
+        description: String,
+        metadata: Option<MetadataAnchor>,
     ) -> Result<()> {
-        let rental = &mut ctx.accounts.rental;
+        InputValidator::validate_description(&description)?;
+        InputValidator::validate_metadata(metadata.as_ref())?;
+        if let Some(metadata) = &metadata {
+            require!(
+                metadata.is_valid() && description.is_empty(),
+                RentalError::InvalidMetadata
+            );
+        }
+        
         let listing = &mut ctx.accounts.listing;
-        let platform = &mut ctx.accounts.platform;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::UPDATE_LISTING,
+            current_time,
+        )?;
+        // 帳戶縮小時退還的租金歸簽署人，代管人不得縮小房東出資的帳戶空間
+        if ctx.accounts.authority.key() != listing.authority {
+            require!(
+                Listing::space(&description, metadata.as_ref())
+                    >= Listing::space(&listing.description, listing.metadata.as_ref()),
+                RentalError::PermissionDenied
+            );
+        }
         
-        // 檢查房源是否可用
-        require!(listing.is_available, RentalError::PropertyNotAvailable);
+        listing.description = description;
+        listing.metadata = metadata;
+        listing.updated_at = current_time;
         
-        // 檢查租客是否有足夠的 USDT
-        let total_amount = listing.deposit + listing.price;
+        msg!("房源中繼資料已更新: {}", listing.title);
+        Ok(())
+    }
+
+    /// 設定租客條件：最低信用分數 (0 表示不限制) 與是否須經申請審核
+    pub fn set_tenant_requirements(
+        ctx: Context<SetTenantRequirements>,
+        min_tenant_score: u16,
+        requires_application: bool,
+    ) -> Result<()> {
         require!(
-            ctx.accounts.tenant_token_account.amount >= total_amount,
-            RentalError::InsufficientFunds
-        );
-        
-        // 轉移 USDT 到房東
-        let transfer_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.tenant_token_account.to_account_info(),
-                to: ctx.accounts.landlord_token_account.to_account_info(),
-                authority: ctx.accounts.tenant.to_account_info(),
-            },
+            min_tenant_score <= reputation::MAX_REPUTATION_SCORE,
+            RentalError::TenantScoreTooLow
         );
         
-        token::transfer(transfer_ctx, total_amount)?;
-        
-        // 創建租約
-        rental.rental_id = rental_id;
-        rental.listing = ctx.accounts.listing.key();
-        rental.landlord = ctx.accounts.landlord.key();
-        rental.tenant = ctx.accounts.tenant.key();
-        rental.price = listing.price;
-        rental.deposit = listing.deposit;
-        rental.contract_length = listing.contract_length;
-        rental.start_date = Clock::get()?.unix_timestamp;
-        rental.end_date = Clock::get()?.unix_timestamp + (listing.contract_length as i64 * 30 * 24 * 60 * 60);
-        rental.next_payment_date = Clock::get()?.unix_timestamp + (30 * 24 * 60 * 60);
-        rental.status = RentalStatus::Active;
-        rental.payment_method = PaymentMethod::Usdt;
-        rental.bump = *ctx.bumps.get("rental").unwrap();
-        rental.created_at = Clock::get()?.unix_timestamp;
-        
-        // 更新房源狀態
-        listing.is_available = false;
-        listing.updated_at = Clock::get()?.unix_timestamp;
+        let listing = &mut ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::UPDATE_LISTING,
+            current_time,
+        )?;
         
-        platform.total_rentals += 1;
-        platform.total_volume += total_amount;
+        listing.min_tenant_score = min_tenant_score;
+        listing.requires_application = requires_application;
+        listing.updated_at = current_time;
         
-        msg!("租約已創建 (USDT 支付)，租約 ID: {}", rental_id);
+        msg!("租客條件已設定，最低信用分數: {}，須經申請: {}", min_tenant_score, requires_application);
         Ok(())
     }
 
-    /// 支付租金 (SOL)
-    pub fn pay_rent_sol(ctx: Context<PayRentSol>) -> Result<()> {
-        let rental = &mut ctx.accounts.rental;
-        let platform = &mut ctx.accounts.platform;
-        
-        // 檢查租約狀態
-        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
-        require!(rental.payment_method == PaymentMethod::Sol, RentalError::InvalidPaymentMethod);
-        
-        // 檢查是否到了付款時間
+    /// 設定預留費用與保留期間 (皆為 0 表示不開放預留)
+    pub fn set_reservation_terms(ctx: Context<SetReservationTerms>, fee: u64, period: i64) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
         let current_time = Clock::get()?.unix_timestamp;
-        require!(current_time >= rental.next_payment_date, RentalError::PaymentNotDue);
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::UPDATE_LISTING,
+            current_time,
+        )?;
         
-        // 檢查租客是否有足夠的 SOL
         require!(
-            ctx.accounts.tenant.to_account_info().lamports() >= rental.price,
-            RentalError::InsufficientFunds
+            reservation::is_valid_terms(fee, period, listing.deposit),
+            RentalError::InvalidReservation
         );
+        listing.reservation_fee = fee;
+        listing.reservation_period = period;
+        listing.updated_at = current_time;
         
-        // 轉移租金到房東
-        let transfer_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.tenant.to_account_info(),
-                to: ctx.accounts.landlord.to_account_info(),
-                authority: ctx.accounts.tenant.to_account_info(),
-            },
-        );
+        msg!("預留條件已設定，費用: {}，保留期間: {} 秒", fee, period);
+        Ok(())
+    }
+
+    /// 設定起租前取消的退款規則 (僅適用之後建立的租約)
+    pub fn set_cancellation_policy(
+        ctx: Context<SetCancellationPolicy>,
+        policy: CancellationPolicy,
+    ) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::UPDATE_LISTING,
+            current_time,
+        )?;
         
-        token::transfer(transfer_ctx, rental.price)?;
+        require!(policy.is_valid(), RentalError::InvalidCancellationPolicy);
+        listing.cancellation_policy = policy;
+        listing.updated_at = current_time;
         
-        // 更新下次付款日期
-        rental.next_payment_date += 30 * 24 * 60 * 60; // 30 天後
-        rental.updated_at = current_time;
+        msg!(
+            "取消退款規則已設定，全額退款期限: {} 秒，逾期退款: {} 基點",
+            policy.full_refund_notice,
+            policy.late_refund_bps
+        );
+        Ok(())
+    }
+
+    /// 設定報修逾期的租金減免比例 (僅適用之後建立的租約)，0 表示不提供減免
+    pub fn set_maintenance_abatement(ctx: Context<SetMaintenanceAbatement>, abatement_bps: u16) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::UPDATE_LISTING,
+            current_time,
+        )?;
         
-        platform.total_volume += rental.price;
+        require!(abatement_bps <= MAX_ABATEMENT_BPS, RentalError::InvalidAbatement);
+        listing.maintenance_abatement_bps = abatement_bps;
+        listing.updated_at = current_time;
         
-        msg!("租金已支付 (SOL)，下次付款日期: {}", rental.next_payment_date);
+        msg!("報修逾期租金減免已設定: {} 基點", abatement_bps);
         Ok(())
     }
 
-    /// 支付租金 (USDT)
-    pub fn pay_rent_usdt(ctx: Context<PayRentUsdt>) -> Result<()> {
-        let rental = &mut ctx.accounts.rental;
-        let platform = &mut ctx.accounts.platform;
-        
-        // 檢查租約狀態
-        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
-        require!(rental.payment_method == PaymentMethod::Usdt, RentalError::InvalidPaymentMethod);
+    /// 作廢 nonce 不大於指定值的所有離線要約
+    pub fn cancel_offers(ctx: Context<CancelOffers>, nonce: u64) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::UPDATE_LISTING,
+            current_time,
+        )?;
         
-        // 檢查是否到了付款時間
+        require!(nonce > listing.offer_nonce, RentalError::OfferNonceUsed);
+        listing.offer_nonce = nonce;
+        listing.updated_at = current_time;
+        
+        msg!("已作廢 nonce {} 以前的要約", nonce);
+        Ok(())
+    }
+
+    /// 設定分租房間 (房間編號、坪數、租金、押金與衛浴)，傳入空清單即恢復為整層出租
+    ///
+    /// 須於房源可租且無房間出租中時設定。
+    pub fn configure_rooms(ctx: Context<ConfigureRooms>, rooms: Vec<RoomUnit>) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
         let current_time = Clock::get()?.unix_timestamp;
-        require!(current_time >= rental.next_payment_date, RentalError::PaymentNotDue);
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::UPDATE_LISTING,
+            current_time,
+        )?;
         
-        // 檢查租客是否有足夠的 USDT
         require!(
-            ctx.accounts.tenant_token_account.amount >= rental.price,
-            RentalError::InsufficientFunds
+            listing.is_available && !listing.has_occupied_rooms(),
+            RentalError::PropertyNotAvailable
         );
+        rooms::validate_rooms(&rooms, listing.size)?;
+        
+        listing.room_units = rooms
+            .into_iter()
+            .map(|room| RoomUnit {
+                rental: Pubkey::default(),
+                ..room
+            })
+            .collect();
+        listing.updated_at = current_time;
+        
+        msg!("分租房間已設定，房間數: {}", listing.room_units.len());
+        Ok(())
+    }
+
+    /// 開啟短租模式並建立可預訂日曆 (僅限 SOL 計價房源)
+    ///
+    /// 短租模式下房源改以 `book_stay` 按晚預訂，不接受長期租約。
+    pub fn enable_short_stay(ctx: Context<EnableShortStay>, terms: ShortStayTerms) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::UPDATE_LISTING,
+            current_time,
+        )?;
         
-        // 轉移 USDT 到房東
-        let transfer_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.tenant_token_account.to_account_info(),
-                to: ctx.accounts.landlord_token_account.to_account_info(),
-                authority: ctx.accounts.tenant.to_account_info(),
-            },
+        require!(
+            listing.is_available && !listing.has_occupied_rooms(),
+            RentalError::PropertyNotAvailable
         );
+        require!(!listing.is_reserved(current_time), RentalError::ListingReserved);
+        require!(listing.payment_method == PaymentMethod::Sol, RentalError::InvalidPaymentMethod);
+        require!(terms.is_valid(), RentalError::InvalidStayTerms);
         
-        token::transfer(transfer_ctx, rental.price)?;
+        let calendar = &mut ctx.accounts.calendar;
+        calendar.listing = listing.key();
+        calendar.start_day = day_of(current_time);
+        calendar.booked = [0; CALENDAR_BYTES];
+        calendar.bump = ctx.bumps.calendar;
         
-        // 更新下次付款日期
-        rental.next_payment_date += 30 * 24 * 60 * 60; // 30 天後
-        rental.updated_at = current_time;
+        listing.short_stay = Some(terms);
+        listing.updated_at = current_time;
+        
+        msg!("短租模式已開啟，每晚價格: {}", terms.nightly_price);
+        Ok(())
+    }
+
+    /// 調整短租每晚價格與晚數限制 (不影響已預訂的住宿)
+    pub fn update_short_stay(ctx: Context<UpdateShortStay>, terms: ShortStayTerms) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::UPDATE_LISTING,
+            current_time,
+        )?;
         
-        platform.total_volume += rental.price;
+        require!(listing.short_stay.is_some(), RentalError::ShortStayNotEnabled);
+        require!(terms.is_valid(), RentalError::InvalidStayTerms);
+        listing.short_stay = Some(terms);
+        listing.updated_at = current_time;
         
-        msg!("租金已支付 (USDT)，下次付款日期: {}", rental.next_payment_date);
+        msg!("短租條件已更新，每晚價格: {}", terms.nightly_price);
         Ok(())
     }
 
-    /// 終止租約
-    pub fn terminate_rental(ctx: Context<TerminateRental>) -> Result<()> {
-        let rental = &mut ctx.accounts.rental;
+    /// 關閉短租模式並關閉日曆 (帳戶租金退還房東)，須已無今日起的預訂
+    pub fn disable_short_stay(ctx: Context<DisableShortStay>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &ctx.accounts.listing.key(),
+            &ctx.accounts.listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::UPDATE_LISTING,
+            current_time,
+        )?;
+        
+        let calendar = &mut ctx.accounts.calendar;
+        calendar.advance(day_of(current_time));
+        require!(calendar.is_clear(), RentalError::StayUnavailable);
+        
         let listing = &mut ctx.accounts.listing;
+        listing.short_stay = None;
+        listing.updated_at = current_time;
         
-        // 檢查租約狀態
-        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        msg!("短租模式已關閉");
+        Ok(())
+    }
+
+    /// 預訂短租住宿：檢查日曆無重疊後，將住宿費用託管於預訂帳戶
+    ///
+    /// `check_in_day` 為入住日 (UTC 日，`unix_timestamp / 86400`)，住宿 `nights` 晚。
+    pub fn book_stay(ctx: Context<BookStay>, check_in_day: u32, nights: u16) -> Result<()> {
+        let listing = &ctx.accounts.listing;
+        let terms = listing.short_stay.ok_or(RentalError::ShortStayNotEnabled)?;
+        let current_time = Clock::get()?.unix_timestamp;
+        let today = day_of(current_time);
+        
+        require!(check_in_day >= today, RentalError::InvalidStayDates);
+        let amount = terms.total_price(nights)?;
+        
+        let calendar = &mut ctx.accounts.calendar;
+        calendar.advance(today);
+        calendar.reserve(check_in_day, nights)?;
+        
+        // 住宿費用託管於預訂帳戶，入住後才交付房東
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.guest.to_account_info(),
+                    to: ctx.accounts.booking.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        
+        let booking = &mut ctx.accounts.booking;
+        booking.listing = listing.key();
+        booking.guest = ctx.accounts.guest.key();
+        booking.check_in_day = check_in_day;
+        booking.nights = nights;
+        booking.amount = amount;
+        booking.booked_at = current_time;
+        booking.bump = ctx.bumps.booking;
+        
+        emit!(StayBooked {
+            listing: listing.key(),
+            guest: booking.guest,
+            check_in_day,
+            nights,
+            amount,
+        });
+        
+        msg!("短租已預訂，入住日: {}，{} 晚，金額: {}", check_in_day, nights, amount);
+        Ok(())
+    }
+
+    /// 入住後將託管的住宿費用交付房東 (任何人皆可呼叫)，並關閉預訂帳戶
+    ///
+    /// 設有收費代管授權時先支付管理費；共有房源的收款帳戶依 `owners` 順序放入 remaining accounts。
+    pub fn release_stay_payment<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReleaseStayPayment<'info>>,
+    ) -> Result<()> {
+        let booking = &ctx.accounts.booking;
+        let listing = &ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
         
-        // 檢查是否為房東或租客
-        let current_user = ctx.accounts.authority.key();
         require!(
-            current_user == rental.landlord || current_user == rental.tenant,
-            RentalError::Unauthorized
+            current_time >= booking.check_in_day as i64 * SECONDS_PER_DAY,
+            RentalError::StayNotStarted
         );
         
-        // 如果租客提前終止，押金不退還
-        // 如果房東提前終止，需要退還押金和剩餘租金
-        if current_user == rental.landlord {
-            // 計算剩餘天數的租金
-            let current_time = Clock::get()?.unix_timestamp;
-            let remaining_days = (rental.end_date - current_time) / (24 * 60 * 60);
-            let refund_amount = rental.deposit + (rental.price * remaining_days as u64 / 30);
-            
-            // 根據支付方式進行退款
-            if rental.payment_method == PaymentMethod::Sol {
-                // SOL 退款
-                let transfer_ctx = CpiContext::new(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.landlord.to_account_info(),
-                        to: ctx.accounts.tenant.to_account_info(),
-                        authority: ctx.accounts.landlord.to_account_info(),
-                    },
-                );
-                token::transfer(transfer_ctx, refund_amount)?;
-            } else {
-                // USDT 退款
-                let transfer_ctx = CpiContext::new(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.landlord_token_account.to_account_info(),
-                        to: ctx.accounts.tenant_token_account.to_account_info(),
-                        authority: ctx.accounts.landlord.to_account_info(),
-                    },
-                );
-                token::transfer(transfer_ctx, refund_amount)?;
-            }
+        let amount = booking.amount;
+        let fee = listing.management_fee(
+            &listing.key(),
+            ctx.accounts.delegation.as_deref(),
+            amount,
+            current_time,
+        )?;
+        if fee > 0 {
+            let manager = ctx.accounts.manager.as_ref().ok_or(RentalError::InvalidDelegation)?;
+            escrow::release_lamports(&booking.to_account_info(), &manager.to_account_info(), fee)?;
+        }
+        if listing.is_co_owned() {
+            ownership::release_to_owners(
+                &booking.to_account_info(),
+                &listing.owners,
+                ctx.remaining_accounts,
+                amount - fee,
+            )?;
+        } else {
+            escrow::release_lamports(
+                &booking.to_account_info(),
+                &ctx.accounts.landlord.to_account_info(),
+                amount - fee,
+            )?;
         }
         
-        // 更新租約狀態
-        rental.status = RentalStatus::Terminated;
-        rental.updated_at = Clock::get()?.unix_timestamp;
+        msg!("短租費用已交付房東: {}", amount);
+        Ok(())
+    }
+
+    /// 入住前取消短租預訂：全額退還住客並重新開放日曆 (住客或房東、代管人皆可取消)
+    pub fn cancel_stay(ctx: Context<CancelStay>) -> Result<()> {
+        let booking = &ctx.accounts.booking;
+        let listing = &ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
         
-        // 重新開放房源
-        listing.is_available = true;
-        listing.updated_at = Clock::get()?.unix_timestamp;
+        if ctx.accounts.authority.key() != booking.guest {
+            Delegation::authorize(
+                &listing.key(),
+                &listing.authority,
+                &ctx.accounts.authority.key(),
+                ctx.accounts.delegation.as_deref(),
+                Delegation::MANAGE_TENANTS,
+                current_time,
+            )?;
+        }
+        require!(
+            current_time < booking.check_in_day as i64 * SECONDS_PER_DAY,
+            RentalError::StayAlreadyStarted
+        );
         
-        msg!("租約已終止");
+        ctx.accounts.calendar.release(booking.check_in_day, booking.nights);
+        
+        msg!("短租預訂已取消，退還: {}", booking.amount);
         Ok(())
     }
 
-    /// 驗證房源（預言機驗證）
-    pub fn verify_listing(ctx: Context<VerifyListing>) -> Result<()> {
+    /// 設定房源共有人與持分，須經現有持分過半數的共有人簽署同意
+    ///
+    /// 同意的共有人以簽署者身分放入 remaining accounts；未設定共有人時由房源管理者同意。
+    /// 傳入空清單即恢復為單一房東。
+    pub fn set_listing_owners(ctx: Context<SetListingOwners>, owners: Vec<OwnerShare>) -> Result<()> {
         let listing = &mut ctx.accounts.listing;
         
-        // 這裡可以整合 Switchboard 預言機來驗證房源資訊
-        // 目前簡單地標記為已驗證
-        listing.is_verified = true;
-        listing.updated_at = Clock::get()?.unix_timestamp;
+        if !owners.is_empty() {
+            ownership::validate_owners(&owners)?;
+        }
         
-        msg!("房源已通過預言機驗證");
+        let signers: Vec<Pubkey> = ctx
+            .remaining_accounts
+            .iter()
+            .filter(|info| info.is_signer)
+            .map(|info| info.key())
+            .collect();
+        let approved_bps = listing.consent_bps(&signers);
+        require!(
+            approved_bps as u32 * 2 > ownership::TOTAL_OWNER_BPS as u32,
+            RentalError::OwnerConsentRequired
+        );
+        
+        let current_time = Clock::get()?.unix_timestamp;
+        listing.owners = owners;
+        listing.updated_at = current_time;
+        
+        emit!(ListingOwnersUpdated {
+            listing: listing.key(),
+            owners: listing.owners.clone(),
+            approved_bps,
+            timestamp: current_time,
+        });
+        
+        msg!("房源共有人已更新，共有人數: {}", listing.owners.len());
         Ok(())
     }
 
-    /// 調整租約
-    pub fn adjust_rental(
-        ctx: Context<AdjustRental>,
-        new_price: u64,
-        new_end_date: i64,
-        reason: String,
+    /// 房東授權代管人管理房源，權限以 `Delegation` 的位元常數組合
+    ///
+    /// 授權收取管理費時，每期租金的 `fee_bps` 基點先付給代管人；同一房源僅能有一份收費授權。
+    pub fn grant_delegation(
+        ctx: Context<GrantDelegation>,
+        manager: Pubkey,
+        permissions: u8,
+        fee_bps: u16,
+        expires_at: i64,
     ) -> Result<()> {
-        let rental = &mut ctx.accounts.rental;
+        let listing = &mut ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
         
-        // 檢查操作權限（只有房東或租客可以調整）
         require!(
-            ctx.accounts.authority.key() == rental.landlord || 
-            ctx.accounts.authority.key() == rental.tenant,
-            RentalError::Unauthorized
+            manager != listing.authority
+                && Delegation::is_valid_grant(permissions, fee_bps, expires_at, current_time),
+            RentalError::InvalidDelegation
         );
         
-        // 檢查租約狀態
-        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        let delegation = &mut ctx.accounts.delegation;
+        if permissions & Delegation::COLLECT_FEE != 0 {
+            require!(
+                listing.fee_delegation == Pubkey::default(),
+                RentalError::InvalidDelegation
+            );
+            listing.fee_delegation = delegation.key();
+            listing.updated_at = current_time;
+        }
         
-        // 更新租約資訊
-        rental.price = new_price;
-        rental.end_date = new_end_date;
-        rental.updated_at = Clock::get()?.unix_timestamp;
+        delegation.listing = listing.key();
+        delegation.authority = listing.authority;
+        delegation.manager = manager;
+        delegation.permissions = permissions;
+        delegation.fee_bps = fee_bps;
+        delegation.expires_at = expires_at;
+        delegation.created_at = current_time;
+        delegation.bump = ctx.bumps.delegation;
         
-        // 發送調整事件
-        emit!(RentalAdjusted {
-            rental: rental.key(),
-            new_price,
-            new_end_date,
-            reason,
-            adjusted_by: ctx.accounts.authority.key(),
-            timestamp: Clock::get()?.unix_timestamp,
+        emit!(DelegationGranted {
+            delegation: delegation.key(),
+            listing: listing.key(),
+            manager,
+            permissions,
+            fee_bps,
+            expires_at,
+            timestamp: current_time,
         });
         
-        msg!("租約已調整，新租金: {}, 新結束日期: {}", new_price, new_end_date);
+        msg!("已授權代管人: {}，權限: {}", manager, permissions);
         Ok(())
     }
 
-    /// 續約
-    pub fn renew_rental(
-        ctx: Context<RenewRental>,
-        months: u8,
-        new_price: u64,
-        auto_renew: bool,
-    ) -> Result<()> {
-        let rental = &mut ctx.accounts.rental;
-        let platform = &mut ctx.accounts.platform;
+    /// 房東撤銷代管授權並取回帳戶租金
+    pub fn revoke_delegation(ctx: Context<RevokeDelegation>) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
+        let delegation = &ctx.accounts.delegation;
+        let current_time = Clock::get()?.unix_timestamp;
         
-        // 檢查操作權限（只有租客可以續約）
-        require!(
-            ctx.accounts.authority.key() == rental.tenant,
-            RentalError::Unauthorized
-        );
+        if listing.fee_delegation == delegation.key() {
+            listing.fee_delegation = Pubkey::default();
+            listing.updated_at = current_time;
+        }
         
-        // 檢查租約狀態
-        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        emit!(DelegationRevoked {
+            delegation: delegation.key(),
+            listing: listing.key(),
+            manager: delegation.manager,
+            timestamp: current_time,
+        });
         
-        // 計算新的結束日期
-        let new_end_date = rental.end_date + (months as i64 * 30 * 24 * 60 * 60);
+        msg!("已撤銷代管授權: {}", delegation.manager);
+        Ok(())
+    }
+
+    /// 新增房源文件 (照片、格局圖、權狀等)
+    pub fn add_listing_document(
+        ctx: Context<AddListingDocument>,
+        content_hash: [u8; 32],
+        kind: DocumentKind,
+        uri: String,
+        hash_algorithm: HashAlgorithm,
+    ) -> Result<()> {
+        InputValidator::validate_document(&uri, &content_hash)?;
         
-        // 更新租約資訊
-        rental.price = new_price;
-        rental.end_date = new_end_date;
-        rental.contract_length = months;
-        rental.updated_at = Clock::get()?.unix_timestamp;
+        let listing = &ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::UPDATE_LISTING,
+            current_time,
+        )?;
         
-        // 發送續約事件
-        emit!(RentalRenewed {
-            rental: rental.key(),
-            months,
-            new_price,
-            new_end_date,
-            auto_renew,
-            renewed_by: ctx.accounts.authority.key(),
-            timestamp: Clock::get()?.unix_timestamp,
-        });
+        let document = &mut ctx.accounts.document;
+        document.listing = listing.key();
+        document.kind = kind;
+        document.content_hash = content_hash;
+        document.hash_algorithm = hash_algorithm;
+        document.uri = uri;
+        document.uploader = ctx.accounts.authority.key();
+        document.uploaded_at = current_time;
+        document.is_verified = false;
+        document.verified_by = Pubkey::default();
+        document.verified_at = 0;
+        document.bump = ctx.bumps.document;
         
-        platform.total_volume += new_price * months as u64;
+        msg!("房源文件已新增: {}", document.uri);
+        Ok(())
+    }
+
+    /// 移除房源文件並退還租金
+    pub fn remove_listing_document(ctx: Context<RemoveListingDocument>) -> Result<()> {
+        let document = &ctx.accounts.document;
+        let listing = &mut ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::UPDATE_LISTING,
+            current_time,
+        )?;
         
-        msg!("租約已續約，期數: {} 個月, 新租金: {}", months, new_price);
+        // 移除已查核的權狀時，房源需重新驗證
+        if document.kind == DocumentKind::OwnershipCertificate && document.is_verified {
+            listing.is_verified = false;
+            listing.updated_at = current_time;
+        }
+        
+        msg!("房源文件已移除: {}", document.uri);
         Ok(())
     }
 
-    /// 轉讓租約
-    pub fn transfer_rental(
-        ctx: Context<TransferRental>,
-        new_tenant: Pubkey,
-        transfer_fee: u64,
+    /// 建立房源索引頁 (任何人皆可建立並支付租金)
+    pub fn create_listing_index(
+        ctx: Context<CreateListingIndex>,
+        kind: IndexKind,
+        key: u32,
+        page: u16,
     ) -> Result<()> {
-        let rental = &mut ctx.accounts.rental;
+        let listing_index = &mut ctx.accounts.listing_index;
+        listing_index.kind = kind;
+        listing_index.key = key;
+        listing_index.page = page;
+        listing_index.listings = Vec::new();
+        listing_index.bump = ctx.bumps.listing_index;
         
-        // 檢查操作權限（只有租客可以轉讓）
+        msg!("房源索引已建立: {:?} {} 第 {} 頁", kind, key, page);
+        Ok(())
+    }
+
+    /// 房東 (或代管人) 提出租約：記錄租約文件雜湊與版本，視為房東簽署
+    pub fn propose_lease(
+        ctx: Context<ProposeLease>,
+        tenant: Pubkey,
+        document_hash: [u8; 32],
+        version: u16,
+    ) -> Result<()> {
         require!(
-            ctx.accounts.authority.key() == rental.tenant,
-            RentalError::Unauthorized
+            version > 0 && document_hash != [0u8; 32],
+            RentalError::InvalidLease
         );
         
-        // 檢查租約狀態
-        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        let listing = &ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::MANAGE_TENANTS,
+            current_time,
+        )?;
         
-        // 檢查新租客不能是原租客
-        require!(new_tenant != rental.tenant, RentalError::InvalidTransfer);
+        let lease = &mut ctx.accounts.lease;
+        lease.listing = listing.key();
+        lease.landlord = listing.authority;
+        lease.tenant = tenant;
+        lease.document_hash = document_hash;
+        lease.version = version;
+        lease.landlord_signed_at = current_time;
+        lease.tenant_signed_at = 0;
+        lease.rental = Pubkey::default();
+        lease.bump = ctx.bumps.lease;
         
-        // 轉移租約所有權
-        let old_tenant = rental.tenant;
-        rental.tenant = new_tenant;
-        rental.updated_at = Clock::get()?.unix_timestamp;
+        msg!("租約已提出，版本: {}", version);
+        Ok(())
+    }
+
+    /// 租客簽署租約，須帶入相同的文件雜湊與版本
+    pub fn sign_lease(
+        ctx: Context<SignLease>,
+        document_hash: [u8; 32],
+        version: u16,
+    ) -> Result<()> {
+        let lease = &mut ctx.accounts.lease;
+        let current_time = Clock::get()?.unix_timestamp;
+        lease.sign_as_tenant(&document_hash, version, current_time)?;
         
-        // 發送轉讓事件
-        emit!(RentalTransferred {
-            rental: rental.key(),
-            old_tenant,
-            new_tenant,
-            transfer_fee,
-            transferred_by: ctx.accounts.authority.key(),
-            timestamp: Clock::get()?.unix_timestamp,
+        emit!(LeaseSigned {
+            lease: lease.key(),
+            listing: lease.listing,
+            landlord: lease.landlord,
+            tenant: lease.tenant,
+            document_hash,
+            version,
+            timestamp: current_time,
         });
         
-        msg!("租約已轉讓給新租客: {}", new_tenant);
+        msg!("租約已由雙方簽署，版本: {}", version);
         Ok(())
     }
 
-    /// 延期申請
-    pub fn extend_rental(
-        ctx: Context<ExtendRental>,
-        days: u8,
-        reason: String,
-    ) -> Result<()> {
-        let rental = &mut ctx.accounts.rental;
+    /// 預留房源：將預留費用託管於預留帳戶，保留期間內其他租客不可租用或申請 (僅限 SOL 計價房源)
+    ///
+    /// 保留期間內以 `rent_property_sol` 帶入預留帳戶即轉為租約，預留費用抵付押金。
+    pub fn reserve_listing(ctx: Context<ReserveListing>) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
         
-        // 檢查操作權限（只有租客可以申請延期）
         require!(
-            ctx.accounts.authority.key() == rental.tenant,
-            RentalError::Unauthorized
+            listing.is_available && !listing.has_occupied_rooms() && listing.short_stay.is_none(),
+            RentalError::PropertyNotAvailable
         );
+        require!(!listing.is_reserved(current_time), RentalError::ListingReserved);
+        require!(listing.payment_method == PaymentMethod::Sol, RentalError::InvalidPaymentMethod);
+        require!(listing.reservation_fee > 0, RentalError::InvalidReservation);
         
-        // 檢查租約狀態
-        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        // 預留費用託管於預留帳戶
+        let fee = listing.reservation_fee;
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.tenant.to_account_info(),
+                    to: ctx.accounts.reservation.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
         
-        // 檢查延期天數合理性
-        require!(days > 0 && days <= 60, RentalError::InvalidExtension);
+        let expires_at = current_time + listing.reservation_period;
+        let reservation = &mut ctx.accounts.reservation;
+        reservation.listing = listing.key();
+        reservation.tenant = ctx.accounts.tenant.key();
+        reservation.fee = fee;
+        reservation.expires_at = expires_at;
+        reservation.created_at = current_time;
+        reservation.bump = ctx.bumps.reservation;
         
-        // 計算新的結束日期
-        let new_end_date = rental.end_date + (days as i64 * 24 * 60 * 60);
+        listing.reserved_by = reservation.tenant;
+        listing.reserved_until = expires_at;
+        listing.updated_at = current_time;
         
-        // 更新租約資訊
-        rental.end_date = new_end_date;
-        rental.updated_at = Clock::get()?.unix_timestamp;
+        emit!(ReservationPlaced {
+            listing: listing.key(),
+            tenant: reservation.tenant,
+            fee,
+            expires_at,
+        });
         
-        // 發送延期事件
-        emit!(RentalExtended {
-            rental: rental.key(),
-            days,
-            new_end_date,
-            reason,
-            extended_by: ctx.accounts.authority.key(),
-            timestamp: Clock::get()?.unix_timestamp,
+        msg!("房源已預留至 {}，預留費用: {}", expires_at, fee);
+        Ok(())
+    }
+
+    /// 保留期間屆滿後將預留費用全額退還租客並解除保留 (任何人皆可呼叫)
+    pub fn release_reservation(ctx: Context<ReleaseReservation>) -> Result<()> {
+        let reservation = &ctx.accounts.reservation;
+        let listing = &mut ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(current_time >= reservation.expires_at, RentalError::ReservationActive);
+        if listing.reserved_until == reservation.expires_at {
+            listing.clear_reservation(&reservation.tenant);
+        }
+        
+        emit!(ReservationReleased {
+            listing: listing.key(),
+            tenant: reservation.tenant,
+            fee: reservation.fee,
         });
         
-        msg!("租約已延期 {} 天，新結束日期: {}", days, new_end_date);
+        msg!("預留已到期，退還預留費用: {}", reservation.fee);
         Ok(())
     }
-}
 
-#[derive(Accounts)]
+    /// 租用房源 (SOL 支付)
+    pub fn rent_property_sol<'info>(
+        ctx: Context<'_, '_, '_, 'info, RentPropertySol<'info>>,
+        rental_id: u64,
+        offer: Option<SignedOffer>,
+        room_id: Option<u8>,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental;
+        let listing = &mut ctx.accounts.listing;
+        let platform = &mut ctx.accounts.platform;
+        
+        // 檢查房源是否可用 (短租模式不接受長期租約) 且未由他人保留
+        require!(
+            listing.is_available && listing.short_stay.is_none(),
+            RentalError::PropertyNotAvailable
+        );
+        let current_time = Clock::get()?.unix_timestamp;
+        listing.check_not_reserved(&ctx.accounts.tenant.key(), current_time)?;
+        
+        // 房東離線簽署的要約可取代申請審核與租約簽署，條款以要約為準
+        let (price, contract_length) = match &offer {
+            Some(offer) => {
+                offer.verify(
+                    &listing.key(),
+                    listing,
+                    &ctx.accounts.tenant.key(),
+                    ctx.accounts.instructions.as_ref().map(|info| info.as_ref()),
+                    current_time,
+                )?;
+                listing.offer_nonce = offer.nonce;
+                (offer.price, offer.contract_length)
+            }
+            None => {
+                require!(!listing.requires_application, RentalError::ApplicationRequired);
+                // 須附上雙方已簽署的租約
+                ctx.accounts
+                    .lease
+                    .as_ref()
+                    .ok_or(RentalError::LeaseNotSigned)?
+                    .check_usable(&listing.key(), &ctx.accounts.tenant.key())?;
+                (listing.price, listing.contract_length)
+            }
+        };
+        
+        // 分租：依房間租金與押金承租單一房間 (離線要約僅適用整層出租)；整層出租須無房間出租中
+        let (price, deposit) = match room_id {
+            Some(room_id) => {
+                require!(offer.is_none(), RentalError::InvalidOffer);
+                let room = listing.vacant_room(room_id)?;
+                (room.price, room.deposit)
+            }
+            None => {
+                require!(!listing.has_occupied_rooms(), RentalError::PropertyNotAvailable);
+                (price, listing.deposit)
+            }
+        };
+        
+        // 法幣計價房源：首月租金與押金 (依房源標示的押租比例) 依當下 Pyth 報價換算為 lamports
+        let (price, deposit) = match listing.fiat_price {
+            Some(fiat_price) if offer.is_none() && room_id.is_none() => {
+                let sol_usd = PriceFeedValidator::load_platform_quote(
+                    ctx.accounts.sol_usd_price_feed.as_deref(),
+                    &platform.sol_usd_price_feed,
+                    platform,
+                    current_time,
+                )?;
+                let usd_twd = if fiat_price.currency == FiatCurrency::Twd {
+                    Some(PriceFeedValidator::load_platform_quote(
+                        ctx.accounts.usd_twd_price_feed.as_deref(),
+                        &platform.usd_twd_price_feed,
+                        platform,
+                        current_time,
+                    )?)
+                } else {
+                    None
+                };
+                let fiat_deposit = PriceFeedValidator::peg_deposit(&fiat_price, listing.price, deposit)?;
+                (
+                    PriceFeedValidator::fiat_to_lamports(&fiat_price, &sol_usd, usd_twd.as_ref())?,
+                    PriceFeedValidator::fiat_to_lamports(&fiat_deposit, &sol_usd, usd_twd.as_ref())?,
+                )
+            }
+            _ => (price, deposit),
+        };
+        
+        // 預留轉為租約：預留費用抵付押金
+        let reservation_credit = match ctx.accounts.reservation.as_deref() {
+            Some(reservation) => {
+                require!(room_id.is_none(), RentalError::InvalidReservation);
+                reservation.credit(&ctx.accounts.tenant.key(), current_time)?
+            }
+            None => 0,
+        };
+        let deposit_payable = deposit
+            .checked_sub(reservation_credit)
+            .ok_or(RentalError::InvalidReservation)?;
+        
+        // 租約自房源約定的入住日起算，起租前押金與首月租金託管於租約帳戶
+        let start_date = pending::start_date(listing.move_in_date, current_time);
+        let is_pending = start_date > current_time;
+        
+        // 依平台設定檢查雙方 KYC 認證
+        platform.kyc_policy.enforce(
+            platform.kyc_policy.require_landlord,
+            ctx.accounts
+                .landlord_profile
+                .as_ref()
+                .and_then(|profile| profile.attestation.as_ref()),
+            current_time,
+        )?;
+        platform.kyc_policy.enforce(
+            platform.kyc_policy.require_tenant,
+            ctx.accounts.tenant_profile.attestation.as_ref(),
+            current_time,
+        )?;
+        
+        // 檢查租客信用分數是否達到房東要求
+        require!(
+            ctx.accounts.tenant_profile.reputation_score >= listing.min_tenant_score,
+            RentalError::TenantScoreTooLow
+        );
+        
+        require!(listing.payment_method == PaymentMethod::Sol, RentalError::InvalidPaymentMethod);
+        
+        // 檢查租客是否有足夠的 SOL 支付押金 (扣除預留費用) 和首月租金
+        let total_amount = deposit_payable + price;
+        require!(
+            ctx.accounts.tenant.to_account_info().lamports() >= total_amount,
+            RentalError::InsufficientFunds
+        );
+        if let Some(reservation) = ctx.accounts.reservation.as_ref() {
+            let recipient = if is_pending {
+                rental.to_account_info()
+            } else {
+                ctx.accounts.landlord.to_account_info()
+            };
+            escrow::release_lamports(&reservation.to_account_info(), &recipient, reservation_credit)?;
+        }
+        
+        if is_pending {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.tenant.to_account_info(),
+                        to: rental.to_account_info(),
+                    },
+                ),
+                total_amount,
+            )?;
+        } else if listing.is_co_owned() {
+            // 共有房源：押金由房源管理者收取 (負責退還)，首月租金依持分分配給各共有人
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.tenant.to_account_info(),
+                        to: ctx.accounts.landlord.to_account_info(),
+                    },
+                ),
+                deposit_payable,
+            )?;
+            ownership::pay_owners_sol(
+                &ctx.accounts.tenant.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &listing.owners,
+                ctx.remaining_accounts,
+                price,
+            )?;
+        } else {
+            // 轉移押金和首月租金到房東
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.tenant.to_account_info(),
+                        to: ctx.accounts.landlord.to_account_info(),
+                    },
+                ),
+                total_amount,
+            )?;
+        }
+        
+        // 創建租約
+        rental.rental_id = rental_id;
+        rental.listing = ctx.accounts.listing.key();
+        rental.landlord = ctx.accounts.landlord.key();
+        rental.tenant = ctx.accounts.tenant.key();
+        rental.price = price;
+        rental.deposit = deposit;
+        rental.contract_length = contract_length;
+        rental.payment_method = PaymentMethod::Sol;
+        rental.schedule(start_date, contract_length, current_time);
+        rental.next_payment_date = start_date + (30 * 24 * 60 * 60);
+        // 法幣計價僅適用於 SOL 支付，每期依當下匯率換算；要約與房間租金以 SOL 計價
+        rental.fiat_price = if offer.is_some() || room_id.is_some() { None } else { listing.fiat_price };
+        rental.deposit_settled = false;
+        rental.deposit_deduction = 0;
+        rental.escrow_balance = if is_pending { deposit } else { 0 };
+        rental.prepaid_rent = if is_pending { price } else { 0 };
+        rental.cancellation_policy = listing.cancellation_policy;
+        rental.escalation = None;
+        rental.bill_count = 0;
+        rental.ticket_count = 0;
+        rental.maintenance_abatement_bps = listing.maintenance_abatement_bps;
+        rental.pending_abatement_bps = 0;
+        rental.deposit_due = 0;
+        match (&offer, &ctx.accounts.lease) {
+            (Some(offer), _) => rental.record_offer(offer, current_time),
+            (None, Some(lease)) => rental.record_lease(lease),
+            (None, None) => {}
+        }
+        rental.co_tenants = Vec::new();
+        rental.reset_collection();
+        rental.room_id = room_id;
+        rental.bump = *ctx.bumps.get("rental").unwrap();
+        rental.created_at = Clock::get()?.unix_timestamp;
+        
+        // 租約文件綁定此租約，不可重複使用
+        if let (None, Some(lease)) = (&offer, ctx.accounts.lease.as_mut()) {
+            lease.rental = rental.key();
+        }
+        
+        // 更新房源狀態：分租僅標記房間，其餘房間與整層房源維持可租
+        listing.updated_at = Clock::get()?.unix_timestamp;
+        if let Some(room_id) = room_id {
+            listing.occupy_room(room_id, rental.key())?;
+        } else {
+            listing.is_available = false;
+            listing.clear_reservation(&ctx.accounts.tenant.key());
+            property::record_occupancy(listing, ctx.accounts.property.as_deref_mut(), true)?;
+            
+            // 自可租房源索引移除
+            ctx.accounts.district_index.remove(
+                IndexKind::District,
+                listing.address.district_code,
+                &listing.key(),
+            )?;
+            ctx.accounts.price_index.remove(
+                IndexKind::PriceBucket,
+                listing.price_bucket,
+                &listing.key(),
+            )?;
+        }
+        
+        platform.total_rentals += 1;
+        platform.total_volume += total_amount + reservation_credit;
+        
+        msg!("租約已創建 (SOL 支付)，租約 ID: {}", rental_id);
+        Ok(())
+    }
+
+    /// 租用房源 (USDT 支付)
+    pub fn rent_property_usdt<'info>(
+        ctx: Context<'_, '_, '_, 'info, RentPropertyUsdt<'info>>,
+        rental_id: u64,
+        offer: Option<SignedOffer>,
+        room_id: Option<u8>,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental;
+        let listing = &mut ctx.accounts.listing;
+        let platform = &mut ctx.accounts.platform;
+        
+        // 檢查房源是否可用 (短租模式不接受長期租約) 且未由他人保留
+        require!(
+            listing.is_available && listing.short_stay.is_none(),
+            RentalError::PropertyNotAvailable
+        );
+        let current_time = Clock::get()?.unix_timestamp;
+        listing.check_not_reserved(&ctx.accounts.tenant.key(), current_time)?;
+        
+        // 房東離線簽署的要約可取代申請審核與租約簽署，條款以要約為準
+        let (price, contract_length) = match &offer {
+            Some(offer) => {
+                offer.verify(
+                    &listing.key(),
+                    listing,
+                    &ctx.accounts.tenant.key(),
+                    ctx.accounts.instructions.as_ref().map(|info| info.as_ref()),
+                    current_time,
+                )?;
+                listing.offer_nonce = offer.nonce;
+                (offer.price, offer.contract_length)
+            }
+            None => {
+                require!(!listing.requires_application, RentalError::ApplicationRequired);
+                // 須附上雙方已簽署的租約
+                ctx.accounts
+                    .lease
+                    .as_ref()
+                    .ok_or(RentalError::LeaseNotSigned)?
+                    .check_usable(&listing.key(), &ctx.accounts.tenant.key())?;
+                (listing.price, listing.contract_length)
+            }
+        };
+        
+        // 分租：依房間租金與押金承租單一房間 (離線要約僅適用整層出租)；整層出租須無房間出租中
+        let (price, deposit) = match room_id {
+            Some(room_id) => {
+                require!(offer.is_none(), RentalError::InvalidOffer);
+                let room = listing.vacant_room(room_id)?;
+                (room.price, room.deposit)
+            }
+            None => {
+                require!(!listing.has_occupied_rooms(), RentalError::PropertyNotAvailable);
+                (price, listing.deposit)
+            }
+        };
+        
+        // 依平台設定檢查雙方 KYC 認證
+        platform.kyc_policy.enforce(
+            platform.kyc_policy.require_landlord,
+            ctx.accounts
+                .landlord_profile
+                .as_ref()
+                .and_then(|profile| profile.attestation.as_ref()),
+            current_time,
+        )?;
+        platform.kyc_policy.enforce(
+            platform.kyc_policy.require_tenant,
+            ctx.accounts.tenant_profile.attestation.as_ref(),
+            current_time,
+        )?;
+        
+        // 檢查租客信用分數是否達到房東要求
+        require!(
+            ctx.accounts.tenant_profile.reputation_score >= listing.min_tenant_score,
+            RentalError::TenantScoreTooLow
+        );
+        
+        require!(listing.payment_method == PaymentMethod::Usdt, RentalError::InvalidPaymentMethod);
+        
+        // 檢查租客是否有足夠的 USDT
+        let total_amount = deposit + price;
+        require!(
+            ctx.accounts.tenant_token_account.amount >= total_amount,
+            RentalError::InsufficientFunds
+        );
+        
+        // 租約自房源約定的入住日起算，起租前押金與首月租金託管於租金託管代幣帳戶
+        let start_date = pending::start_date(listing.move_in_date, current_time);
+        let is_pending = start_date > current_time;
+        if is_pending {
+            let rent_escrow = ctx
+                .accounts
+                .rent_escrow_token_account
+                .as_ref()
+                .ok_or(RentalError::InvalidEscrowAccount)?;
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.tenant_token_account.to_account_info(),
+                        to: rent_escrow.to_account_info(),
+                        authority: ctx.accounts.tenant.to_account_info(),
+                    },
+                ),
+                total_amount,
+            )?;
+        } else if listing.is_co_owned() {
+            // 共有房源：押金由房源管理者收取 (負責退還)，首月租金依持分分配給各共有人
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.tenant_token_account.to_account_info(),
+                        to: ctx.accounts.landlord_token_account.to_account_info(),
+                        authority: ctx.accounts.tenant.to_account_info(),
+                    },
+                ),
+                deposit,
+            )?;
+            ownership::pay_owners_usdt(
+                &ctx.accounts.tenant_token_account,
+                &ctx.accounts.tenant.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                &listing.owners,
+                ctx.remaining_accounts,
+                price,
+                &[],
+            )?;
+        } else {
+            // 轉移 USDT 到房東
+            let transfer_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.tenant_token_account.to_account_info(),
+                    to: ctx.accounts.landlord_token_account.to_account_info(),
+                    authority: ctx.accounts.tenant.to_account_info(),
+                },
+            );
+            
+            token::transfer(transfer_ctx, total_amount)?;
+        }
+        
+        // 創建租約
+        rental.rental_id = rental_id;
+        rental.listing = ctx.accounts.listing.key();
+        rental.landlord = ctx.accounts.landlord.key();
+        rental.tenant = ctx.accounts.tenant.key();
+        rental.price = price;
+        rental.deposit = deposit;
+        rental.contract_length = contract_length;
+        rental.payment_method = PaymentMethod::Usdt;
+        rental.schedule(start_date, contract_length, current_time);
+        rental.next_payment_date = start_date + (30 * 24 * 60 * 60);
+        rental.fiat_price = None;
+        rental.deposit_settled = false;
+        rental.deposit_deduction = 0;
+        rental.escrow_balance = 0;
+        rental.prepaid_rent = if is_pending { price } else { 0 };
+        rental.cancellation_policy = listing.cancellation_policy;
+        rental.escalation = None;
+        rental.bill_count = 0;
+        rental.ticket_count = 0;
+        rental.maintenance_abatement_bps = listing.maintenance_abatement_bps;
+        rental.pending_abatement_bps = 0;
+        rental.deposit_due = 0;
+        match (&offer, &ctx.accounts.lease) {
+            (Some(offer), _) => rental.record_offer(offer, current_time),
+            (None, Some(lease)) => rental.record_lease(lease),
+            (None, None) => {}
+        }
+        rental.co_tenants = Vec::new();
+        rental.reset_collection();
+        rental.room_id = room_id;
+        rental.bump = *ctx.bumps.get("rental").unwrap();
+        rental.created_at = Clock::get()?.unix_timestamp;
+        
+        // 租約文件綁定此租約，不可重複使用
+        if let (None, Some(lease)) = (&offer, ctx.accounts.lease.as_mut()) {
+            lease.rental = rental.key();
+        }
+        
+        // 更新房源狀態：分租僅標記房間，其餘房間與整層房源維持可租
+        listing.updated_at = Clock::get()?.unix_timestamp;
+        if let Some(room_id) = room_id {
+            listing.occupy_room(room_id, rental.key())?;
+        } else {
+            listing.is_available = false;
+            listing.clear_reservation(&ctx.accounts.tenant.key());
+            property::record_occupancy(listing, ctx.accounts.property.as_deref_mut(), true)?;
+            
+            // 自可租房源索引移除
+            ctx.accounts.district_index.remove(
+                IndexKind::District,
+                listing.address.district_code,
+                &listing.key(),
+            )?;
+            ctx.accounts.price_index.remove(
+                IndexKind::PriceBucket,
+                listing.price_bucket,
+                &listing.key(),
+            )?;
+        }
+        
+        platform.total_rentals += 1;
+        platform.total_volume += total_amount;
+        
+        msg!("租約已創建 (USDT 支付)，租約 ID: {}", rental_id);
+        Ok(())
+    }
+
+    /// 提交租屋申請，可附上可退還的申請保證金 (SOL，計入押金)
+    pub fn submit_application(
+        ctx: Context<SubmitApplication>,
+        move_in_date: i64,
+        contract_length: u8,
+        offer_price: Option<u64>,
+        hold_amount: u64,
+    ) -> Result<()> {
+        let listing = &ctx.accounts.listing;
+        
+        require!(
+            listing.is_available && listing.short_stay.is_none(),
+            RentalError::PropertyNotAvailable
+        );
+        listing.check_not_reserved(&ctx.accounts.tenant.key(), Clock::get()?.unix_timestamp)?;
+        require!(
+            contract_length > 0 && !matches!(offer_price, Some(0)),
+            RentalError::InvalidApplication
+        );
+        require!(hold_amount <= listing.deposit, RentalError::InvalidApplication);
+        require!(
+            hold_amount == 0 || listing.payment_method == PaymentMethod::Sol,
+            RentalError::InvalidPaymentMethod
+        );
+        
+        // 申請保證金託管於申請帳戶
+        if hold_amount > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.tenant.to_account_info(),
+                        to: ctx.accounts.application.to_account_info(),
+                    },
+                ),
+                hold_amount,
+            )?;
+        }
+        
+        let application = &mut ctx.accounts.application;
+        application.listing = listing.key();
+        application.tenant = ctx.accounts.tenant.key();
+        application.tenant_profile = ctx.accounts.tenant_profile.key();
+        application.move_in_date = move_in_date;
+        application.contract_length = contract_length;
+        application.offer_price = offer_price;
+        application.hold_amount = hold_amount;
+        application.submitted_at = Clock::get()?.unix_timestamp;
+        application.bump = ctx.bumps.application;
+        
+        msg!("租屋申請已提交，保證金: {}", hold_amount);
+        Ok(())
+    }
+
+    /// 房東 (或代管人) 接受申請：建立租約、將保證金轉入租約託管，並退還其他申請
+    ///
+    /// 其他申請以 (申請帳戶, 租客帳戶) 成對放入 remaining accounts。
+    pub fn accept_application(ctx: Context<AcceptApplication>) -> Result<()> {
+        let application = &ctx.accounts.application;
+        let listing = &mut ctx.accounts.listing;
+        let platform = &mut ctx.accounts.platform;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::MANAGE_TENANTS,
+            current_time,
+        )?;
+        require!(
+            listing.is_available && !listing.has_occupied_rooms() && listing.short_stay.is_none(),
+            RentalError::PropertyNotAvailable
+        );
+        listing.check_not_reserved(&application.tenant, current_time)?;
+        
+        // 依平台設定檢查雙方 KYC 認證與租客信用分數
+        platform.kyc_policy.enforce(
+            platform.kyc_policy.require_landlord,
+            ctx.accounts
+                .landlord_profile
+                .as_ref()
+                .and_then(|profile| profile.attestation.as_ref()),
+            current_time,
+        )?;
+        platform.kyc_policy.enforce(
+            platform.kyc_policy.require_tenant,
+            ctx.accounts.tenant_profile.attestation.as_ref(),
+            current_time,
+        )?;
+        require!(
+            ctx.accounts.tenant_profile.reputation_score >= listing.min_tenant_score,
+            RentalError::TenantScoreTooLow
+        );
+        
+        // 須附上雙方已簽署的租約
+        ctx.accounts.lease.check_usable(&listing.key(), &application.tenant)?;
+        
+        // 建立租約，申請出價優先於房源定價 (出價以原生幣別計價)
+        let rental = &mut ctx.accounts.rental;
+        rental.rental_id = platform.total_rentals;
+        rental.listing = listing.key();
+        rental.landlord = listing.authority;
+        rental.tenant = application.tenant;
+        rental.price = application.offer_price.unwrap_or(listing.price);
+        // 押金先以申請保證金計，不足部分於第一期租金時向租客收取
+        let (escrowed, deposit_due) = escrow::split_hold(application.hold_amount, listing.deposit);
+        rental.deposit = escrowed;
+        rental.deposit_due = deposit_due;
+        rental.contract_length = application.contract_length;
+        // 租約自申請的入住日起算，首期租金於起租日到期
+        let start_date = pending::start_date(application.move_in_date, current_time);
+        rental.schedule(start_date, application.contract_length, current_time);
+        rental.next_payment_date = start_date;
+        rental.payment_method = listing.payment_method.clone();
+        rental.fiat_price = match (&listing.payment_method, application.offer_price) {
+            (PaymentMethod::Sol, None) => listing.fiat_price,
+            _ => None,
+        };
+        rental.deposit_settled = false;
+        rental.deposit_deduction = 0;
+        rental.escrow_balance = escrowed;
+        rental.prepaid_rent = 0;
+        rental.cancellation_policy = listing.cancellation_policy;
+        rental.escalation = None;
+        rental.bill_count = 0;
+        rental.ticket_count = 0;
+        rental.maintenance_abatement_bps = listing.maintenance_abatement_bps;
+        rental.pending_abatement_bps = 0;
+        rental.record_lease(&ctx.accounts.lease);
+        rental.co_tenants = Vec::new();
+        rental.reset_collection();
+        rental.room_id = None;
+        rental.bump = ctx.bumps.rental;
+        rental.created_at = current_time;
+        rental.updated_at = current_time;
+        
+        // 申請保證金轉入租約託管 (超出押金的部分隨申請帳戶關閉退還租客)
+        escrow::release_lamports(
+            &application.to_account_info(),
+            &rental.to_account_info(),
+            escrowed,
+        )?;
+        
+        ctx.accounts.lease.rental = rental.key();
+        
+        // 退還其他申請的保證金並關閉申請帳戶
+        let mut refunded: u8 = 0;
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let [competing_info, tenant_info] = pair else {
+                return err!(RentalError::InvalidApplication);
+            };
+            require!(competing_info.owner == &crate::ID, RentalError::InvalidApplication);
+            let competing = RentalApplication::try_deserialize(&mut &competing_info.try_borrow_data()?[..])?;
+            require!(
+                competing.listing == listing.key()
+                    && competing.tenant == tenant_info.key()
+                    && competing_info.key() != application.key(),
+                RentalError::InvalidApplication
+            );
+            escrow::close_account(competing_info, tenant_info)?;
+            refunded += 1;
+        }
+        
+        // 更新房源狀態並自可租房源索引移除
+        listing.is_available = false;
+        listing.clear_reservation(&application.tenant);
+        listing.updated_at = current_time;
+        property::record_occupancy(listing, ctx.accounts.property.as_deref_mut(), true)?;
+        ctx.accounts.district_index.remove(
+            IndexKind::District,
+            listing.address.district_code,
+            &listing.key(),
+        )?;
+        ctx.accounts.price_index.remove(
+            IndexKind::PriceBucket,
+            listing.price_bucket,
+            &listing.key(),
+        )?;
+        
+        platform.total_rentals += 1;
+        
+        emit!(ApplicationAccepted {
+            application: application.key(),
+            rental: rental.key(),
+            listing: listing.key(),
+            tenant: application.tenant,
+            price: rental.price,
+            escrowed,
+            refunded_applications: refunded,
+            timestamp: current_time,
+        });
+        
+        msg!("租屋申請已接受，租約 ID: {}", rental.rental_id);
+        Ok(())
+    }
+
+    /// 房東 (或代管人) 拒絕申請，退還保證金
+    pub fn reject_application(ctx: Context<RejectApplication>) -> Result<()> {
+        let listing = &ctx.accounts.listing;
+        Delegation::authorize(
+            &listing.key(),
+            &listing.authority,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::MANAGE_TENANTS,
+            Clock::get()?.unix_timestamp,
+        )?;
+        
+        msg!("租屋申請已拒絕: {}", ctx.accounts.application.tenant);
+        Ok(())
+    }
+
+    /// 撤回申請並退還保證金：租客可隨時撤回，房源已出租後任何人皆可代為退還
+    pub fn withdraw_application(ctx: Context<WithdrawApplication>) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.application.tenant
+                || !ctx.accounts.listing.is_available,
+            RentalError::Unauthorized
+        );
+        
+        msg!("租屋申請已撤回: {}", ctx.accounts.application.tenant);
+        Ok(())
+    }
+
+    /// 設定合租租客與固定分攤比例，須房東 (或代管人)、主要租客與所有新加入的租客簽署
+    ///
+    /// 新租客以簽署者身分放入 remaining accounts；傳入空清單即恢復為單一租客。
+    pub fn configure_co_tenants(ctx: Context<ConfigureCoTenants>, shares: Vec<RentShare>) -> Result<()> {
+        let rental = &mut ctx.accounts.rental;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        Delegation::authorize(
+            &rental.listing,
+            &rental.landlord,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::MANAGE_TENANTS,
+            current_time,
+        )?;
+        // 待起租的租約亦可先設定合租租客
+        require!(
+            rental.status == RentalStatus::Active || rental.status == RentalStatus::Pending,
+            RentalError::RentalNotActive
+        );
+        require!(!rental.is_collecting(), RentalError::RentCollectionInProgress);
+        
+        if !shares.is_empty() {
+            cotenancy::validate_shares(&shares, &rental.tenant)?;
+        }
+        
+        // 新加入、分攤比例變更或被移除的合租租客皆須簽署同意
+        for tenant in rental.consenting_tenants(&shares) {
+            require!(
+                ctx.remaining_accounts.iter().any(|info| info.key() == tenant && info.is_signer),
+                RentalError::Unauthorized
+            );
+        }
+        
+        rental.co_tenants = shares;
+        rental.updated_at = current_time;
+        
+        msg!("合租設定已更新，租客人數: {}", rental.co_tenants.len().max(1));
+        Ok(())
+    }
+
+    /// 支付租金 (SOL)
+    pub fn pay_rent_sol<'info>(ctx: Context<'_, '_, '_, 'info, PayRentSol<'info>>) -> Result<()> {
+        let rental = &mut ctx.accounts.rental;
+        let listing = &ctx.accounts.listing;
+        let platform = &mut ctx.accounts.platform;
+        
+        // 檢查租約狀態
+        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        require!(rental.payment_method == PaymentMethod::Sol, RentalError::InvalidPaymentMethod);
+        
+        // 檢查是否到了付款時間
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(current_time >= rental.next_payment_date, RentalError::PaymentNotDue);
+        
+        // 套用本期應生效的租金調漲
+        escalation::apply(rental, ctx.accounts.cpi_feed.as_deref(), current_time)?;
+        
+        // 計算本期應付金額：法幣計價租約依 Pyth 報價換算為 lamports
+        let mut sol_usd_rate = None;
+        let mut usd_twd_rate = None;
+        let amount_due = match rental.fiat_price {
+            Some(fiat_price) => {
+                let sol_usd = PriceFeedValidator::load_platform_quote(
+                    ctx.accounts.sol_usd_price_feed.as_deref(),
+                    &platform.sol_usd_price_feed,
+                    platform,
+                    current_time,
+                )?;
+                if fiat_price.currency == FiatCurrency::Twd {
+                    usd_twd_rate = Some(PriceFeedValidator::load_platform_quote(
+                        ctx.accounts.usd_twd_price_feed.as_deref(),
+                        &platform.usd_twd_price_feed,
+                        platform,
+                        current_time,
+                    )?);
+                }
+                sol_usd_rate = Some(sol_usd);
+                PriceFeedValidator::fiat_to_lamports(&fiat_price, &sol_usd, usd_twd_rate.as_ref())?
+            }
+            None => rental.price,
+        };
+        
+        // 扣抵報修逾期的租金減免
+        let abated = rental.take_abatement(amount_due);
+        if abated > 0 {
+            emit!(RentAbated { rental: rental.key(), amount: abated });
+        }
+        let amount_due = amount_due - abated;
+        
+        // 單一租客租約須由租客本人付款，信用紀錄才會記在租客身上 (合租由分攤名單驗證)
+        require!(
+            rental.is_shared() || ctx.accounts.tenant.key() == rental.tenant,
+            RentalError::Unauthorized
+        );
+        
+        // 申請保證金不足押金的部分由租客於第一期租金時補足，存入租約託管
+        let deposit_due = rental.deposit_due;
+        if deposit_due > 0 {
+            require!(ctx.accounts.tenant.key() == rental.tenant, RentalError::DepositOutstanding);
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.tenant.to_account_info(),
+                        to: rental.to_account_info(),
+                    },
+                ),
+                deposit_due,
+            )?;
+            rental.escrow_balance += deposit_due;
+            rental.deposit += deposit_due;
+            rental.deposit_due = 0;
+        }
+        
+        // 記錄付款是否準時
+        let tenant_profile = &mut ctx.accounts.tenant_profile;
+        tenant_profile.record_payment(rental.next_payment_date, current_time);
+        tenant_profile.updated_at = current_time;
+        
+        let amount_paid = if rental.is_shared() {
+            // 多人合租：各租客分攤款項先託管於租約帳戶，收齊後才轉交房東
+            let (contribution, complete) =
+                rental.record_contribution(&ctx.accounts.tenant.key(), amount_due)?;
+            require!(
+                ctx.accounts.tenant.to_account_info().lamports() >= contribution,
+                RentalError::InsufficientFunds
+            );
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.tenant.to_account_info(),
+                        to: rental.to_account_info(),
+                    },
+                ),
+                contribution,
+            )?;
+            
+            if !complete {
+                rental.updated_at = current_time;
+                msg!("已收到分攤租金 {}，本期已收 {} / {}", contribution, rental.rent_collected, rental.rent_period_due);
+                return Ok(());
+            }
+            
+            let collected = rental.rent_collected;
+            let fee = listing.management_fee(
+                &listing.key(),
+                ctx.accounts.delegation.as_deref(),
+                collected,
+                current_time,
+            )?;
+            if fee > 0 {
+                let manager = ctx.accounts.manager.as_ref().ok_or(RentalError::InvalidDelegation)?;
+                escrow::release_lamports(&rental.to_account_info(), &manager.to_account_info(), fee)?;
+            }
+            if listing.is_co_owned() {
+                ownership::release_to_owners(
+                    &rental.to_account_info(),
+                    &listing.owners,
+                    ctx.remaining_accounts,
+                    collected - fee,
+                )?;
+            } else {
+                escrow::release_lamports(
+                    &rental.to_account_info(),
+                    &ctx.accounts.landlord.to_account_info(),
+                    collected - fee,
+                )?;
+            }
+            rental.reset_collection();
+            collected
+        } else {
+            // 檢查租客是否有足夠的 SOL
+            require!(
+                ctx.accounts.tenant.to_account_info().lamports() >= amount_due,
+                RentalError::InsufficientFunds
+            );
+            
+            // 代管人先抽取管理費，其餘歸房東
+            let fee = listing.management_fee(
+                &listing.key(),
+                ctx.accounts.delegation.as_deref(),
+                amount_due,
+                current_time,
+            )?;
+            if fee > 0 {
+                let manager = ctx.accounts.manager.as_ref().ok_or(RentalError::InvalidDelegation)?;
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.tenant.to_account_info(),
+                            to: manager.to_account_info(),
+                        },
+                    ),
+                    fee,
+                )?;
+            }
+            
+            if listing.is_co_owned() {
+                // 共有房源依持分分配租金
+                ownership::pay_owners_sol(
+                    &ctx.accounts.tenant.to_account_info(),
+                    &ctx.accounts.system_program.to_account_info(),
+                    &listing.owners,
+                    ctx.remaining_accounts,
+                    amount_due - fee,
+                )?;
+            } else {
+                // 轉移租金到房東
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.tenant.to_account_info(),
+                            to: ctx.accounts.landlord.to_account_info(),
+                        },
+                    ),
+                    amount_due - fee,
+                )?;
+            }
+            amount_due
+        };
+        
+        // 更新下次付款日期
+        rental.next_payment_date += 30 * 24 * 60 * 60; // 30 天後
+        rental.updated_at = current_time;
+        
+        platform.total_volume += amount_paid;
+        
+        emit!(RentPaid {
+            rental: rental.key(),
+            tenant: ctx.accounts.tenant.key(),
+            amount: amount_paid,
+            payment_method: PaymentMethod::Sol,
+            fiat_price: rental.fiat_price,
+            sol_usd_rate,
+            usd_twd_rate,
+            next_payment_date: rental.next_payment_date,
+            timestamp: current_time,
+        });
+        
+        msg!("租金已支付 (SOL)，下次付款日期: {}", rental.next_payment_date);
+        Ok(())
+    }
+
+    /// 支付租金 (USDT)
+    pub fn pay_rent_usdt<'info>(ctx: Context<'_, '_, '_, 'info, PayRentUsdt<'info>>) -> Result<()> {
+        let rental = &mut ctx.accounts.rental;
+        let listing = &ctx.accounts.listing;
+        let platform = &mut ctx.accounts.platform;
+        
+        // 檢查租約狀態
+        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        require!(rental.payment_method == PaymentMethod::Usdt, RentalError::InvalidPaymentMethod);
+        
+        // 檢查是否到了付款時間
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(current_time >= rental.next_payment_date, RentalError::PaymentNotDue);
+        
+        // 套用本期應生效的租金調漲
+        escalation::apply(rental, ctx.accounts.cpi_feed.as_deref(), current_time)?;
+        
+        // 扣抵報修逾期的租金減免
+        let amount_due = rental.price;
+        let abated = rental.take_abatement(amount_due);
+        if abated > 0 {
+            emit!(RentAbated { rental: rental.key(), amount: abated });
+        }
+        let amount_due = amount_due - abated;
+        
+        // 單一租客租約須由租客本人付款，信用紀錄才會記在租客身上 (合租由分攤名單驗證)
+        require!(
+            rental.is_shared() || ctx.accounts.tenant.key() == rental.tenant,
+            RentalError::Unauthorized
+        );
+        
+        // 押金由租客於第一期租金時補足，與其他 USDT 租約相同交付房東
+        let deposit_due = rental.deposit_due;
+        if deposit_due > 0 {
+            require!(ctx.accounts.tenant.key() == rental.tenant, RentalError::DepositOutstanding);
+            require!(
+                ctx.accounts.tenant_token_account.amount >= deposit_due,
+                RentalError::InsufficientFunds
+            );
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.tenant_token_account.to_account_info(),
+                        to: ctx.accounts.landlord_token_account.to_account_info(),
+                        authority: ctx.accounts.tenant.to_account_info(),
+                    },
+                ),
+                deposit_due,
+            )?;
+            ctx.accounts.tenant_token_account.reload()?;
+            rental.deposit += deposit_due;
+            rental.deposit_due = 0;
+        }
+        
+        // 記錄付款是否準時
+        let tenant_profile = &mut ctx.accounts.tenant_profile;
+        tenant_profile.record_payment(rental.next_payment_date, current_time);
+        tenant_profile.updated_at = current_time;
+        
+        let amount_paid = if rental.is_shared() {
+            // 多人合租：各租客分攤款項先託管於租約的 USDT 帳戶，收齊後才轉交房東
+            let rent_escrow = ctx
+                .accounts
+                .rent_escrow_token_account
+                .as_ref()
+                .ok_or(RentalError::InvalidEscrowAccount)?;
+            let (contribution, complete) =
+                rental.record_contribution(&ctx.accounts.tenant.key(), amount_due)?;
+            require!(
+                ctx.accounts.tenant_token_account.amount >= contribution,
+                RentalError::InsufficientFunds
+            );
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.tenant_token_account.to_account_info(),
+                        to: rent_escrow.to_account_info(),
+                        authority: ctx.accounts.tenant.to_account_info(),
+                    },
+                ),
+                contribution,
+            )?;
+            
+            if !complete {
+                rental.updated_at = current_time;
+                msg!("已收到分攤租金 {}，本期已收 {} / {}", contribution, rental.rent_collected, rental.rent_period_due);
+                return Ok(());
+            }
+            
+            let collected = rental.rent_collected;
+            let fee = listing.management_fee(
+                &listing.key(),
+                ctx.accounts.delegation.as_deref(),
+                collected,
+                current_time,
+            )?;
+            if fee > 0 {
+                cotenancy::forward_usdt(
+                    rental,
+                    rent_escrow,
+                    ctx.accounts
+                        .manager_token_account
+                        .as_ref()
+                        .ok_or(RentalError::InvalidDelegation)?,
+                    &ctx.accounts.token_program,
+                    fee,
+                )?;
+            }
+            if listing.is_co_owned() {
+                let bump = [rental.bump];
+                let seeds: &[&[u8]] = &[b"rental", rental.listing.as_ref(), rental.tenant.as_ref(), &bump];
+                ownership::pay_owners_usdt(
+                    rent_escrow,
+                    &rental.to_account_info(),
+                    &ctx.accounts.token_program.to_account_info(),
+                    &listing.owners,
+                    ctx.remaining_accounts,
+                    collected - fee,
+                    &[seeds],
+                )?;
+            } else {
+                cotenancy::forward_usdt(
+                    rental,
+                    rent_escrow,
+                    &ctx.accounts.landlord_token_account,
+                    &ctx.accounts.token_program,
+                    collected - fee,
+                )?;
+            }
+            rental.reset_collection();
+            collected
+        } else {
+            // 檢查租客是否有足夠的 USDT
+            require!(
+                ctx.accounts.tenant_token_account.amount >= amount_due,
+                RentalError::InsufficientFunds
+            );
+            
+            // 代管人先抽取管理費，其餘歸房東
+            let fee = listing.management_fee(
+                &listing.key(),
+                ctx.accounts.delegation.as_deref(),
+                amount_due,
+                current_time,
+            )?;
+            if fee > 0 {
+                let manager_token_account = ctx
+                    .accounts
+                    .manager_token_account
+                    .as_ref()
+                    .ok_or(RentalError::InvalidDelegation)?;
+                token::transfer(
+                    CpiContext::new(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.tenant_token_account.to_account_info(),
+                            to: manager_token_account.to_account_info(),
+                            authority: ctx.accounts.tenant.to_account_info(),
+                        },
+                    ),
+                    fee,
+                )?;
+            }
+            
+            if listing.is_co_owned() {
+                // 共有房源依持分分配租金
+                ownership::pay_owners_usdt(
+                    &ctx.accounts.tenant_token_account,
+                    &ctx.accounts.tenant.to_account_info(),
+                    &ctx.accounts.token_program.to_account_info(),
+                    &listing.owners,
+                    ctx.remaining_accounts,
+                    amount_due - fee,
+                    &[],
+                )?;
+            } else {
+                // 轉移 USDT 到房東
+                let transfer_ctx = CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.tenant_token_account.to_account_info(),
+                        to: ctx.accounts.landlord_token_account.to_account_info(),
+                        authority: ctx.accounts.tenant.to_account_info(),
+                    },
+                );
+                
+                token::transfer(transfer_ctx, amount_due - fee)?;
+            }
+            amount_due
+        };
+        
+        // 更新下次付款日期
+        rental.next_payment_date += 30 * 24 * 60 * 60; // 30 天後
+        rental.updated_at = current_time;
+        
+        platform.total_volume += amount_paid;
+        
+        emit!(RentPaid {
+            rental: rental.key(),
+            tenant: ctx.accounts.tenant.key(),
+            amount: amount_paid,
+            payment_method: PaymentMethod::Usdt,
+            fiat_price: None,
+            sol_usd_rate: None,
+            usd_twd_rate: None,
+            next_payment_date: rental.next_payment_date,
+            timestamp: current_time,
+        });
+        
+        msg!("租金已支付 (USDT)，下次付款日期: {}", rental.next_payment_date);
+        Ok(())
+    }
+
+    /// 起租日到達後啟用待起租的租約，將託管的首月租金交付房東 (任何人皆可呼叫)
+    ///
+    /// SOL 租約的押金續留租約託管；USDT 租約的押金連同首月租金轉交房東。
+    /// 共有房源的收款帳戶依 `owners` 順序放入 remaining accounts。
+    pub fn activate_rental<'info>(ctx: Context<'_, '_, '_, 'info, ActivateRental<'info>>) -> Result<()> {
+        let rental = &mut ctx.accounts.rental;
+        let listing = &ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(rental.status == RentalStatus::Pending, RentalError::RentalNotActive);
+        require!(current_time >= rental.start_date, RentalError::RentalNotStarted);
+        
+        let prepaid_rent = rental.prepaid_rent;
+        if rental.payment_method == PaymentMethod::Sol {
+            if listing.is_co_owned() {
+                ownership::release_to_owners(
+                    &rental.to_account_info(),
+                    &listing.owners,
+                    ctx.remaining_accounts,
+                    prepaid_rent,
+                )?;
+            } else {
+                escrow::release_lamports(
+                    &rental.to_account_info(),
+                    &ctx.accounts.landlord.to_account_info(),
+                    prepaid_rent,
+                )?;
+            }
+        } else if prepaid_rent > 0 {
+            let rent_escrow = ctx
+                .accounts
+                .rent_escrow_token_account
+                .as_ref()
+                .ok_or(RentalError::InvalidEscrowAccount)?;
+            let landlord_token_account = ctx
+                .accounts
+                .landlord_token_account
+                .as_ref()
+                .ok_or(RentalError::InvalidEscrowAccount)?;
+            // 押金由房東收取 (負責退還)
+            cotenancy::forward_usdt(
+                rental,
+                rent_escrow,
+                landlord_token_account,
+                &ctx.accounts.token_program,
+                rental.deposit,
+            )?;
+            if listing.is_co_owned() {
+                let bump = [rental.bump];
+                let seeds: &[&[u8]] = &[b"rental", rental.listing.as_ref(), rental.tenant.as_ref(), &bump];
+                ownership::pay_owners_usdt(
+                    rent_escrow,
+                    &rental.to_account_info(),
+                    &ctx.accounts.token_program.to_account_info(),
+                    &listing.owners,
+                    ctx.remaining_accounts,
+                    prepaid_rent,
+                    &[seeds],
+                )?;
+            } else {
+                cotenancy::forward_usdt(
+                    rental,
+                    rent_escrow,
+                    landlord_token_account,
+                    &ctx.accounts.token_program,
+                    prepaid_rent,
+                )?;
+            }
+        }
+        
+        rental.prepaid_rent = 0;
+        rental.status = RentalStatus::Active;
+        rental.updated_at = current_time;
+        
+        emit!(RentalActivated {
+            rental: rental.key(),
+            tenant: rental.tenant,
+            start_date: rental.start_date,
+            prepaid_rent,
+        });
+        
+        msg!("租約已起租，首月租金 {} 已交付房東", prepaid_rent);
+        Ok(())
+    }
+
+    /// 終止租約
+    pub fn terminate_rental<'info>(ctx: Context<'_, '_, '_, 'info, TerminateRental<'info>>) -> Result<()> {
+        let rental = &mut ctx.accounts.rental;
+        let listing = &mut ctx.accounts.listing;
+        
+        // 檢查租約狀態 (待起租的租約可於起租日前取消)
+        require!(
+            rental.status == RentalStatus::Active || rental.status == RentalStatus::Pending,
+            RentalError::RentalNotActive
+        );
+        
+        // 檢查是否為租客，或房東 (含具管理租約權限的代管人)
+        let current_user = ctx.accounts.authority.key();
+        let by_landlord = current_user != rental.tenant;
+        if by_landlord {
+            Delegation::authorize(
+                &rental.listing,
+                &rental.landlord,
+                &current_user,
+                ctx.accounts.delegation.as_deref(),
+                Delegation::MANAGE_TENANTS,
+                Clock::get()?.unix_timestamp,
+            )?;
+        }
+        
+        // 託管款項僅可退還租約記錄的租客與房東
+        require_keys_eq!(ctx.accounts.landlord.key(), rental.landlord, RentalError::Unauthorized);
+        require_keys_eq!(ctx.accounts.tenant.key(), rental.tenant, RentalError::Unauthorized);
+        
+        if rental.status == RentalStatus::Pending {
+            // 起租前取消：依租約記錄的退款規則退還託管款項，其餘歸房東，不計入租客終止紀錄
+            let current_time = Clock::get()?.unix_timestamp;
+            require!(current_time < rental.start_date, RentalError::RentalAlreadyStarted);
+            let (refund, retained) = rental.cancellation_split(by_landlord, current_time);
+            if rental.payment_method == PaymentMethod::Sol {
+                escrow::release_lamports(&rental.to_account_info(), &ctx.accounts.tenant.to_account_info(), refund)?;
+                escrow::release_lamports(
+                    &rental.to_account_info(),
+                    &ctx.accounts.landlord.to_account_info(),
+                    retained,
+                )?;
+            } else if refund + retained > 0 {
+                let rent_escrow = ctx
+                    .accounts
+                    .rent_escrow_token_account
+                    .as_ref()
+                    .ok_or(RentalError::InvalidEscrowAccount)?;
+                let tenant_token_account = ctx
+                    .accounts
+                    .tenant_token_account
+                    .as_ref()
+                    .filter(|account| account.owner == rental.tenant)
+                    .ok_or(RentalError::InvalidEscrowAccount)?;
+                cotenancy::forward_usdt(rental, rent_escrow, tenant_token_account, &ctx.accounts.token_program, refund)?;
+                if retained > 0 {
+                    let landlord_token_account = ctx
+                        .accounts
+                        .landlord_token_account
+                        .as_ref()
+                        .filter(|account| account.owner == rental.landlord)
+                        .ok_or(RentalError::InvalidEscrowAccount)?;
+                    cotenancy::forward_usdt(
+                        rental,
+                        rent_escrow,
+                        landlord_token_account,
+                        &ctx.accounts.token_program,
+                        retained,
+                    )?;
+                }
+            }
+            rental.escrow_balance = 0;
+            rental.prepaid_rent = 0;
+            rental.deposit_settled = true;
+        } else {
+            // 合租中已收取的分攤租金屬已到期租金，先轉交房東 (共有房源依持分分配)
+            if rental.is_collecting() {
+                let collected = rental.rent_collected;
+                let co_owned = listing.is_co_owned();
+                if rental.payment_method == PaymentMethod::Sol {
+                    if co_owned {
+                        ownership::release_to_owners(
+                            &rental.to_account_info(),
+                            &listing.owners,
+                            ctx.remaining_accounts,
+                            collected,
+                        )?;
+                    } else {
+                        escrow::release_lamports(
+                            &rental.to_account_info(),
+                            &ctx.accounts.landlord.to_account_info(),
+                            collected,
+                        )?;
+                    }
+                } else {
+                    let rent_escrow = ctx
+                        .accounts
+                        .rent_escrow_token_account
+                        .as_ref()
+                        .ok_or(RentalError::InvalidEscrowAccount)?;
+                    if co_owned {
+                        let bump = [rental.bump];
+                        let seeds: &[&[u8]] = &[b"rental", rental.listing.as_ref(), rental.tenant.as_ref(), &bump];
+                        ownership::pay_owners_usdt(
+                            rent_escrow,
+                            &rental.to_account_info(),
+                            &ctx.accounts.token_program.to_account_info(),
+                            &listing.owners,
+                            ctx.remaining_accounts,
+                            collected,
+                            &[seeds],
+                        )?;
+                    } else {
+                        cotenancy::forward_usdt(
+                            rental,
+                            rent_escrow,
+                            escrow::token_account_of(ctx.accounts.landlord_token_account.as_ref(), &rental.landlord)?,
+                            &ctx.accounts.token_program,
+                            collected,
+                        )?;
+                    }
+                }
+                rental.reset_collection();
+            }
+            
+            // 如果租客提前終止，押金不退還
+            // 如果房東提前終止，需要退還押金和剩餘租金 (不足部分由終止者支付)
+            let escrow_balance = rental.escrow_balance;
+            rental.escrow_balance = 0;
+            if by_landlord {
+                // 計算剩餘天數的租金
+                let current_time = Clock::get()?.unix_timestamp;
+                let remaining_days = (rental.end_date - current_time) / (24 * 60 * 60);
+                let total_refund = rental.deposit + (rental.price * remaining_days as u64 / 30);
+            
+                // 退款先由託管支付，剩餘的託管款項歸房東，不足部分由終止者支付
+                let (from_escrow, from_landlord) = escrow::split_refund(escrow_balance, total_refund);
+                escrow::release_lamports(
+                    &rental.to_account_info(),
+                    &ctx.accounts.tenant.to_account_info(),
+                    from_escrow,
+                )?;
+                escrow::release_lamports(
+                    &rental.to_account_info(),
+                    &ctx.accounts.landlord.to_account_info(),
+                    escrow_balance - from_escrow,
+                )?;
+            
+                // 根據支付方式支付不足部分
+                if from_landlord > 0 {
+                    if rental.payment_method == PaymentMethod::Sol {
+                        anchor_lang::system_program::transfer(
+                            CpiContext::new(
+                                ctx.accounts.system_program.to_account_info(),
+                                anchor_lang::system_program::Transfer {
+                                    from: ctx.accounts.authority.to_account_info(),
+                                    to: ctx.accounts.tenant.to_account_info(),
+                                },
+                            ),
+                            from_landlord,
+                        )?;
+                    } else {
+                        // 退款須由終止者持有的帳戶轉入租約記錄的租客
+                        let landlord_token_account = escrow::token_account_of(
+                            ctx.accounts.landlord_token_account.as_ref(),
+                            &ctx.accounts.authority.key(),
+                        )?;
+                        let tenant_token_account =
+                            escrow::token_account_of(ctx.accounts.tenant_token_account.as_ref(), &rental.tenant)?;
+                        let transfer_ctx = CpiContext::new(
+                            ctx.accounts.token_program.to_account_info(),
+                            Transfer {
+                                from: landlord_token_account.to_account_info(),
+                                to: tenant_token_account.to_account_info(),
+                                authority: ctx.accounts.authority.to_account_info(),
+                            },
+                        );
+                        token::transfer(transfer_ctx, from_landlord)?;
+                    }
+                }
+            } else {
+                // 租客提前終止，託管的押金歸房東
+                escrow::release_lamports(
+                    &rental.to_account_info(),
+                    &ctx.accounts.landlord.to_account_info(),
+                    escrow_balance,
+                )?;
+            }
+            
+            // 房東終止時押金已退還，租客終止時押金不退還，皆視為已結算
+            rental.deposit_settled = true;
+            
+            // 記錄終止方
+            let tenant_profile = &mut ctx.accounts.tenant_profile;
+            tenant_profile.record_termination(!by_landlord);
+            tenant_profile.updated_at = Clock::get()?.unix_timestamp;
+        }
+        
+        // 更新租約狀態
+        rental.status = RentalStatus::Terminated;
+        rental.updated_at = Clock::get()?.unix_timestamp;
+        
+        // 重新開放房源 (分租租約僅重新開放該房間)
+        listing.updated_at = Clock::get()?.unix_timestamp;
+        if let Some(room_id) = rental.room_id {
+            listing.release_room(room_id, &rental.key());
+        } else {
+            listing.is_available = true;
+            property::record_occupancy(listing, ctx.accounts.property.as_deref_mut(), false)?;
+            
+            // 重新加入可租房源索引
+            ctx.accounts.district_index.insert(
+                IndexKind::District,
+                listing.address.district_code,
+                listing.key(),
+            )?;
+            ctx.accounts.price_index.insert(
+                IndexKind::PriceBucket,
+                listing.price_bucket,
+                listing.key(),
+            )?;
+        }
+        
+        msg!("租約已終止");
+        Ok(())
+    }
+
+    /// 租期屆滿後由房東 (或代管人) 結算押金，退還扣除後的餘額並重新開放房源
+    pub fn settle_deposit(ctx: Context<SettleDeposit>, deduction: u64) -> Result<()> {
+        let rental = &mut ctx.accounts.rental;
+        let listing = &mut ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        Delegation::authorize(
+            &listing.key(),
+            &rental.landlord,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::SETTLE_DEPOSITS,
+            current_time,
+        )?;
+        require!(!rental.deposit_settled, RentalError::DepositAlreadySettled);
+        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        require!(current_time >= rental.end_date, RentalError::RentalNotEnded);
+        require!(deduction <= rental.deposit, RentalError::InvalidDeduction);
+        
+        // 退還扣除後的押金：先由託管支付，剩餘的託管押金歸房東，不足部分由結算者支付
+        let refund_amount = rental.deposit - deduction;
+        let (from_escrow, from_landlord) = escrow::split_refund(rental.escrow_balance, refund_amount);
+        escrow::release_lamports(
+            &rental.to_account_info(),
+            &ctx.accounts.tenant.to_account_info(),
+            from_escrow,
+        )?;
+        escrow::release_lamports(
+            &rental.to_account_info(),
+            &ctx.accounts.landlord.to_account_info(),
+            rental.escrow_balance - from_escrow,
+        )?;
+        rental.escrow_balance = 0;
+        
+        if from_landlord > 0 {
+            if rental.payment_method == PaymentMethod::Sol {
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.authority.to_account_info(),
+                            to: ctx.accounts.tenant.to_account_info(),
+                        },
+                    ),
+                    from_landlord,
+                )?;
+            } else {
+                let landlord_token_account = ctx
+                    .accounts
+                    .landlord_token_account
+                    .as_ref()
+                    .ok_or(RentalError::InvalidPaymentMethod)?;
+                let tenant_token_account = ctx
+                    .accounts
+                    .tenant_token_account
+                    .as_ref()
+                    .ok_or(RentalError::InvalidPaymentMethod)?;
+                let transfer_ctx = CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: landlord_token_account.to_account_info(),
+                        to: tenant_token_account.to_account_info(),
+                        authority: ctx.accounts.authority.to_account_info(),
+                    },
+                );
+                token::transfer(transfer_ctx, from_landlord)?;
+            }
+        }
+        
+        rental.deposit_settled = true;
+        rental.deposit_deduction = deduction;
+        rental.status = RentalStatus::Expired;
+        rental.updated_at = current_time;
+        
+        // 記錄押金扣除比例
+        let tenant_profile = &mut ctx.accounts.tenant_profile;
+        tenant_profile.record_deposit_settlement(rental.deposit, deduction);
+        tenant_profile.updated_at = current_time;
+        
+        // 重新開放房源並加入可租房源索引 (分租租約僅重新開放該房間)
+        listing.updated_at = current_time;
+        if let Some(room_id) = rental.room_id {
+            listing.release_room(room_id, &rental.key());
+        } else {
+            listing.is_available = true;
+            property::record_occupancy(listing, ctx.accounts.property.as_deref_mut(), false)?;
+            ctx.accounts.district_index.insert(
+                IndexKind::District,
+                listing.address.district_code,
+                listing.key(),
+            )?;
+            ctx.accounts.price_index.insert(
+                IndexKind::PriceBucket,
+                listing.price_bucket,
+                listing.key(),
+            )?;
+        }
+        
+        emit!(DepositSettled {
+            rental: rental.key(),
+            tenant: rental.tenant,
+            deposit: rental.deposit,
+            deduction,
+            refund_amount,
+            reputation_score: tenant_profile.reputation_score,
+            timestamp: current_time,
+        });
+        
+        msg!("押金已結算，扣除: {}，退還: {}", deduction, refund_amount);
+        Ok(())
+    }
+
+    /// 驗證房源（預言機驗證）
+    pub fn verify_listing(ctx: Context<VerifyListing>) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        // 驗證者審閱權狀後標記為已查核
+        if let Some(document) = ctx.accounts.ownership_document.as_mut() {
+            require!(
+                document.kind == DocumentKind::OwnershipCertificate,
+                RentalError::InvalidDocument
+            );
+            document.is_verified = true;
+            document.verified_by = ctx.accounts.authority.key();
+            document.verified_at = current_time;
+        }
+        
+        // 這裡可以整合 Switchboard 預言機來驗證房源資訊
+        // 目前簡單地標記為已驗證
+        listing.is_verified = true;
+        listing.updated_at = current_time;
+        
+        msg!("房源已通過預言機驗證");
+        Ok(())
+    }
+
+    /// 設定租金調漲條款 (房東或具管理租約權限的代管人與租客共同簽署)
+    ///
+    /// 自設定當期之後依 `interval_months` 週期調漲，已經過的週期不追溯；傳入 None 取消調漲。
+    /// CPI 連動須提供指數預言機帳戶，以當下指數作為基準。
+    pub fn set_rent_escalation(
+        ctx: Context<SetRentEscalation>,
+        kind: Option<EscalationKind>,
+        interval_months: u8,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &rental.listing,
+            &rental.landlord,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::MANAGE_TENANTS,
+            current_time,
+        )?;
+        
+        require!(
+            rental.status == RentalStatus::Active || rental.status == RentalStatus::Pending,
+            RentalError::RentalNotActive
+        );
+        // 合租收款期間租金已鎖定，須待本期收齊
+        require!(!rental.is_collecting(), RentalError::RentCollectionInProgress);
+        
+        rental.escalation = match kind {
+            Some(kind) => {
+                let cpi_feed = ctx.accounts.cpi_feed.as_ref().map(|feed| feed.key()).unwrap_or_default();
+                let mut escalation = RentEscalation {
+                    kind,
+                    interval_months,
+                    cpi_feed,
+                    cpi_index: 0,
+                    tier: 0,
+                };
+                require!(
+                    escalation.is_valid(rental.fiat_price.is_some()),
+                    RentalError::InvalidEscalation
+                );
+                if let (EscalationKind::CpiIndexed { .. }, Some(feed)) = (kind, &ctx.accounts.cpi_feed) {
+                    escalation.cpi_index = escalation::load_cpi_index(feed, &cpi_feed, current_time)?;
+                }
+                escalation.tier = escalation.due_tier(rental.start_date, rental.next_payment_date);
+                Some(escalation)
+            }
+            None => None,
+        };
+        rental.updated_at = current_time;
+        
+        msg!("租金調漲條款已設定，每 {} 個月調整一次", interval_months);
+        Ok(())
+    }
+
+    /// 開立帳單 (電費、水費、管理費等)，由房東或具管理租約權限的代管人依租約開立
+    ///
+    /// 退租後仍可開立最後一期帳單；金額以租約幣別計算 (SOL 租約為 lamports)。
+    pub fn issue_bill(
+        ctx: Context<IssueBill>,
+        category: BillCategory,
+        amount: u64,
+        due_date: i64,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &rental.listing,
+            &rental.landlord,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::MANAGE_TENANTS,
+            current_time,
+        )?;
+        
+        require!(rental.status != RentalStatus::Pending, RentalError::RentalNotStarted);
+        require!(Bill::is_valid_terms(amount, due_date, current_time), RentalError::InvalidBill);
+        
+        let bill = &mut ctx.accounts.bill;
+        bill.rental = rental.key();
+        bill.bill_id = rental.bill_count;
+        bill.category = category;
+        bill.amount = amount;
+        bill.due_date = due_date;
+        bill.evidence_hash = evidence_hash;
+        bill.status = BillStatus::Open;
+        bill.issued_at = current_time;
+        bill.settled_at = 0;
+        bill.bump = ctx.bumps.bill;
+        
+        rental.bill_count += 1;
+        rental.updated_at = current_time;
+        
+        emit!(BillIssued {
+            bill: bill.key(),
+            rental: rental.key(),
+            category,
+            amount,
+            due_date,
+            evidence_hash,
+        });
+        
+        msg!("帳單已開立，金額: {}，繳費期限: {}", amount, due_date);
+        Ok(())
+    }
+
+    /// 租客以租約幣別繳納帳單，款項直接交付房東
+    pub fn pay_bill(ctx: Context<PayBill>) -> Result<()> {
+        let bill = &mut ctx.accounts.bill;
+        let rental = &ctx.accounts.rental;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        bill.mark_paid(current_time)?;
+        
+        match rental.payment_method {
+            PaymentMethod::Sol => {
+                require!(
+                    ctx.accounts.tenant.to_account_info().lamports() >= bill.amount,
+                    RentalError::InsufficientFunds
+                );
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.tenant.to_account_info(),
+                            to: ctx.accounts.landlord.to_account_info(),
+                        },
+                    ),
+                    bill.amount,
+                )?;
+            }
+            PaymentMethod::Usdt => {
+                let tenant_token_account = ctx
+                    .accounts
+                    .tenant_token_account
+                    .as_ref()
+                    .ok_or(RentalError::InvalidEscrowAccount)?;
+                let landlord_token_account = ctx
+                    .accounts
+                    .landlord_token_account
+                    .as_ref()
+                    .ok_or(RentalError::InvalidEscrowAccount)?;
+                require!(tenant_token_account.amount >= bill.amount, RentalError::InsufficientFunds);
+                token::transfer(
+                    CpiContext::new(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: tenant_token_account.to_account_info(),
+                            to: landlord_token_account.to_account_info(),
+                            authority: ctx.accounts.tenant.to_account_info(),
+                        },
+                    ),
+                    bill.amount,
+                )?;
+            }
+        }
+        
+        emit!(BillPaid {
+            bill: bill.key(),
+            rental: rental.key(),
+            tenant: ctx.accounts.tenant.key(),
+            amount: bill.amount,
+            payment_method: rental.payment_method.clone(),
+            timestamp: current_time,
+        });
+        
+        msg!("帳單已繳納，金額: {}", bill.amount);
+        Ok(())
+    }
+
+    /// 租客對待繳帳單提出爭議，爭議中的帳單仍可繳納或由房東方免除
+    pub fn dispute_bill(ctx: Context<DisputeBill>) -> Result<()> {
+        let bill = &mut ctx.accounts.bill;
+        bill.dispute()?;
+        
+        emit!(BillStatusChanged {
+            bill: bill.key(),
+            rental: bill.rental,
+            status: BillStatus::Disputed,
+            changed_by: ctx.accounts.tenant.key(),
+        });
+        
+        msg!("帳單已提出爭議");
+        Ok(())
+    }
+
+    /// 房東或具管理租約權限的代管人免除尚未繳納的帳單
+    pub fn waive_bill(ctx: Context<WaiveBill>) -> Result<()> {
+        let bill = &mut ctx.accounts.bill;
+        let rental = &ctx.accounts.rental;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &rental.listing,
+            &rental.landlord,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::MANAGE_TENANTS,
+            current_time,
+        )?;
+        
+        bill.waive(current_time)?;
+        
+        emit!(BillStatusChanged {
+            bill: bill.key(),
+            rental: bill.rental,
+            status: BillStatus::Waived,
+            changed_by: ctx.accounts.authority.key(),
+        });
+        
+        msg!("帳單已免除");
+        Ok(())
+    }
+
+    /// 租客就租約開立報修單
+    pub fn open_maintenance_ticket(
+        ctx: Context<OpenMaintenanceTicket>,
+        category: MaintenanceCategory,
+        priority: MaintenancePriority,
+        description_hash: [u8; 32],
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        require!(description_hash != [0; 32], RentalError::InvalidMaintenanceTicket);
+        
+        let ticket = &mut ctx.accounts.ticket;
+        ticket.rental = rental.key();
+        ticket.ticket_id = rental.ticket_count;
+        ticket.category = category;
+        ticket.priority = priority;
+        ticket.description_hash = description_hash;
+        ticket.status = MaintenanceStatus::Open;
+        ticket.opened_at = current_time;
+        ticket.acknowledged_at = 0;
+        ticket.scheduled_for = 0;
+        ticket.resolved_at = 0;
+        ticket.confirmed_at = 0;
+        ticket.abatement_claimed = false;
+        ticket.bump = ctx.bumps.ticket;
+        
+        rental.ticket_count += 1;
+        rental.updated_at = current_time;
+        
+        emit!(MaintenanceTicketOpened {
+            ticket: ticket.key(),
+            rental: rental.key(),
+            category,
+            priority,
+            sla_deadline: current_time + priority.resolution_sla(),
+        });
+        
+        msg!("報修單已開立，編號: {}", ticket.ticket_id);
+        Ok(())
+    }
+
+    /// 房東或具管理租約權限的代管人更新報修處理進度 (受理、安排維修、回報已修繕)
+    pub fn update_maintenance_ticket(
+        ctx: Context<UpdateMaintenanceTicket>,
+        status: MaintenanceStatus,
+        scheduled_for: i64,
+    ) -> Result<()> {
+        let ticket = &mut ctx.accounts.ticket;
+        let rental = &ctx.accounts.rental;
+        let current_time = Clock::get()?.unix_timestamp;
+        Delegation::authorize(
+            &rental.listing,
+            &rental.landlord,
+            &ctx.accounts.authority.key(),
+            ctx.accounts.delegation.as_deref(),
+            Delegation::MANAGE_TENANTS,
+            current_time,
+        )?;
+        
+        ticket.advance(status, scheduled_for, current_time)?;
+        
+        emit!(MaintenanceTicketUpdated {
+            ticket: ticket.key(),
+            rental: rental.key(),
+            status,
+            updated_by: ctx.accounts.authority.key(),
+            timestamp: current_time,
+        });
+        
+        msg!("報修單進度已更新");
+        Ok(())
+    }
+
+    /// 租客確認修繕結果，不接受時報修單退回受理狀態
+    pub fn confirm_maintenance_ticket(ctx: Context<ConfirmMaintenanceTicket>, accepted: bool) -> Result<()> {
+        let ticket = &mut ctx.accounts.ticket;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        ticket.confirm(accepted, current_time)?;
+        
+        emit!(MaintenanceTicketUpdated {
+            ticket: ticket.key(),
+            rental: ticket.rental,
+            status: ticket.status,
+            updated_by: ctx.accounts.tenant.key(),
+            timestamp: current_time,
+        });
+        
+        msg!("{}", if accepted { "租客已確認修繕完成" } else { "租客未接受修繕結果" });
+        Ok(())
+    }
+
+    /// 報修單超過修繕期限後申請租金減免 (任何人皆可呼叫)，於下期 `pay_rent_*` 扣抵
+    pub fn claim_maintenance_abatement(ctx: Context<ClaimMaintenanceAbatement>) -> Result<()> {
+        let ticket = &mut ctx.accounts.ticket;
+        let rental = &mut ctx.accounts.rental;
+        let current_time = Clock::get()?.unix_timestamp;
+        
+        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        let pending_abatement_bps = rental.grant_abatement(ticket, current_time)?;
+        rental.updated_at = current_time;
+        
+        emit!(MaintenanceAbatementGranted {
+            ticket: ticket.key(),
+            rental: rental.key(),
+            abatement_bps: rental.maintenance_abatement_bps,
+            pending_abatement_bps,
+        });
+        
+        msg!("報修逾期租金減免已累計: {} 基點", pending_abatement_bps);
+        Ok(())
+    }
+
+    /// 調整租約
+    pub fn adjust_rental(
+        ctx: Context<AdjustRental>,
+        new_price: u64,
+        new_end_date: i64,
+        reason: String,
+    ) -> Result<()> {
+        InputValidator::validate_reason(&reason)?;
+        
+        let rental = &mut ctx.accounts.rental;
+        
+        // 檢查操作權限（只有房東、具管理租約權限的代管人或租客可以調整）
+        if ctx.accounts.authority.key() != rental.tenant {
+            Delegation::authorize(
+                &rental.listing,
+                &rental.landlord,
+                &ctx.accounts.authority.key(),
+                ctx.accounts.delegation.as_deref(),
+                Delegation::MANAGE_TENANTS,
+                Clock::get()?.unix_timestamp,
+            )?;
+        }
+        
+        // 檢查租約狀態
+        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        
+        // 更新租約資訊
+        rental.price = new_price;
+        rental.end_date = new_end_date;
+        rental.updated_at = Clock::get()?.unix_timestamp;
+        
+        // 發送調整事件
+        emit!(RentalAdjusted {
+            rental: rental.key(),
+            new_price,
+            new_end_date,
+            reason,
+            adjusted_by: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("租約已調整，新租金: {}, 新結束日期: {}", new_price, new_end_date);
+        Ok(())
+    }
+
+    /// 續約
+    pub fn renew_rental(
+        ctx: Context<RenewRental>,
+        months: u8,
+        new_price: u64,
+        auto_renew: bool,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental;
+        let platform = &mut ctx.accounts.platform;
+        
+        // 檢查操作權限（只有租客可以續約）
+        require!(
+            ctx.accounts.authority.key() == rental.tenant,
+            RentalError::Unauthorized
+        );
+        
+        // 檢查租約狀態
+        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        
+        // 計算新的結束日期
+        let new_end_date = rental.end_date + (months as i64 * 30 * 24 * 60 * 60);
+        
+        // 更新租約資訊
+        rental.price = new_price;
+        rental.end_date = new_end_date;
+        rental.contract_length = months;
+        rental.updated_at = Clock::get()?.unix_timestamp;
+        
+        // 發送續約事件
+        emit!(RentalRenewed {
+            rental: rental.key(),
+            months,
+            new_price,
+            new_end_date,
+            auto_renew,
+            renewed_by: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        platform.total_volume += new_price * months as u64;
+        
+        msg!("租約已續約，期數: {} 個月, 新租金: {}", months, new_price);
+        Ok(())
+    }
+
+    /// 轉讓租約
+    pub fn transfer_rental(
+        ctx: Context<TransferRental>,
+        new_tenant: Pubkey,
+        transfer_fee: u64,
+    ) -> Result<()> {
+        let rental = &mut ctx.accounts.rental;
+        
+        // 檢查操作權限（只有租客可以轉讓）
+        require!(
+            ctx.accounts.authority.key() == rental.tenant,
+            RentalError::Unauthorized
+        );
+        
+        // 檢查租約狀態
+        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        
+        // 檢查新租客不能是原租客，合租租約須先恢復為單一租客
+        require!(new_tenant != rental.tenant, RentalError::InvalidTransfer);
+        require!(!rental.is_shared(), RentalError::InvalidTransfer);
+        
+        // 轉移租約所有權
+        let old_tenant = rental.tenant;
+        rental.tenant = new_tenant;
+        rental.updated_at = Clock::get()?.unix_timestamp;
+        
+        // 發送轉讓事件
+        emit!(RentalTransferred {
+            rental: rental.key(),
+            old_tenant,
+            new_tenant,
+            transfer_fee,
+            transferred_by: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("租約已轉讓給新租客: {}", new_tenant);
+        Ok(())
+    }
+
+    /// 延期申請
+    pub fn extend_rental(
+        ctx: Context<ExtendRental>,
+        days: u8,
+        reason: String,
+    ) -> Result<()> {
+        InputValidator::validate_reason(&reason)?;
+        
+        let rental = &mut ctx.accounts.rental;
+        
+        // 檢查操作權限（只有租客可以申請延期）
+        require!(
+            ctx.accounts.authority.key() == rental.tenant,
+            RentalError::Unauthorized
+        );
+        
+        // 檢查租約狀態
+        require!(rental.status == RentalStatus::Active, RentalError::RentalNotActive);
+        
+        // 檢查延期天數合理性
+        require!(days > 0 && days <= 60, RentalError::InvalidExtension);
+        
+        // 計算新的結束日期
+        let new_end_date = rental.end_date + (days as i64 * 24 * 60 * 60);
+        
+        // 更新租約資訊
+        rental.end_date = new_end_date;
+        rental.updated_at = Clock::get()?.unix_timestamp;
+        
+        // 發送延期事件
+        emit!(RentalExtended {
+            rental: rental.key(),
+            days,
+            new_end_date,
+            reason,
+            extended_by: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("租約已延期 {} 天，新結束日期: {}", days, new_end_date);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(
         init,
-        payer = authority,
-        space = 8 + Platform::INIT_SPACE,
+        payer = authority,
+        space = 8 + Platform::INIT_SPACE,
+        seeds = [b"platform"],
+        bump
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigurePriceFeeds<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform"],
+        bump = platform.bump,
+        has_one = authority @ RentalError::Unauthorized
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetVerifier<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform"],
+        bump = platform.bump,
+        has_one = authority @ RentalError::Unauthorized
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(region_code: u32)]
+pub struct CreatePriceBand<'info> {
+    #[account(
+        init,
+        payer = verifier,
+        space = 8 + PriceBand::INIT_SPACE,
+        seeds = [b"price_band", region_code.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub price_band: Account<'info, PriceBand>,
+    
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump,
+        has_one = verifier @ RentalError::Unauthorized
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    #[account(mut)]
+    pub verifier: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePriceBand<'info> {
+    #[account(
+        mut,
+        seeds = [b"price_band", price_band.region_code.to_le_bytes().as_ref()],
+        bump = price_band.bump
+    )]
+    pub price_band: Account<'info, PriceBand>,
+    
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump,
+        has_one = verifier @ RentalError::Unauthorized
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    pub verifier: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePriceBand<'info> {
+    #[account(
+        mut,
+        close = verifier,
+        seeds = [b"price_band", price_band.region_code.to_le_bytes().as_ref()],
+        bump = price_band.bump
+    )]
+    pub price_band: Account<'info, PriceBand>,
+    
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump,
+        has_one = verifier @ RentalError::Unauthorized
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    #[account(mut)]
+    pub verifier: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureKycPolicy<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform"],
+        bump = platform.bump,
+        has_one = authority @ RentalError::Unauthorized
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(attester_key: Pubkey)]
+pub struct AddAttester<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Attester::INIT_SPACE,
+        seeds = [b"attester", attester_key.as_ref()],
+        bump
+    )]
+    pub attester: Account<'info, Attester>,
+    
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump,
+        has_one = authority @ RentalError::Unauthorized
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAttester<'info> {
+    #[account(
+        mut,
+        seeds = [b"attester", attester.attester.as_ref()],
+        bump = attester.bump,
+        close = authority
+    )]
+    pub attester: Account<'info, Attester>,
+    
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump,
+        has_one = authority @ RentalError::Unauthorized
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateLandlordProfile<'info> {
+    #[account(
+        init,
+        payer = wallet,
+        space = 8 + LandlordProfile::INIT_SPACE,
+        seeds = [b"landlord_profile", wallet.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, LandlordProfile>,
+    
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateTenantProfile<'info> {
+    #[account(
+        init,
+        payer = wallet,
+        space = 8 + TenantProfile::INIT_SPACE,
+        seeds = [b"tenant_profile", wallet.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, TenantProfile>,
+    
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AttestLandlord<'info> {
+    #[account(
+        mut,
+        seeds = [b"landlord_profile", profile.wallet.as_ref()],
+        bump = profile.bump
+    )]
+    pub profile: Account<'info, LandlordProfile>,
+    
+    /// 平台核可的認證機構
+    #[account(
+        seeds = [b"attester", issuer.key().as_ref()],
+        bump = attester.bump
+    )]
+    pub attester: Account<'info, Attester>,
+    
+    pub issuer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AttestTenant<'info> {
+    #[account(
+        mut,
+        seeds = [b"tenant_profile", profile.wallet.as_ref()],
+        bump = profile.bump
+    )]
+    pub profile: Account<'info, TenantProfile>,
+    
+    /// 平台核可的認證機構
+    #[account(
+        seeds = [b"attester", issuer.key().as_ref()],
+        bump = attester.bump
+    )]
+    pub attester: Account<'info, Attester>,
+    
+    pub issuer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeLandlordAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [b"landlord_profile", profile.wallet.as_ref()],
+        bump = profile.bump
+    )]
+    pub profile: Account<'info, LandlordProfile>,
+    
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeTenantAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [b"tenant_profile", profile.wallet.as_ref()],
+        bump = profile.bump
+    )]
+    pub profile: Account<'info, TenantProfile>,
+    
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    title: String,
+    description: String,
+    metadata: Option<MetadataAnchor>,
+    address: Address,
+    coordinates: Coordinates
+)]
+pub struct CreateListing<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = Listing::space(&description, metadata.as_ref()),
+        seeds = [b"listing", platform.key().as_ref(), &[platform.total_listings]],
+        bump
+    )]
+    pub listing: Account<'info, Listing>,
+    
+    #[account(
+        mut,
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    #[account(
+        seeds = [b"price_band", price_band.region_code.to_le_bytes().as_ref()],
+        bump = price_band.bump
+    )]
+    pub price_band: Account<'info, PriceBand>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + GeoIndex::INIT_SPACE,
+        seeds = [
+            b"geo_index",
+            &coordinates.geohash()[..GEOHASH_INDEX_PRECISION],
+            listing.key().as_ref()
+        ],
+        bump
+    )]
+    pub geo_index: Account<'info, GeoIndex>,
+    
+    #[account(mut)]
+    pub district_index: Account<'info, ListingIndex>,
+    
+    #[account(mut)]
+    pub price_index: Account<'info, ListingIndex>,
+    
+    /// CHECK: Pyth SOL/USD 價格帳戶，於指令中比對平台設定後解析
+    pub sol_usd_price_feed: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Pyth USD/TWD 價格帳戶
+    pub usd_twd_price_feed: Option<UncheckedAccount<'info>>,
+    
+    /// 房東身分檔案 (平台要求房東 KYC 時必填)
+    #[account(
+        seeds = [b"landlord_profile", authority.key().as_ref()],
+        bump = landlord_profile.bump
+    )]
+    pub landlord_profile: Option<Account<'info, LandlordProfile>>,
+    
+    /// 所屬建物 (上架建物單位時帶入)
+    #[account(
+        mut,
+        has_one = authority @ RentalError::Unauthorized
+    )]
+    pub property: Option<Account<'info, Property>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(property_id: u64)]
+pub struct CreateProperty<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Property::INIT_SPACE,
+        seeds = [b"property", authority.key().as_ref(), &property_id.to_le_bytes()],
+        bump
+    )]
+    pub property: Account<'info, Property>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProperty<'info> {
+    #[account(
+        mut,
+        has_one = authority @ RentalError::Unauthorized
+    )]
+    pub property: Account<'info, Property>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateListing<'info> {
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    #[account(
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    #[account(
+        seeds = [b"price_band", price_band.region_code.to_le_bytes().as_ref()],
+        bump = price_band.bump
+    )]
+    pub price_band: Account<'info, PriceBand>,
+    
+    /// 目前所在的價格索引 (價格分桶改變時需要)
+    #[account(mut)]
+    pub price_index: Option<Account<'info, ListingIndex>>,
+    
+    /// 新的價格索引 (價格分桶改變時需要)
+    #[account(mut)]
+    pub new_price_index: Option<Account<'info, ListingIndex>>,
+    
+    /// CHECK: Pyth SOL/USD 價格帳戶，於指令中比對平台設定後解析
+    pub sol_usd_price_feed: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Pyth USD/TWD 價格帳戶
+    pub usd_twd_price_feed: Option<UncheckedAccount<'info>>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(description: String, metadata: Option<MetadataAnchor>)]
+pub struct UpdateListingMetadata<'info> {
+    #[account(
+        mut,
+        realloc = Listing::space(&description, metadata.as_ref()),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetTenantRequirements<'info> {
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetReservationTerms<'info> {
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCancellationPolicy<'info> {
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaintenanceAbatement<'info> {
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetListingOwners<'info> {
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOffers<'info> {
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRooms<'info> {
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EnableShortStay<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + StayCalendar::INIT_SPACE,
+        seeds = [b"calendar", listing.key().as_ref()],
+        bump
+    )]
+    pub calendar: Account<'info, StayCalendar>,
+    
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateShortStay<'info> {
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DisableShortStay<'info> {
+    #[account(
+        mut,
+        seeds = [b"calendar", listing.key().as_ref()],
+        bump = calendar.bump,
+        close = landlord
+    )]
+    pub calendar: Account<'info, StayCalendar>,
+    
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    /// CHECK: 房東帳戶，收回日曆帳戶租金
+    #[account(
+        mut,
+        address = listing.authority @ RentalError::Unauthorized
+    )]
+    pub landlord: AccountInfo<'info>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(check_in_day: u32)]
+pub struct BookStay<'info> {
+    #[account(
+        init,
+        payer = guest,
+        space = 8 + StayBooking::INIT_SPACE,
+        seeds = [b"stay", listing.key().as_ref(), check_in_day.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub booking: Account<'info, StayBooking>,
+    
+    #[account(
+        mut,
+        seeds = [b"calendar", listing.key().as_ref()],
+        bump = calendar.bump
+    )]
+    pub calendar: Account<'info, StayCalendar>,
+    
+    pub listing: Account<'info, Listing>,
+    
+    #[account(mut)]
+    pub guest: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseStayPayment<'info> {
+    #[account(
+        mut,
+        has_one = listing @ RentalError::InvalidStayBooking,
+        has_one = guest @ RentalError::InvalidStayBooking,
+        close = guest
+    )]
+    pub booking: Account<'info, StayBooking>,
+    
+    pub listing: Account<'info, Listing>,
+    
+    /// 收費代管授權 (`listing.fee_delegation`)，房源未設定時省略
+    #[account(address = listing.fee_delegation @ RentalError::InvalidDelegation)]
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    /// CHECK: 代管人錢包，由 delegation.manager 驗證
+    #[account(
+        mut,
+        constraint = delegation.as_ref().map(|d| d.manager) == Some(manager.key()) @ RentalError::InvalidDelegation
+    )]
+    pub manager: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: 房東錢包，由 listing.authority 驗證
+    #[account(mut, address = listing.authority @ RentalError::Unauthorized)]
+    pub landlord: UncheckedAccount<'info>,
+    
+    /// CHECK: 預訂住客，取回預訂帳戶租金，由 booking.guest 驗證
+    #[account(mut)]
+    pub guest: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelStay<'info> {
+    #[account(
+        mut,
+        has_one = listing @ RentalError::InvalidStayBooking,
+        has_one = guest @ RentalError::InvalidStayBooking,
+        close = guest
+    )]
+    pub booking: Account<'info, StayBooking>,
+    
+    #[account(
+        mut,
+        seeds = [b"calendar", listing.key().as_ref()],
+        bump = calendar.bump
+    )]
+    pub calendar: Account<'info, StayCalendar>,
+    
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人取消時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    /// CHECK: 預訂住客，取回全額費用，由 booking.guest 驗證
+    #[account(mut)]
+    pub guest: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(manager: Pubkey)]
+pub struct GrantDelegation<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Delegation::INIT_SPACE,
+        seeds = [b"delegation", listing.key().as_ref(), manager.as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+    
+    #[account(
+        mut,
+        has_one = authority @ RentalError::Unauthorized
+    )]
+    pub listing: Account<'info, Listing>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegation<'info> {
+    #[account(
+        mut,
+        has_one = listing @ RentalError::InvalidDelegation,
+        has_one = authority @ RentalError::Unauthorized,
+        close = authority
+    )]
+    pub delegation: Account<'info, Delegation>,
+    
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(content_hash: [u8; 32])]
+pub struct AddListingDocument<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ListingDocument::INIT_SPACE,
+        seeds = [b"listing_document", listing.key().as_ref(), content_hash.as_ref()],
+        bump
+    )]
+    pub document: Account<'info, ListingDocument>,
+    
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveListingDocument<'info> {
+    #[account(
+        mut,
+        has_one = listing @ RentalError::InvalidDocument,
+        close = authority
+    )]
+    pub document: Account<'info, ListingDocument>,
+    
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: IndexKind, key: u32, page: u16)]
+pub struct CreateListingIndex<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ListingIndex::INIT_SPACE,
+        seeds = [
+            b"listing_index",
+            [kind as u8].as_ref(),
+            key.to_le_bytes().as_ref(),
+            page.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub listing_index: Account<'info, ListingIndex>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(tenant: Pubkey, document_hash: [u8; 32], version: u16)]
+pub struct ProposeLease<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LeaseAgreement::INIT_SPACE,
+        seeds = [b"lease", listing.key().as_ref(), tenant.as_ref(), &version.to_le_bytes()],
+        bump
+    )]
+    pub lease: Account<'info, LeaseAgreement>,
+    
+    pub listing: Account<'info, Listing>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    /// 房東或代管人
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SignLease<'info> {
+    #[account(
+        mut,
+        has_one = tenant @ RentalError::Unauthorized
+    )]
+    pub lease: Account<'info, LeaseAgreement>,
+    
+    pub tenant: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReserveListing<'info> {
+    #[account(
+        init,
+        payer = tenant,
+        space = 8 + Reservation::INIT_SPACE,
+        seeds = [b"reservation", listing.key().as_ref()],
+        bump
+    )]
+    pub reservation: Account<'info, Reservation>,
+    
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    #[account(mut)]
+    pub tenant: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseReservation<'info> {
+    #[account(
+        mut,
+        seeds = [b"reservation", listing.key().as_ref()],
+        bump = reservation.bump,
+        has_one = tenant @ RentalError::InvalidReservation,
+        close = tenant
+    )]
+    pub reservation: Account<'info, Reservation>,
+    
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    /// CHECK: 預留租客，取回預留費用，由 reservation.tenant 驗證
+    #[account(mut)]
+    pub tenant: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RentPropertySol<'info> {
+    #[account(
+        init,
+        payer = tenant,
+        space = 8 + Rental::INIT_SPACE,
+        seeds = [b"rental", listing.key().as_ref(), tenant.key().as_ref()],
+        bump
+    )]
+    pub rental: Account<'info, Rental>,
+    
+    #[account(
+        mut,
+        seeds = [b"listing", platform.key().as_ref(), &[listing.id]],
+        bump = listing.bump
+    )]
+    pub listing: Account<'info, Listing>,
+    
+    #[account(
+        mut,
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    /// 房源所屬建物 (建物單位必填，更新出租統計)
+    #[account(
+        mut,
+        address = listing.property @ RentalError::PropertyMismatch
+    )]
+    pub property: Option<Account<'info, Property>>,
+    
+    #[account(mut)]
+    pub district_index: Account<'info, ListingIndex>,
+    
+    #[account(mut)]
+    pub price_index: Account<'info, ListingIndex>,
+    
+    /// 房東身分檔案 (平台要求房東 KYC 時必填)
+    #[account(
+        seeds = [b"landlord_profile", listing.authority.as_ref()],
+        bump = landlord_profile.bump
+    )]
+    pub landlord_profile: Option<Account<'info, LandlordProfile>>,
+    
+    /// 租客身分檔案 (KYC 認證與信用分數)
+    #[account(
+        seeds = [b"tenant_profile", tenant.key().as_ref()],
+        bump = tenant_profile.bump
+    )]
+    pub tenant_profile: Account<'info, TenantProfile>,
+    
+    /// 雙方已簽署的租約文件 (未使用離線要約時必填)
+    #[account(mut)]
+    pub lease: Option<Account<'info, LeaseAgreement>>,
+    
+    /// 租客的預留 (轉為租約時帶入，預留費用抵付押金)
+    #[account(
+        mut,
+        seeds = [b"reservation", listing.key().as_ref()],
+        bump = reservation.bump,
+        close = tenant
+    )]
+    pub reservation: Option<Account<'info, Reservation>>,
+    
+    /// CHECK: 指令 sysvar，用於讀取離線要約的 Ed25519 簽章指令
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Pyth SOL/USD 價格帳戶 (法幣計價房源使用)，於指令中比對平台設定後解析
+    pub sol_usd_price_feed: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Pyth USD/TWD 價格帳戶 (新台幣計價房源使用)
+    pub usd_twd_price_feed: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: 房東帳戶
+    #[account(
+        mut,
+        address = listing.authority @ RentalError::Unauthorized
+    )]
+    pub landlord: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub tenant: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RentPropertyUsdt<'info> {
+    #[account(
+        init,
+        payer = tenant,
+        space = 8 + Rental::INIT_SPACE,
+        seeds = [b"rental", listing.key().as_ref(), tenant.key().as_ref()],
+        bump
+    )]
+    pub rental: Account<'info, Rental>,
+    
+    #[account(
+        mut,
+        seeds = [b"listing", platform.key().as_ref(), &[listing.id]],
+        bump = listing.bump
+    )]
+    pub listing: Account<'info, Listing>,
+    
+    #[account(
+        mut,
         seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    /// 房源所屬建物 (建物單位必填，更新出租統計)
+    #[account(
+        mut,
+        address = listing.property @ RentalError::PropertyMismatch
+    )]
+    pub property: Option<Account<'info, Property>>,
+    
+    #[account(mut)]
+    pub district_index: Account<'info, ListingIndex>,
+    
+    #[account(mut)]
+    pub price_index: Account<'info, ListingIndex>,
+    
+    /// 房東身分檔案 (平台要求房東 KYC 時必填)
+    #[account(
+        seeds = [b"landlord_profile", listing.authority.as_ref()],
+        bump = landlord_profile.bump
+    )]
+    pub landlord_profile: Option<Account<'info, LandlordProfile>>,
+    
+    /// 租客身分檔案 (KYC 認證與信用分數)
+    #[account(
+        seeds = [b"tenant_profile", tenant.key().as_ref()],
+        bump = tenant_profile.bump
+    )]
+    pub tenant_profile: Account<'info, TenantProfile>,
+    
+    /// 雙方已簽署的租約文件 (未使用離線要約時必填)
+    #[account(mut)]
+    pub lease: Option<Account<'info, LeaseAgreement>>,
+    
+    /// CHECK: 指令 sysvar，用於讀取離線要約的 Ed25519 簽章指令
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: 房東帳戶
+    #[account(
+        mut,
+        address = listing.authority @ RentalError::Unauthorized
+    )]
+    pub landlord: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub tenant: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"landlord_token_account"],
+        bump = landlord_token_account.bump
+    )]
+    pub landlord_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"tenant_token_account"],
+        bump = tenant_token_account.bump
+    )]
+    pub tenant_token_account: Account<'info, TokenAccount>,
+    
+    /// 起租前託管押金與首月租金的 USDT 託管帳戶 (入住日尚未到達時必填)
+    #[account(
+        mut,
+        constraint = rent_escrow_token_account.owner == rental.key() @ RentalError::InvalidEscrowAccount,
+        constraint = rent_escrow_token_account.mint == tenant_token_account.mint @ RentalError::InvalidEscrowAccount
+    )]
+    pub rent_escrow_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitApplication<'info> {
+    #[account(
+        init,
+        payer = tenant,
+        space = 8 + RentalApplication::INIT_SPACE,
+        seeds = [b"application", listing.key().as_ref(), tenant.key().as_ref()],
+        bump
+    )]
+    pub application: Account<'info, RentalApplication>,
+    
+    pub listing: Account<'info, Listing>,
+    
+    #[account(
+        seeds = [b"tenant_profile", tenant.key().as_ref()],
+        bump = tenant_profile.bump
+    )]
+    pub tenant_profile: Account<'info, TenantProfile>,
+    
+    #[account(mut)]
+    pub tenant: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptApplication<'info> {
+    #[account(
+        mut,
+        has_one = listing @ RentalError::InvalidApplication,
+        has_one = tenant @ RentalError::InvalidApplication,
+        close = tenant
+    )]
+    pub application: Account<'info, RentalApplication>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Rental::INIT_SPACE,
+        seeds = [b"rental", listing.key().as_ref(), application.tenant.as_ref()],
         bump
     )]
-    pub platform: Account<'info, Platform>,
+    pub rental: Account<'info, Rental>,
+    
+    #[account(mut)]
+    pub listing: Account<'info, Listing>,
+    
+    #[account(
+        mut,
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    /// 房源所屬建物 (建物單位必填，更新出租統計)
+    #[account(
+        mut,
+        address = listing.property @ RentalError::PropertyMismatch
+    )]
+    pub property: Option<Account<'info, Property>>,
+    
+    #[account(mut)]
+    pub district_index: Account<'info, ListingIndex>,
+    
+    #[account(mut)]
+    pub price_index: Account<'info, ListingIndex>,
+    
+    #[account(
+        seeds = [b"tenant_profile", application.tenant.as_ref()],
+        bump = tenant_profile.bump
+    )]
+    pub tenant_profile: Account<'info, TenantProfile>,
+    
+    /// 房東身分檔案 (平台要求房東 KYC 時必填)
+    #[account(
+        seeds = [b"landlord_profile", listing.authority.as_ref()],
+        bump = landlord_profile.bump
+    )]
+    pub landlord_profile: Option<Account<'info, LandlordProfile>>,
+    
+    /// 雙方已簽署的租約文件
+    #[account(mut)]
+    pub lease: Account<'info, LeaseAgreement>,
+    
+    /// CHECK: 申請租客，由 application.tenant 驗證
+    #[account(mut)]
+    pub tenant: UncheckedAccount<'info>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    /// 房東或代管人 (支付租約帳戶租金)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RejectApplication<'info> {
+    #[account(
+        mut,
+        has_one = listing @ RentalError::InvalidApplication,
+        has_one = tenant @ RentalError::InvalidApplication,
+        close = tenant
+    )]
+    pub application: Account<'info, RentalApplication>,
+    
+    pub listing: Account<'info, Listing>,
+    
+    /// CHECK: 申請租客，由 application.tenant 驗證
+    #[account(mut)]
+    pub tenant: UncheckedAccount<'info>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawApplication<'info> {
+    #[account(
+        mut,
+        has_one = listing @ RentalError::InvalidApplication,
+        has_one = tenant @ RentalError::InvalidApplication,
+        close = tenant
+    )]
+    pub application: Account<'info, RentalApplication>,
+    
+    pub listing: Account<'info, Listing>,
+    
+    /// CHECK: 申請租客，由 application.tenant 驗證
+    #[account(mut)]
+    pub tenant: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PayRentSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
+        bump = rental.bump
+    )]
+    pub rental: Account<'info, Rental>,
+    
+    #[account(
+        mut,
+        seeds = [b"platform"],
+        bump = platform.bump
+    )]
+    pub platform: Account<'info, Platform>,
+    
+    /// 房源 (共有人與持分)
+    #[account(address = rental.listing)]
+    pub listing: Account<'info, Listing>,
+    
+    /// CHECK: 房東帳戶
+    #[account(
+        mut,
+        address = rental.landlord @ RentalError::Unauthorized
+    )]
+    pub landlord: AccountInfo<'info>,
+    
+    /// 付款租客 (合租時可為任一租客)
+    #[account(mut)]
+    pub tenant: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"tenant_profile", tenant.key().as_ref()],
+        bump = tenant_profile.bump
+    )]
+    pub tenant_profile: Account<'info, TenantProfile>,
     
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    /// CHECK: Pyth SOL/USD 價格帳戶，於指令中比對平台設定後解析
+    pub sol_usd_price_feed: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Pyth USD/TWD 價格帳戶 (新台幣計價租約使用)
+    pub usd_twd_price_feed: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: CPI 指數預言機 (CPI 連動調漲生效當期必填)，於指令中比對 `rental.escalation.cpi_feed`
+    pub cpi_feed: Option<UncheckedAccount<'info>>,
+    
+    /// 收取管理費的代管授權 (房源設有收費代管時必填)
+    #[account(address = listing.fee_delegation @ RentalError::InvalidDelegation)]
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    /// CHECK: 代管人帳戶，收取管理費
+    #[account(
+        mut,
+        constraint = delegation.as_ref().map(|delegation| delegation.manager) == Some(manager.key())
+            @ RentalError::InvalidDelegation
+    )]
+    pub manager: Option<UncheckedAccount<'info>>,
     
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CreateListing<'info> {
+pub struct PayRentUsdt<'info> {
     #[account(
-        init,
-        payer = authority,
-        space = 8 + Listing::INIT_SPACE,
-        seeds = [b"listing", platform.key().as_ref(), &[platform.total_listings]],
-        bump
+        mut,
+        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
+        bump = rental.bump
     )]
-    pub listing: Account<'info, Listing>,
+    pub rental: Account<'info, Rental>,
     
     #[account(
         mut,
@@ -557,20 +4747,125 @@ pub struct CreateListing<'info> {
     )]
     pub platform: Account<'info, Platform>,
     
+    /// 房源 (共有人與持分)
+    #[account(address = rental.listing)]
+    pub listing: Account<'info, Listing>,
+    
+    /// CHECK: 房東帳戶
+    #[account(
+        mut,
+        address = rental.landlord @ RentalError::Unauthorized
+    )]
+    pub landlord: AccountInfo<'info>,
+    
+    /// 付款租客 (合租時可為任一租客)
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub tenant: Signer<'info>,
     
+    #[account(
+        mut,
+        seeds = [b"tenant_profile", tenant.key().as_ref()],
+        bump = tenant_profile.bump
+    )]
+    pub tenant_profile: Account<'info, TenantProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"landlord_token_account"],
+        bump = landlord_token_account.bump
+    )]
+    pub landlord_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"tenant_token_account"],
+        bump = tenant_token_account.bump
+    )]
+    pub tenant_token_account: Account<'info, TokenAccount>,
+    
+    /// 合租分攤租金的 USDT 託管帳戶 (由租約 PDA 持有)
+    #[account(
+        mut,
+        constraint = rent_escrow_token_account.owner == rental.key() @ RentalError::InvalidEscrowAccount,
+        constraint = rent_escrow_token_account.mint == tenant_token_account.mint @ RentalError::InvalidEscrowAccount
+    )]
+    pub rent_escrow_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// CHECK: CPI 指數預言機 (CPI 連動調漲生效當期必填)，於指令中比對 `rental.escalation.cpi_feed`
+    pub cpi_feed: Option<UncheckedAccount<'info>>,
+    
+    /// 收取管理費的代管授權 (房源設有收費代管時必填)
+    #[account(address = listing.fee_delegation @ RentalError::InvalidDelegation)]
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    /// 代管人 USDT 帳戶，收取管理費
+    #[account(
+        mut,
+        constraint = delegation.as_ref().map(|delegation| delegation.manager) == Some(manager_token_account.owner)
+            @ RentalError::InvalidDelegation,
+        constraint = manager_token_account.mint == tenant_token_account.mint @ RentalError::InvalidDelegation
+    )]
+    pub manager_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RentPropertySol<'info> {
+pub struct ConfigureCoTenants<'info> {
     #[account(
-        init,
-        payer = tenant,
-        space = 8 + Rental::INIT_SPACE,
-        seeds = [b"rental", listing.key().as_ref(), tenant.key().as_ref()],
-        bump
+        mut,
+        has_one = tenant @ RentalError::Unauthorized
+    )]
+    pub rental: Account<'info, Rental>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    /// 房東或代管人
+    pub authority: Signer<'info>,
+    
+    pub tenant: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ActivateRental<'info> {
+    #[account(
+        mut,
+        has_one = listing @ RentalError::Unauthorized,
+        has_one = landlord @ RentalError::Unauthorized
+    )]
+    pub rental: Account<'info, Rental>,
+    
+    pub listing: Account<'info, Listing>,
+    
+    /// CHECK: 房東錢包，由 rental.landlord 驗證
+    #[account(mut)]
+    pub landlord: UncheckedAccount<'info>,
+    
+    /// 房東 USDT 帳戶 (USDT 租約必填)
+    #[account(
+        mut,
+        constraint = landlord_token_account.owner == rental.landlord @ RentalError::InvalidEscrowAccount
+    )]
+    pub landlord_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// 起租前託管押金與首月租金的 USDT 託管帳戶 (USDT 租約必填)
+    #[account(
+        mut,
+        constraint = rent_escrow_token_account.owner == rental.key() @ RentalError::InvalidEscrowAccount
+    )]
+    pub rent_escrow_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct TerminateRental<'info> {
+    #[account(
+        mut,
+        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
+        bump = rental.bump
     )]
     pub rental: Account<'info, Rental>,
     
@@ -581,35 +4876,136 @@ pub struct RentPropertySol<'info> {
     )]
     pub listing: Account<'info, Listing>,
     
+    /// 房源所屬建物 (建物單位必填，更新出租統計)
     #[account(
         mut,
-        seeds = [b"platform"],
-        bump = platform.bump
+        address = listing.property @ RentalError::PropertyMismatch
     )]
-    pub platform: Account<'info, Platform>,
+    pub property: Option<Account<'info, Property>>,
+    
+    #[account(mut)]
+    pub district_index: Account<'info, ListingIndex>,
+    
+    #[account(mut)]
+    pub price_index: Account<'info, ListingIndex>,
     
     /// CHECK: 房東帳戶
     #[account(mut)]
     pub landlord: AccountInfo<'info>,
     
+    /// CHECK: 租客帳戶
     #[account(mut)]
-    pub tenant: Signer<'info>,
+    pub tenant: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"tenant_profile", rental.tenant.as_ref()],
+        bump = tenant_profile.bump
+    )]
+    pub tenant_profile: Account<'info, TenantProfile>,
+    
+    /// 代管授權 (由代管人終止時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    /// 租客、房東或代管人 (房東方終止時支付押金與剩餘租金退款)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// 房東方 USDT 帳戶 (可選)：房東方終止時須由 authority 持有，支付退款不足部分；
+    /// 起租前取消或轉交已收取的分攤租金時須由房東持有
+    #[account(mut)]
+    pub landlord_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// 租客 USDT 帳戶 (可選)
+    #[account(
+        mut,
+        constraint = tenant_token_account.owner == rental.tenant @ RentalError::Unauthorized
+    )]
+    pub tenant_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// 合租分攤租金或起租前款項的 USDT 託管帳戶 (收取中終止或起租前取消時必填)
+    #[account(
+        mut,
+        constraint = rent_escrow_token_account.owner == rental.key() @ RentalError::InvalidEscrowAccount
+    )]
+    pub rent_escrow_token_account: Option<Account<'info, TokenAccount>>,
     
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RentPropertyUsdt<'info> {
+pub struct SettleDeposit<'info> {
     #[account(
-        init,
-        payer = tenant,
-        space = 8 + Rental::INIT_SPACE,
-        seeds = [b"rental", listing.key().as_ref(), tenant.key().as_ref()],
-        bump
+        mut,
+        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
+        bump = rental.bump,
+        has_one = landlord @ RentalError::Unauthorized,
+        has_one = tenant @ RentalError::Unauthorized
     )]
     pub rental: Account<'info, Rental>,
     
+    #[account(
+        mut,
+        address = rental.listing @ RentalError::Unauthorized
+    )]
+    pub listing: Account<'info, Listing>,
+    
+    /// 房源所屬建物 (建物單位必填，更新出租統計)
+    #[account(
+        mut,
+        address = listing.property @ RentalError::PropertyMismatch
+    )]
+    pub property: Option<Account<'info, Property>>,
+    
+    #[account(mut)]
+    pub district_index: Account<'info, ListingIndex>,
+    
+    #[account(mut)]
+    pub price_index: Account<'info, ListingIndex>,
+    
+    #[account(
+        mut,
+        seeds = [b"tenant_profile", rental.tenant.as_ref()],
+        bump = tenant_profile.bump
+    )]
+    pub tenant_profile: Account<'info, TenantProfile>,
+    
+    /// CHECK: 房東帳戶，由 rental.landlord 驗證 (收取剩餘的託管押金)
+    #[account(mut)]
+    pub landlord: UncheckedAccount<'info>,
+    
+    /// CHECK: 租客帳戶，由 rental.tenant 驗證
+    #[account(mut)]
+    pub tenant: UncheckedAccount<'info>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    /// 房東或代管人 (支付託管不足的退款)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// 結算者 USDT 帳戶 (USDT 租約需要)
+    #[account(
+        mut,
+        constraint = landlord_token_account.owner == authority.key() @ RentalError::Unauthorized
+    )]
+    pub landlord_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// 租客 USDT 帳戶 (USDT 租約需要)
+    #[account(
+        mut,
+        constraint = tenant_token_account.owner == rental.tenant @ RentalError::Unauthorized
+    )]
+    pub tenant_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyListing<'info> {
     #[account(
         mut,
         seeds = [b"listing", platform.key().as_ref(), &[listing.id]],
@@ -618,107 +5014,220 @@ pub struct RentPropertyUsdt<'info> {
     pub listing: Account<'info, Listing>,
     
     #[account(
-        mut,
         seeds = [b"platform"],
-        bump = platform.bump
+        bump = platform.bump,
+        constraint = platform.verifier == authority.key() @ RentalError::Unauthorized
     )]
     pub platform: Account<'info, Platform>,
     
-    /// CHECK: 房東帳戶
+    /// 待查核的權狀文件 (選填)
+    #[account(
+        mut,
+        constraint = ownership_document.listing == listing.key() @ RentalError::InvalidDocument
+    )]
+    pub ownership_document: Option<Account<'info, ListingDocument>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRentEscalation<'info> {
+    #[account(
+        mut,
+        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
+        bump = rental.bump,
+        has_one = tenant @ RentalError::Unauthorized
+    )]
+    pub rental: Account<'info, Rental>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    /// 房東或代管人
+    pub authority: Signer<'info>,
+    
+    pub tenant: Signer<'info>,
+    
+    /// CHECK: CPI 指數預言機 (Pyth 格式價格帳戶，CPI 連動調漲必填)
+    pub cpi_feed: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct IssueBill<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Bill::INIT_SPACE,
+        seeds = [b"bill", rental.key().as_ref(), rental.bill_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bill: Account<'info, Bill>,
+    
     #[account(mut)]
-    pub landlord: AccountInfo<'info>,
+    pub rental: Account<'info, Rental>,
+    
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
     
+    /// 房東或代管人
     #[account(mut)]
-    pub tenant: Signer<'info>,
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
 
+#[derive(Accounts)]
+pub struct PayBill<'info> {
     #[account(
         mut,
-        seeds = [b"landlord_token_account"],
-        bump = landlord_token_account.bump
+        has_one = rental @ RentalError::InvalidBill
     )]
-    pub landlord_token_account: Account<'info, TokenAccount>,
-
+    pub bill: Account<'info, Bill>,
+    
+    #[account(
+        has_one = landlord @ RentalError::Unauthorized,
+        has_one = tenant @ RentalError::Unauthorized
+    )]
+    pub rental: Account<'info, Rental>,
+    
+    /// CHECK: 房東錢包，由 rental.landlord 驗證
+    #[account(mut)]
+    pub landlord: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub tenant: Signer<'info>,
+    
+    /// 租客 USDT 帳戶 (USDT 租約必填)
     #[account(
         mut,
-        seeds = [b"tenant_token_account"],
-        bump = tenant_token_account.bump
+        constraint = tenant_token_account.owner == tenant.key() @ RentalError::InvalidEscrowAccount
     )]
-    pub tenant_token_account: Account<'info, TokenAccount>,
+    pub tenant_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// 房東 USDT 帳戶 (USDT 租約必填)
+    #[account(
+        mut,
+        constraint = landlord_token_account.owner == rental.landlord @ RentalError::InvalidEscrowAccount
+    )]
+    pub landlord_token_account: Option<Account<'info, TokenAccount>>,
     
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct PayRentSol<'info> {
+pub struct DisputeBill<'info> {
+    #[account(
+        mut,
+        has_one = rental @ RentalError::InvalidBill
+    )]
+    pub bill: Account<'info, Bill>,
+    
+    #[account(has_one = tenant @ RentalError::Unauthorized)]
+    pub rental: Account<'info, Rental>,
+    
+    pub tenant: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WaiveBill<'info> {
     #[account(
         mut,
-        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
-        bump = rental.bump
+        has_one = rental @ RentalError::InvalidBill
     )]
+    pub bill: Account<'info, Bill>,
+    
     pub rental: Account<'info, Rental>,
     
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OpenMaintenanceTicket<'info> {
     #[account(
-        mut,
-        seeds = [b"platform"],
-        bump = platform.bump
+        init,
+        payer = tenant,
+        space = 8 + MaintenanceTicket::INIT_SPACE,
+        seeds = [b"maintenance", rental.key().as_ref(), rental.ticket_count.to_le_bytes().as_ref()],
+        bump
     )]
-    pub platform: Account<'info, Platform>,
+    pub ticket: Account<'info, MaintenanceTicket>,
     
-    /// CHECK: 房東帳戶
-    #[account(mut)]
-    pub landlord: AccountInfo<'info>,
+    #[account(
+        mut,
+        has_one = tenant @ RentalError::Unauthorized
+    )]
+    pub rental: Account<'info, Rental>,
     
     #[account(mut)]
     pub tenant: Signer<'info>,
     
-    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct PayRentUsdt<'info> {
+pub struct UpdateMaintenanceTicket<'info> {
     #[account(
         mut,
-        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
-        bump = rental.bump
+        has_one = rental @ RentalError::InvalidMaintenanceTicket
     )]
+    pub ticket: Account<'info, MaintenanceTicket>,
+    
     pub rental: Account<'info, Rental>,
     
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmMaintenanceTicket<'info> {
     #[account(
         mut,
-        seeds = [b"platform"],
-        bump = platform.bump
+        has_one = rental @ RentalError::InvalidMaintenanceTicket
     )]
-    pub platform: Account<'info, Platform>,
+    pub ticket: Account<'info, MaintenanceTicket>,
     
-    /// CHECK: 房東帳戶
-    #[account(mut)]
-    pub landlord: AccountInfo<'info>,
+    #[account(has_one = tenant @ RentalError::Unauthorized)]
+    pub rental: Account<'info, Rental>,
     
-    #[account(mut)]
     pub tenant: Signer<'info>,
+}
 
+#[derive(Accounts)]
+pub struct ClaimMaintenanceAbatement<'info> {
     #[account(
         mut,
-        seeds = [b"landlord_token_account"],
-        bump = landlord_token_account.bump
+        has_one = rental @ RentalError::InvalidMaintenanceTicket
     )]
-    pub landlord_token_account: Account<'info, TokenAccount>,
+    pub ticket: Account<'info, MaintenanceTicket>,
+    
+    #[account(mut)]
+    pub rental: Account<'info, Rental>,
+}
 
+#[derive(Accounts)]
+pub struct AdjustRental<'info> {
     #[account(
         mut,
-        seeds = [b"tenant_token_account"],
-        bump = tenant_token_account.bump
+        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
+        bump = rental.bump
     )]
-    pub tenant_token_account: Account<'info, TokenAccount>,
+    pub rental: Account<'info, Rental>,
     
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    /// 代管授權 (由代管人操作時必填)
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct TerminateRental<'info> {
+pub struct RenewRental<'info> {
     #[account(
         mut,
         seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
@@ -728,168 +5237,722 @@ pub struct TerminateRental<'info> {
     
     #[account(
         mut,
-        seeds = [b"listing", platform.key().as_ref(), &[listing.id]],
-        bump = listing.bump
+        seeds = [b"platform"],
+        bump = platform.bump
     )]
-    pub listing: Account<'info, Listing>,
-    
-    /// CHECK: 房東帳戶
-    #[account(mut)]
-    pub landlord: AccountInfo<'info>,
-    
-    /// CHECK: 租客帳戶
-    #[account(mut)]
-    pub tenant: AccountInfo<'info>,
+    pub platform: Account<'info, Platform>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
-
-    /// CHECK: 房東 USDT 帳戶 (可選)
-    #[account(mut)]
-    pub landlord_token_account: Option<Account<'info, TokenAccount>>,
-
-    /// CHECK: 租客 USDT 帳戶 (可選)
-    #[account(mut)]
-    pub tenant_token_account: Option<Account<'info, TokenAccount>>,
-    
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct VerifyListing<'info> {
+pub struct TransferRental<'info> {
     #[account(
         mut,
-        seeds = [b"listing", platform.key().as_ref(), &[listing.id]],
-        bump = listing.bump
+        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
+        bump = rental.bump
     )]
-    pub listing: Account<'info, Listing>,
+    pub rental: Account<'info, Rental>,
     
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendRental<'info> {
     #[account(
-        seeds = [b"platform"],
-        bump = platform.bump
+        mut,
+        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
+        bump = rental.bump
     )]
-    pub platform: Account<'info, Platform>,
+    pub rental: Account<'info, Rental>,
     
+    #[account(mut)]
     pub authority: Signer<'info>,
 }
 
-#[derive(Accounts)]
-pub struct AdjustRental<'info> {
-    #[account(
-        mut,
-        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
-        bump = rental.bump
-    )]
-    pub rental: Account<'info, Rental>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
+#[account]
+#[derive(InitSpace)]
+pub struct Platform {
+    pub authority: Pubkey,
+    pub verifier: Pubkey,
+    pub bump: u8,
+    pub total_listings: u64,
+    pub total_rentals: u64,
+    pub total_volume: u64,
+    pub sol_usd_price_feed: Pubkey,
+    pub usd_twd_price_feed: Pubkey,
+    pub max_price_age: u64,
+    pub max_price_conf_bps: u16,
+    pub kyc_policy: KycPolicy,
+}
+
+/// 多單位建物 (每位屋主與建物編號一份)
+///
+/// 建物內各單位為獨立的 `Listing`，以 `Listing.property` 指向此帳戶並共用地址與公共設施。
+#[account]
+#[derive(InitSpace)]
+pub struct Property {
+    pub authority: Pubkey,
+    pub property_id: u64,
+    #[max_len(100)]
+    pub name: String,
+    /// 建物地址 (`floor` 為 0、不含單位)
+    pub address: Address,
+    pub coordinates: Coordinates,
+    pub total_floors: u8,
+    /// 公共設施，單位上架時併入房源設施
+    pub amenities: Amenities,
+    pub total_units: u16,
+    /// 出租中的單位數
+    pub occupied_units: u16,
+    /// 已上架單位的最高樓層
+    pub top_unit_floor: u8,
+    pub bump: u8,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Listing {
+    pub authority: Pubkey,
+    pub bump: u8,
+    pub id: u64,
+    /// 設施配備位元組合，固定位於 `LISTING_AMENITIES_OFFSET` 供 memcmp 查詢
+    pub amenities: Amenities,
+    #[max_len(100)]
+    pub title: String,
+    #[max_len(500)]
+    pub description: String,
+    /// 鏈下中繼資料 (照片、格局圖、完整描述)，設定時 `description` 留空
+    pub metadata: Option<MetadataAnchor>,
+    /// 格式化地址，由 `address` 產生供前端顯示
+    #[max_len(200)]
+    pub location: String,
+    pub address: Address,
+    pub coordinates: Coordinates,
+    pub geohash: [u8; GEOHASH_PRECISION],
+    pub payment_method: PaymentMethod,
+    pub price: u64,
+    pub deposit: u64,
+    pub size: u32,
+    pub rooms: u8,
+    pub bathrooms: u8,
+    pub floor: u8,
+    pub total_floors: u8,
+    pub contract_length: u8,
+    pub move_in_date: i64,
+    /// 自訂標籤 (最多 4 個，每個最長 16 bytes)
+    #[max_len(4, 16)]
+    pub custom_tags: Vec<String>,
+    pub fiat_price: Option<FiatPrice>,
+    /// 新台幣月租價格分桶 (每 5,000 元一桶)
+    pub price_bucket: u32,
+    /// 租客最低信用分數 (0 表示不限制)
+    pub min_tenant_score: u16,
+    /// 是否須經申請審核 (停用 `rent_property_*` 直接租用)
+    pub requires_application: bool,
+    /// 已使用的最大離線要約 nonce (防止重放)
+    pub offer_nonce: u64,
+    /// 共有人與持分 (空清單表示由 `authority` 單獨持有)
+    #[max_len(5)]
+    pub owners: Vec<OwnerShare>,
+    /// 收取管理費的代管授權 (預設值表示無)
+    pub fee_delegation: Pubkey,
+    /// 所屬建物 (預設值表示獨立房源)
+    pub property: Pubkey,
+    /// 分租房間 (空清單表示整層出租)
+    #[max_len(8)]
+    pub room_units: Vec<RoomUnit>,
+    /// 短租條件 (設定時僅接受按晚預訂)
+    pub short_stay: Option<ShortStayTerms>,
+    /// 預留費用 (lamports，0 表示不開放預留) 與保留期間 (秒)
+    pub reservation_fee: u64,
+    pub reservation_period: i64,
+    /// 保留中的租客與保留到期時間 (到期後即不再阻擋)
+    pub reserved_by: Pubkey,
+    pub reserved_until: i64,
+    /// 起租前取消的退款規則 (建立租約時記錄於租約)
+    pub cancellation_policy: CancellationPolicy,
+    /// 報修逾期的租金減免比例 (基點，建立租約時記錄於租約)
+    pub maintenance_abatement_bps: u16,
+    pub is_available: bool,
+    pub is_verified: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Rental {
+    pub rental_id: u64,
+    pub listing: Pubkey,
+    pub landlord: Pubkey,
+    pub tenant: Pubkey,
+    pub price: u64,
+    pub deposit: u64,
+    pub contract_length: u8,
+    pub start_date: i64,
+    pub end_date: i64,
+    pub next_payment_date: i64,
+    pub status: RentalStatus,
+    pub payment_method: PaymentMethod,
+    pub fiat_price: Option<FiatPrice>,
+    /// 押金是否已結算 (退還或扣除)
+    pub deposit_settled: bool,
+    pub deposit_deduction: u64,
+    /// 租約帳戶託管的押金 (lamports，不含租金豁免餘額)
+    pub escrow_balance: u64,
+    /// 雙方簽署的租約文件雜湊與版本
+    pub lease_document_hash: [u8; 32],
+    pub lease_version: u16,
+    /// 租約完成雙方簽署的時間
+    pub lease_signed_at: i64,
+    /// 合租租客與分攤比例 (含主要租客，空清單表示單一租客)
+    #[max_len(5)]
+    pub co_tenants: Vec<RentShare>,
+    /// 本期已收取的分攤租金
+    pub rent_collected: u64,
+    /// 本期應付金額 (第一筆分攤付款時鎖定)
+    pub rent_period_due: u64,
+    /// 本期已付款租客 (依 `co_tenants` 索引的位元遮罩)
+    pub co_tenants_paid: u8,
+    /// 分租的房間編號 (整層出租為 None)
+    pub room_id: Option<u8>,
+    /// 起租前託管的首月租金，起租 (`activate_rental`) 時交付房東
+    pub prepaid_rent: u64,
+    /// 建立租約時的起租前取消退款規則
+    pub cancellation_policy: CancellationPolicy,
+    /// 租金調漲條款
+    pub escalation: Option<RentEscalation>,
+    /// 已開立的帳單數 (下一張帳單的編號)
+    pub bill_count: u32,
+    /// 已開立的報修單數 (下一張報修單的編號)
+    pub ticket_count: u32,
+    /// 每張逾期報修單的租金減免比例 (基點)
+    pub maintenance_abatement_bps: u16,
+    /// 累計待扣抵的租金減免 (基點)，下期付款時扣抵
+    pub pending_abatement_bps: u16,
+    /// 尚未收取的押金 (申請保證金不足押金的部分)，於第一期租金時由租客補足
+    pub deposit_due: u64,
+    pub bump: u8,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[cfg(test)]
+impl Rental {
+    /// 單元測試共用的單一租客 SOL 租約，各測試僅覆寫所需欄位
+    pub fn test_fixture() -> Rental {
+        Rental {
+            rental_id: 1,
+            listing: Pubkey::new_unique(),
+            landlord: Pubkey::new_unique(),
+            tenant: Pubkey::new_unique(),
+            price: 1_000,
+            deposit: 2_000,
+            contract_length: 12,
+            start_date: 0,
+            end_date: 0,
+            next_payment_date: 0,
+            status: RentalStatus::Active,
+            payment_method: PaymentMethod::Sol,
+            fiat_price: None,
+            deposit_settled: false,
+            deposit_deduction: 0,
+            escrow_balance: 0,
+            lease_document_hash: [0; 32],
+            lease_version: 1,
+            lease_signed_at: 0,
+            co_tenants: Vec::new(),
+            rent_collected: 0,
+            rent_period_due: 0,
+            co_tenants_paid: 0,
+            room_id: None,
+            prepaid_rent: 0,
+            cancellation_policy: CancellationPolicy::default(),
+            escalation: None,
+            bill_count: 0,
+            ticket_count: 0,
+            maintenance_abatement_bps: 0,
+            pending_abatement_bps: 0,
+            deposit_due: 0,
+            bump: 255,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+}
+
+/// 租約文件 (每個房源、租客與版本一份)
+///
+/// 房東提出時即視為房東簽署，租客以相同雜湊與版本簽署後方可用於建立租約。
+#[account]
+#[derive(InitSpace)]
+pub struct LeaseAgreement {
+    pub listing: Pubkey,
+    pub landlord: Pubkey,
+    pub tenant: Pubkey,
+    /// 租約全文雜湊 (SHA-256)
+    pub document_hash: [u8; 32],
+    pub version: u16,
+    pub landlord_signed_at: i64,
+    pub tenant_signed_at: i64,
+    /// 使用此租約建立的租約帳戶 (未使用為預設值)
+    pub rental: Pubkey,
+    pub bump: u8,
+}
+
+/// 代管授權 (每個房源與代管人一份)
+///
+/// 由房東授予，權限以 `Delegation::UPDATE_LISTING` 等位元常數組合；過期或房東變更後即失效。
+#[account]
+#[derive(InitSpace)]
+pub struct Delegation {
+    pub listing: Pubkey,
+    /// 授權的房東 (`listing.authority`)
+    pub authority: Pubkey,
+    pub manager: Pubkey,
+    pub permissions: u8,
+    /// 管理費 (租金的基點)
+    pub fee_bps: u16,
+    pub expires_at: i64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// 房源預留 (每個房源一份)，帳戶餘額託管預留費用直到轉為租約或到期退還
+#[account]
+#[derive(InitSpace)]
+pub struct Reservation {
+    pub listing: Pubkey,
+    pub tenant: Pubkey,
+    /// 託管的預留費用 (lamports)
+    pub fee: u64,
+    pub expires_at: i64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// 短租可預訂日曆 (每個房源一份)
+///
+/// 以位元圖記錄自 `start_day` 起 `CALENDAR_DAYS` 天中已預訂的夜晚，預訂時自動推進起點。
+#[account]
+#[derive(InitSpace)]
+pub struct StayCalendar {
+    pub listing: Pubkey,
+    /// 位元 0 對應的 UTC 日
+    pub start_day: u32,
+    pub booked: [u8; CALENDAR_BYTES],
+    pub bump: u8,
+}
+
+/// 短租預訂 (每個房源與入住日一份)，帳戶餘額託管住宿費用直到入住
+#[account]
+#[derive(InitSpace)]
+pub struct StayBooking {
+    pub listing: Pubkey,
+    pub guest: Pubkey,
+    /// 入住日 (UTC 日)
+    pub check_in_day: u32,
+    pub nights: u16,
+    /// 託管的住宿費用 (lamports)
+    pub amount: u64,
+    pub booked_at: i64,
+    pub bump: u8,
+}
+
+/// 帳單 (每個租約與帳單編號一份)，由房東方開立、租客以租約幣別繳納
+///
+/// `rental` 固定位於帳戶資料第 8 byte，可用 memcmp 查詢租約的所有帳單。
+#[account]
+#[derive(InitSpace)]
+pub struct Bill {
+    pub rental: Pubkey,
+    pub bill_id: u32,
+    pub category: BillCategory,
+    /// 帳單金額 (租約幣別)
+    pub amount: u64,
+    pub due_date: i64,
+    /// 計費憑證雜湊 (如電表、水表讀數照片)
+    pub evidence_hash: [u8; 32],
+    pub status: BillStatus,
+    pub issued_at: i64,
+    /// 繳納或免除的時間
+    pub settled_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum BillCategory {
+    Electricity,
+    Water,
+    Gas,
+    /// 大樓管理費
+    ManagementFee,
+    Other,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum BillStatus {
+    Open,
+    Paid,
+    Disputed,
+    Waived,
+}
+
+/// 報修單 (每個租約與報修編號一份)，由租客開立、房東方更新進度
+///
+/// `rental` 固定位於帳戶資料第 8 byte，可用 memcmp 查詢租約的所有報修單。
+#[account]
+#[derive(InitSpace)]
+pub struct MaintenanceTicket {
+    pub rental: Pubkey,
+    pub ticket_id: u32,
+    pub category: MaintenanceCategory,
+    pub priority: MaintenancePriority,
+    /// 報修說明與照片的雜湊
+    pub description_hash: [u8; 32],
+    pub status: MaintenanceStatus,
+    pub opened_at: i64,
+    pub acknowledged_at: i64,
+    /// 預定維修時間
+    pub scheduled_for: i64,
+    /// 房東方回報已修繕的時間 (租客不接受時重設為 0)
+    pub resolved_at: i64,
+    pub confirmed_at: i64,
+    /// 是否已申請逾期租金減免
+    pub abatement_claimed: bool,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum MaintenanceCategory {
+    Plumbing,
+    Electrical,
+    Appliance,
+    Structural,
+    Pest,
+    Other,
+}
+
+/// 報修優先度，決定修繕期限 (`MaintenancePriority::resolution_sla`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum MaintenancePriority {
+    Low,
+    Normal,
+    High,
+    Emergency,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum MaintenanceStatus {
+    Open,
+    Acknowledged,
+    Scheduled,
+    Resolved,
+    /// 租客已確認修繕完成
+    Confirmed,
+}
+
+/// 合租租客的固定租金分攤比例
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct RentShare {
+    pub tenant: Pubkey,
+    /// 分攤比例 (基點，總和須為 10,000)
+    pub share_bps: u16,
+}
+
+/// 房源共有人持分
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct OwnerShare {
+    pub owner: Pubkey,
+    /// 持分 (基點，總和須為 10,000)
+    pub share_bps: u16,
+}
+
+/// 短租條件
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct ShortStayTerms {
+    /// 每晚價格 (lamports)
+    pub nightly_price: u64,
+    pub min_nights: u16,
+    pub max_nights: u16,
+}
+
+/// 起租前取消的退款規則
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default, InitSpace)]
+pub struct CancellationPolicy {
+    /// 租客於起租日前至少此秒數取消可全額退款
+    pub full_refund_notice: i64,
+    /// 未達通知期限時退還的比例 (基點)
+    pub late_refund_bps: u16,
+}
+
+/// 租金調漲方式
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum EscalationKind {
+    /// 每期調漲固定比例 (基點)
+    Percent { bps: u16 },
+    /// 每期調漲固定金額 (租約幣別)
+    Amount { amount: u64 },
+    /// 依 CPI 指數漲幅調整，每期漲幅上限 (基點)
+    CpiIndexed { cap_bps: u16 },
+}
+
+/// 租金調漲條款
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct RentEscalation {
+    pub kind: EscalationKind,
+    /// 每隔幾個月調漲一次 (自起租日起算)
+    pub interval_months: u8,
+    /// CPI 指數預言機 (僅 CPI 連動使用)
+    pub cpi_feed: Pubkey,
+    /// 上次調整時的 CPI 指數
+    pub cpi_index: i64,
+    /// 已生效的調漲次數
+    pub tier: u16,
+}
+
+/// 分租房間
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct RoomUnit {
+    pub room_id: u8,
+    /// 坪數
+    pub size: u32,
+    /// 月租與押金 (房源幣別)
+    pub price: u64,
+    pub deposit: u64,
+    /// 是否為獨立衛浴 (否則為共用衛浴)
+    pub private_bathroom: bool,
+    /// 承租中的租約 (預設值表示可租)
+    pub rental: Pubkey,
+}
+
+/// 房東離線簽署的租約要約
+///
+/// 房東以 Ed25519 簽署 `offer::OFFER_MESSAGE_PREFIX` 加上此結構的 Borsh 序列化，
+/// 租客於同一筆交易中附上 Ed25519 預編譯指令即可直接租用。
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct SignedOffer {
+    pub listing: Pubkey,
+    /// 指定租客，未填則任何租客皆可接受
+    pub tenant: Option<Pubkey>,
+    /// 月租 (房源幣別)
+    pub price: u64,
+    pub contract_length: u8,
+    pub expires_at: i64,
+    /// 須大於房源已使用的 nonce
+    pub nonce: u64,
+    /// 要約附帶的租約文件雜湊與版本，租客簽署交易即視為同意
+    pub lease_document_hash: [u8; 32],
+    pub lease_version: u16,
 }
 
-#[derive(Accounts)]
-pub struct RenewRental<'info> {
-    #[account(
-        mut,
-        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
-        bump = rental.bump
-    )]
-    pub rental: Account<'info, Rental>,
-    
-    #[account(
-        mut,
-        seeds = [b"platform"],
-        bump = platform.bump
-    )]
-    pub platform: Account<'info, Platform>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
+/// 租屋申請 (每位租客對每個房源一份)
+///
+/// 申請保證金以 lamports 託管於此帳戶，接受時轉入租約託管，拒絕或撤回時退還租客。
+#[account]
+#[derive(InitSpace)]
+pub struct RentalApplication {
+    pub listing: Pubkey,
+    pub tenant: Pubkey,
+    pub tenant_profile: Pubkey,
+    pub move_in_date: i64,
+    pub contract_length: u8,
+    /// 出價 (與房源相同幣別)，未填則依房源定價
+    pub offer_price: Option<u64>,
+    pub hold_amount: u64,
+    pub submitted_at: i64,
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct TransferRental<'info> {
-    #[account(
-        mut,
-        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
-        bump = rental.bump
-    )]
-    pub rental: Account<'info, Rental>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
+/// 平台 KYC 要求
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, Debug, InitSpace)]
+pub struct KycPolicy {
+    pub require_landlord: bool,
+    pub require_tenant: bool,
+    pub min_level: u8,
 }
 
-#[derive(Accounts)]
-pub struct ExtendRental<'info> {
-    #[account(
-        mut,
-        seeds = [b"rental", rental.listing.as_ref(), rental.tenant.as_ref()],
-        bump = rental.bump
-    )]
-    pub rental: Account<'info, Rental>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
+/// KYC 認證 (由平台核可的認證機構簽發)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct KycAttestation {
+    /// 簽發的認證機構
+    pub issuer: Pubkey,
+    pub level: u8,
+    pub issued_at: i64,
+    pub expires_at: i64,
+    /// 鏈下身分證明文件雜湊
+    pub evidence_hash: [u8; 32],
 }
 
+/// 平台核可的認證機構
 #[account]
 #[derive(InitSpace)]
-pub struct Platform {
-    pub authority: Pubkey,
+pub struct Attester {
+    pub attester: Pubkey,
+    pub added_by: Pubkey,
+    pub added_at: i64,
     pub bump: u8,
-    pub total_listings: u64,
-    pub total_rentals: u64,
-    pub total_volume: u64,
 }
 
+/// 房東身分檔案 (每個錢包一個)
 #[account]
 #[derive(InitSpace)]
-pub struct Listing {
-    pub authority: Pubkey,
+pub struct LandlordProfile {
+    pub wallet: Pubkey,
+    pub attestation: Option<KycAttestation>,
+    pub created_at: i64,
+    pub updated_at: i64,
     pub bump: u8,
-    pub id: u64,
-    #[max_len(100)]
-    pub title: String,
-    #[max_len(500)]
-    pub description: String,
-    #[max_len(200)]
-    pub location: String,
-    pub price: u64,
-    pub deposit: u64,
-    pub size: u32,
-    pub rooms: u8,
-    pub bathrooms: u8,
-    pub floor: u8,
-    pub total_floors: u8,
-    pub contract_length: u8,
-    pub move_in_date: i64,
-    #[max_len(20)]
-    pub amenities: Vec<String>,
-    pub is_available: bool,
-    pub is_verified: bool,
+}
+
+/// 租客身分檔案 (每個錢包一個)
+#[account]
+#[derive(InitSpace)]
+pub struct TenantProfile {
+    pub wallet: Pubkey,
+    /// 信用分數 (0 ~ 1000)，固定位於帳戶資料第 40 byte
+    pub reputation_score: u16,
+    pub attestation: Option<KycAttestation>,
+    pub on_time_payments: u32,
+    pub late_payments: u32,
+    /// 由租客提前終止的租約數
+    pub tenant_terminations: u32,
+    /// 由房東提前終止的租約數
+    pub landlord_terminations: u32,
+    pub deposits_settled: u32,
+    /// 押金扣除比例累計 (基點)
+    pub deposit_deduction_bps: u64,
     pub created_at: i64,
     pub updated_at: i64,
+    pub bump: u8,
 }
 
+/// 房源文件 (每份文件一個 PDA，以內容雜湊為種子)
+///
+/// `listing` 固定位於帳戶資料第 8 byte，可用 memcmp 查詢房源的所有文件。
 #[account]
 #[derive(InitSpace)]
-pub struct Rental {
-    pub rental_id: u64,
+pub struct ListingDocument {
     pub listing: Pubkey,
-    pub landlord: Pubkey,
-    pub tenant: Pubkey,
-    pub price: u64,
-    pub deposit: u64,
-    pub contract_length: u8,
-    pub start_date: i64,
-    pub end_date: i64,
-    pub next_payment_date: i64,
-    pub status: RentalStatus,
-    pub payment_method: PaymentMethod,
+    pub kind: DocumentKind,
+    pub content_hash: [u8; 32],
+    pub hash_algorithm: HashAlgorithm,
+    #[max_len(200)]
+    pub uri: String,
+    pub uploader: Pubkey,
+    pub uploaded_at: i64,
+    /// 權狀是否已由驗證者查核
+    pub is_verified: bool,
+    pub verified_by: Pubkey,
+    pub verified_at: i64,
     pub bump: u8,
-    pub created_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum DocumentKind {
+    Photo,
+    FloorPlan,
+    /// 建物所有權狀
+    OwnershipCertificate,
+    Other,
+}
+
+/// 設施配備位元組合，各位元定義見 `amenities` 模組
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, Debug, InitSpace)]
+pub struct Amenities {
+    pub bits: u32,
+}
+
+/// 鏈下中繼資料文件的 URI 與內容雜湊
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct MetadataAnchor {
+    #[max_len(200)]
+    pub uri: String,
+    pub content_hash: [u8; 32],
+    pub hash_algorithm: HashAlgorithm,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum HashAlgorithm {
+    Sha256,
+    Keccak256,
+}
+
+/// 結構化地址 (內政部行政區域代碼)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct Address {
+    pub county_code: u32,
+    pub district_code: u32,
+    #[max_len(15)]
+    pub district_name: String,
+    #[max_len(64)]
+    pub street: String,
+    #[max_len(24)]
+    pub number: String,
+    pub floor: u8,
+    #[max_len(16)]
+    pub unit: Option<String>,
+    #[max_len(6)]
+    pub postal_code: String,
+}
+
+/// 經緯度座標 (微度，即 10^-6 度)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct Coordinates {
+    pub latitude: i32,
+    pub longitude: i32,
+}
+
+/// 房源 geohash 索引
+///
+/// `geohash` 固定位於帳戶資料第 8 byte (discriminator 之後)，可用 memcmp 以任意長度前綴
+/// 篩選同一格網內的房源。
+#[account]
+#[derive(InitSpace)]
+pub struct GeoIndex {
+    pub geohash: [u8; GEOHASH_PRECISION],
+    pub listing: Pubkey,
+    pub coordinates: Coordinates,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum IndexKind {
+    District,
+    PriceBucket,
+}
+
+/// 可租房源索引頁
+///
+/// 依行政區 (`key` 為鄉鎮市區代碼) 或新台幣月租價格分桶 (`key` 為分桶編號) 收錄目前可租的房源，
+/// 於上架、更新、租用與終止租約時維護。單頁已滿時請建立並使用下一頁。
+#[account]
+#[derive(InitSpace)]
+pub struct ListingIndex {
+    pub kind: IndexKind,
+    pub key: u32,
+    pub page: u16,
+    #[max_len(64)]
+    pub listings: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+/// 地區租金區間 (每坪月租，以分為單位)，`region_code` 可為縣市或鄉鎮市區代碼
+#[account]
+#[derive(InitSpace)]
+pub struct PriceBand {
+    pub region_code: u32,
+    pub currency: FiatCurrency,
+    pub min_price_per_ping: u64,
+    pub max_price_per_ping: u64,
+    pub updated_by: Pubkey,
     pub updated_at: i64,
+    pub bump: u8,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
@@ -897,6 +5960,8 @@ pub enum RentalStatus {
     Active,
     Terminated,
     Expired,
+    /// 已簽約、尚未到起租日
+    Pending,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
@@ -905,6 +5970,33 @@ pub enum PaymentMethod {
     Usdt,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum FiatCurrency {
+    Usd,
+    Twd,
+}
+
+/// 法幣計價租金 (以分為單位)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct FiatPrice {
+    pub amount: u64,
+    pub currency: FiatCurrency,
+}
+
+/// Pyth 報價快照 (實際價格 = price * 10^expo)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct PriceQuote {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+/// 預設報價最長有效秒數
+pub const DEFAULT_MAX_PRICE_AGE: u64 = 60;
+/// 預設信賴區間上限 (基點)
+pub const DEFAULT_MAX_PRICE_CONF_BPS: u16 = 200;
+
 #[error_code]
 pub enum RentalError {
     #[msg("房源不可用")]
@@ -923,9 +6015,164 @@ pub enum RentalError {
     InvalidTransfer,
     #[msg("無效延期")]
     InvalidExtension,
+    #[msg("房源索引已滿")]
+    ListingIndexFull,
+    #[msg("房源索引與房源不符")]
+    ListingIndexMismatch,
+    #[msg("房源不在索引中")]
+    ListingNotIndexed,
+    #[msg("無效房源中繼資料")]
+    InvalidMetadata,
+    #[msg("無效設施配備")]
+    InvalidAmenities,
+    #[msg("必填欄位不可為空")]
+    EmptyField,
+    #[msg("標題過長")]
+    TitleTooLong,
+    #[msg("描述過長")]
+    DescriptionTooLong,
+    #[msg("URI 過長")]
+    MetadataUriTooLong,
+    #[msg("地址欄位過長")]
+    AddressFieldTooLong,
+    #[msg("自訂標籤過多")]
+    TooManyAmenities,
+    #[msg("自訂標籤過長")]
+    TagTooLong,
+    #[msg("原因說明過長")]
+    ReasonTooLong,
+    #[msg("無效房源文件")]
+    InvalidDocument,
+    #[msg("需要 KYC 認證")]
+    KycRequired,
+    #[msg("KYC 認證已過期")]
+    KycExpired,
+    #[msg("KYC 認證等級不足")]
+    KycLevelTooLow,
+    #[msg("無效 KYC 認證")]
+    InvalidKycAttestation,
+    #[msg("租客信用分數不足")]
+    TenantScoreTooLow,
+    #[msg("押金已結算")]
+    DepositAlreadySettled,
+    #[msg("租約尚未到期")]
+    RentalNotEnded,
+    #[msg("押金扣除金額無效")]
+    InvalidDeduction,
+    #[msg("無效租屋申請")]
+    InvalidApplication,
+    #[msg("此房源須經申請審核")]
+    ApplicationRequired,
+    #[msg("無效租約文件")]
+    InvalidLease,
+    #[msg("租約尚未完成雙方簽署")]
+    LeaseNotSigned,
+    #[msg("租約文件不符")]
+    LeaseMismatch,
+    #[msg("租約已簽署")]
+    LeaseAlreadySigned,
+    #[msg("租約已被使用")]
+    LeaseAlreadyUsed,
+    #[msg("無效要約")]
+    InvalidOffer,
+    #[msg("要約已過期")]
+    OfferExpired,
+    #[msg("要約 nonce 已使用")]
+    OfferNonceUsed,
+    #[msg("要約簽章無效")]
+    InvalidOfferSignature,
+    #[msg("無效租金分攤設定")]
+    InvalidRentShares,
+    #[msg("非此租約的租客")]
+    NotCoTenant,
+    #[msg("本期已支付分攤租金")]
+    ContributionAlreadyPaid,
+    #[msg("本期分攤租金收取中")]
+    RentCollectionInProgress,
+    #[msg("無效租金託管帳戶")]
+    InvalidEscrowAccount,
+    #[msg("無效共有人持分設定")]
+    InvalidOwnerShares,
+    #[msg("須經過半數持分的共有人同意")]
+    OwnerConsentRequired,
+    #[msg("無效共有人收款帳戶")]
+    InvalidOwnerAccount,
+    #[msg("無效代管授權")]
+    InvalidDelegation,
+    #[msg("代管授權已過期")]
+    DelegationExpired,
+    #[msg("代管授權不包含此權限")]
+    PermissionDenied,
+    #[msg("房源不屬於此建物")]
+    PropertyMismatch,
+    #[msg("單位樓層與建物總樓層不符")]
+    InvalidUnitFloor,
+    #[msg("無效分租房間設定")]
+    InvalidRoomUnits,
+    #[msg("房間已出租")]
+    RoomNotAvailable,
+    #[msg("房源未開啟短租")]
+    ShortStayNotEnabled,
+    #[msg("無效短租條件")]
+    InvalidStayTerms,
+    #[msg("無效住宿日期")]
+    InvalidStayDates,
+    #[msg("住宿日期已被預訂")]
+    StayUnavailable,
+    #[msg("無效短租預訂")]
+    InvalidStayBooking,
+    #[msg("尚未到入住日")]
+    StayNotStarted,
+    #[msg("已過入住日")]
+    StayAlreadyStarted,
+    #[msg("無效房源預留")]
+    InvalidReservation,
+    #[msg("房源已由其他租客預留")]
+    ListingReserved,
+    #[msg("預留已過期")]
+    ReservationExpired,
+    #[msg("預留尚未到期")]
+    ReservationActive,
+    #[msg("無效取消退款規則")]
+    InvalidCancellationPolicy,
+    #[msg("尚未到起租日")]
+    RentalNotStarted,
+    #[msg("已到起租日，須先起租")]
+    RentalAlreadyStarted,
+    #[msg("無效租金調漲條款")]
+    InvalidEscalation,
+    #[msg("無效帳單")]
+    InvalidBill,
+    #[msg("帳單狀態不允許此操作")]
+    InvalidBillStatus,
+    #[msg("無效報修單")]
+    InvalidMaintenanceTicket,
+    #[msg("報修單狀態不允許此操作")]
+    InvalidTicketStatus,
+    #[msg("無效租金減免")]
+    InvalidAbatement,
+    #[msg("尚未超過修繕期限")]
+    SlaNotExceeded,
+    #[msg("此報修單已申請租金減免")]
+    AbatementAlreadyClaimed,
+    #[msg("押金尚未繳足，須由主要租客補足")]
+    DepositOutstanding,
 }
 
 // 事件定義
+#[event]
+pub struct RentPaid {
+    pub rental: Pubkey,
+    pub tenant: Pubkey,
+    pub amount: u64,
+    pub payment_method: PaymentMethod,
+    pub fiat_price: Option<FiatPrice>,
+    pub sol_usd_rate: Option<PriceQuote>,
+    pub usd_twd_rate: Option<PriceQuote>,
+    pub next_payment_date: i64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct RentalAdjusted {
     pub rental: Pubkey,
@@ -965,4 +6212,167 @@ pub struct RentalExtended {
     pub reason: String,
     pub extended_by: Pubkey,
     pub timestamp: i64,
-} 
\ No newline at end of file
+} 
+
+#[event]
+pub struct DepositSettled {
+    pub rental: Pubkey,
+    pub tenant: Pubkey,
+    pub deposit: u64,
+    pub deduction: u64,
+    pub refund_amount: u64,
+    pub reputation_score: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ApplicationAccepted {
+    pub application: Pubkey,
+    pub rental: Pubkey,
+    pub listing: Pubkey,
+    pub tenant: Pubkey,
+    pub price: u64,
+    pub escrowed: u64,
+    pub refunded_applications: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LeaseSigned {
+    pub lease: Pubkey,
+    pub listing: Pubkey,
+    pub landlord: Pubkey,
+    pub tenant: Pubkey,
+    pub document_hash: [u8; 32],
+    pub version: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingOwnersUpdated {
+    pub listing: Pubkey,
+    pub owners: Vec<OwnerShare>,
+    pub approved_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegationGranted {
+    pub delegation: Pubkey,
+    pub listing: Pubkey,
+    pub manager: Pubkey,
+    pub permissions: u8,
+    pub fee_bps: u16,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegationRevoked {
+    pub delegation: Pubkey,
+    pub listing: Pubkey,
+    pub manager: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StayBooked {
+    pub listing: Pubkey,
+    pub guest: Pubkey,
+    pub check_in_day: u32,
+    pub nights: u16,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReservationPlaced {
+    pub listing: Pubkey,
+    pub tenant: Pubkey,
+    pub fee: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct ReservationReleased {
+    pub listing: Pubkey,
+    pub tenant: Pubkey,
+    pub fee: u64,
+}
+
+#[event]
+pub struct RentalActivated {
+    pub rental: Pubkey,
+    pub tenant: Pubkey,
+    pub start_date: i64,
+    pub prepaid_rent: u64,
+}
+
+#[event]
+pub struct RentEscalated {
+    pub rental: Pubkey,
+    pub tier: u16,
+    pub old_price: u64,
+    pub new_price: u64,
+    pub fiat_price: Option<FiatPrice>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BillIssued {
+    pub bill: Pubkey,
+    pub rental: Pubkey,
+    pub category: BillCategory,
+    pub amount: u64,
+    pub due_date: i64,
+    pub evidence_hash: [u8; 32],
+}
+
+#[event]
+pub struct BillPaid {
+    pub bill: Pubkey,
+    pub rental: Pubkey,
+    pub tenant: Pubkey,
+    pub amount: u64,
+    pub payment_method: PaymentMethod,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BillStatusChanged {
+    pub bill: Pubkey,
+    pub rental: Pubkey,
+    pub status: BillStatus,
+    pub changed_by: Pubkey,
+}
+
+#[event]
+pub struct MaintenanceTicketOpened {
+    pub ticket: Pubkey,
+    pub rental: Pubkey,
+    pub category: MaintenanceCategory,
+    pub priority: MaintenancePriority,
+    pub sla_deadline: i64,
+}
+
+#[event]
+pub struct MaintenanceTicketUpdated {
+    pub ticket: Pubkey,
+    pub rental: Pubkey,
+    pub status: MaintenanceStatus,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaintenanceAbatementGranted {
+    pub ticket: Pubkey,
+    pub rental: Pubkey,
+    pub abatement_bps: u16,
+    pub pending_abatement_bps: u16,
+}
+
+#[event]
+pub struct RentAbated {
+    pub rental: Pubkey,
+    pub amount: u64,
+}
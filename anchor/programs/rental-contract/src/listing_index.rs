@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::{IndexKind, ListingIndex, RentalError};
+
+/// 每頁索引可容納的房源數，需與 `ListingIndex.listings` 的 `max_len` 一致
+pub const MAX_LISTING_INDEX_ENTRIES: usize = 64;
+/// 價格分桶大小：每 5,000 新台幣 (分) 一桶
+pub const PRICE_BUCKET_SIZE: u64 = 500_000;
+
+impl ListingIndex {
+    /// 依新台幣月租 (分) 計算價格分桶
+    pub fn price_bucket(monthly_rent_twd: u64) -> u32 {
+        u32::try_from(monthly_rent_twd / PRICE_BUCKET_SIZE).unwrap_or(u32::MAX)
+    }
+
+    /// 將房源加入索引
+    pub fn insert(&mut self, kind: IndexKind, key: u32, listing: Pubkey) -> Result<()> {
+        require!(self.kind == kind && self.key == key, RentalError::ListingIndexMismatch);
+        if self.listings.contains(&listing) {
+            return Ok(());
+        }
+        require!(
+            self.listings.len() < MAX_LISTING_INDEX_ENTRIES,
+            RentalError::ListingIndexFull
+        );
+
+        self.listings.push(listing);
+        Ok(())
+    }
+
+    /// 將房源自索引移除
+    pub fn remove(&mut self, kind: IndexKind, key: u32, listing: &Pubkey) -> Result<()> {
+        require!(self.kind == kind && self.key == key, RentalError::ListingIndexMismatch);
+        let position = self
+            .listings
+            .iter()
+            .position(|indexed| indexed == listing)
+            .ok_or(RentalError::ListingNotIndexed)?;
+
+        self.listings.swap_remove(position);
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use anchor_lang::prelude::*;
+
+use crate::{MaintenancePriority, MaintenanceStatus, MaintenanceTicket, Rental, RentalError};
+
+/// 租金減免比例上限 (基點)
+pub const MAX_ABATEMENT_BPS: u16 = 10_000;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+impl MaintenancePriority {
+    /// 自報修起算的修繕期限 (秒)
+    pub fn resolution_sla(&self) -> i64 {
+        match self {
+            MaintenancePriority::Low => 30 * SECONDS_PER_DAY,
+            MaintenancePriority::Normal => 14 * SECONDS_PER_DAY,
+            MaintenancePriority::High => 3 * SECONDS_PER_DAY,
+            MaintenancePriority::Emergency => SECONDS_PER_DAY,
+        }
+    }
+}
+
+impl MaintenanceTicket {
+    /// 房東方更新處理進度：確認受理、安排維修 (可重新安排) 或回報已修繕
+    pub fn advance(&mut self, status: MaintenanceStatus, scheduled_for: i64, now: i64) -> Result<()> {
+        use MaintenanceStatus::*;
+        match (self.status, status) {
+            (Open, Acknowledged) => {}
+            (Open | Acknowledged | Scheduled, Scheduled) => {
+                require!(scheduled_for >= now, RentalError::InvalidMaintenanceTicket);
+                self.scheduled_for = scheduled_for;
+            }
+            (Open | Acknowledged | Scheduled, Resolved) => self.resolved_at = now,
+            _ => return err!(RentalError::InvalidTicketStatus),
+        }
+        if self.acknowledged_at == 0 {
+            self.acknowledged_at = now;
+        }
+        self.status = status;
+        Ok(())
+    }
+
+    /// 租客確認修繕結果；不接受時退回受理狀態並繼續計算修繕期限
+    pub fn confirm(&mut self, accepted: bool, now: i64) -> Result<()> {
+        require!(self.status == MaintenanceStatus::Resolved, RentalError::InvalidTicketStatus);
+        if accepted {
+            self.status = MaintenanceStatus::Confirmed;
+            self.confirmed_at = now;
+        } else {
+            self.status = MaintenanceStatus::Acknowledged;
+            self.resolved_at = 0;
+        }
+        Ok(())
+    }
+
+    /// 是否超過修繕期限：已回報修繕者依回報時間判斷，否則依當下時間
+    pub fn exceeded_sla(&self, now: i64) -> bool {
+        let deadline = self.opened_at + self.priority.resolution_sla();
+        if self.resolved_at > 0 {
+            self.resolved_at > deadline
+        } else {
+            now > deadline
+        }
+    }
+}
+
+impl Rental {
+    /// 逾期未修繕的報修單累計租金減免，每張報修單僅可申請一次
+    pub fn grant_abatement(&mut self, ticket: &mut MaintenanceTicket, now: i64) -> Result<u16> {
+        require!(self.maintenance_abatement_bps > 0, RentalError::InvalidAbatement);
+        require!(!ticket.abatement_claimed, RentalError::AbatementAlreadyClaimed);
+        require!(ticket.exceeded_sla(now), RentalError::SlaNotExceeded);
+        ticket.abatement_claimed = true;
+        self.pending_abatement_bps = self
+            .pending_abatement_bps
+            .saturating_add(self.maintenance_abatement_bps)
+            .min(MAX_ABATEMENT_BPS);
+        Ok(self.pending_abatement_bps)
+    }
+
+    /// 付款時扣抵累計的租金減免並歸零，回傳減免金額；合租收款期間應付金額已鎖定，留待下期扣抵
+    pub fn take_abatement(&mut self, amount_due: u64) -> u64 {
+        if self.pending_abatement_bps == 0 || self.is_collecting() {
+            return 0;
+        }
+        let abated = (amount_due as u128 * self.pending_abatement_bps as u128 / MAX_ABATEMENT_BPS as u128) as u64;
+        self.pending_abatement_bps = 0;
+        abated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MaintenanceCategory, RentShare};
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn open_ticket(priority: MaintenancePriority) -> MaintenanceTicket {
+        MaintenanceTicket {
+            rental: Pubkey::new_unique(),
+            ticket_id: 0,
+            category: MaintenanceCategory::Plumbing,
+            priority,
+            description_hash: [3; 32],
+            status: MaintenanceStatus::Open,
+            opened_at: NOW,
+            acknowledged_at: 0,
+            scheduled_for: 0,
+            resolved_at: 0,
+            confirmed_at: 0,
+            abatement_claimed: false,
+            bump: 255,
+        }
+    }
+
+    fn abating_rental(abatement_bps: u16) -> Rental {
+        Rental {
+            price: 10_000,
+            maintenance_abatement_bps: abatement_bps,
+            ..Rental::test_fixture()
+        }
+    }
+
+    #[test]
+    fn moves_ticket_through_repair_flow() {
+        let invalid = Err(RentalError::InvalidTicketStatus.into());
+        let mut ticket = open_ticket(MaintenancePriority::Normal);
+        assert_eq!(ticket.confirm(true, NOW), invalid);
+
+        ticket.advance(MaintenanceStatus::Acknowledged, 0, NOW + 60).unwrap();
+        assert_eq!(ticket.acknowledged_at, NOW + 60);
+        assert_eq!(ticket.advance(MaintenanceStatus::Acknowledged, 0, NOW + 60), invalid);
+        assert_eq!(
+            ticket.advance(MaintenanceStatus::Scheduled, NOW, NOW + 120),
+            Err(RentalError::InvalidMaintenanceTicket.into())
+        );
+        ticket.advance(MaintenanceStatus::Scheduled, NOW + SECONDS_PER_DAY, NOW + 120).unwrap();
+        ticket.advance(MaintenanceStatus::Resolved, 0, NOW + SECONDS_PER_DAY).unwrap();
+
+        // 租客不接受修繕結果時退回受理狀態
+        ticket.confirm(false, NOW + 2 * SECONDS_PER_DAY).unwrap();
+        assert!(ticket.status == MaintenanceStatus::Acknowledged);
+        assert_eq!(ticket.resolved_at, 0);
+
+        ticket.advance(MaintenanceStatus::Resolved, 0, NOW + 3 * SECONDS_PER_DAY).unwrap();
+        ticket.confirm(true, NOW + 4 * SECONDS_PER_DAY).unwrap();
+        assert!(ticket.status == MaintenanceStatus::Confirmed);
+        assert_eq!(ticket.advance(MaintenanceStatus::Resolved, 0, NOW + 5 * SECONDS_PER_DAY), invalid);
+    }
+
+    #[test]
+    fn grants_abatement_after_sla() {
+        let mut rental = abating_rental(2_500);
+        let mut ticket = open_ticket(MaintenancePriority::High);
+        assert_eq!(
+            rental.grant_abatement(&mut ticket, NOW + 3 * SECONDS_PER_DAY),
+            Err(RentalError::SlaNotExceeded.into())
+        );
+        assert_eq!(rental.grant_abatement(&mut ticket, NOW + 3 * SECONDS_PER_DAY + 1), Ok(2_500));
+        assert_eq!(
+            rental.grant_abatement(&mut ticket, NOW + 4 * SECONDS_PER_DAY),
+            Err(RentalError::AbatementAlreadyClaimed.into())
+        );
+
+        // 於期限內回報修繕者不得申請減免
+        let mut resolved = open_ticket(MaintenancePriority::Emergency);
+        resolved.advance(MaintenanceStatus::Resolved, 0, NOW + 60).unwrap();
+        assert!(!resolved.exceeded_sla(NOW + 10 * SECONDS_PER_DAY));
+
+        let mut late = open_ticket(MaintenancePriority::Emergency);
+        late.advance(MaintenanceStatus::Resolved, 0, NOW + 2 * SECONDS_PER_DAY).unwrap();
+        assert_eq!(rental.grant_abatement(&mut late, NOW + 2 * SECONDS_PER_DAY), Ok(5_000));
+
+        assert_eq!(
+            abating_rental(0).grant_abatement(&mut open_ticket(MaintenancePriority::High), NOW + 30 * SECONDS_PER_DAY),
+            Err(RentalError::InvalidAbatement.into())
+        );
+    }
+
+    #[test]
+    fn takes_abatement_from_next_payment() {
+        let mut rental = abating_rental(2_500);
+        rental.pending_abatement_bps = 2_500;
+
+        // 合租收款期間不扣抵
+        rental.co_tenants = vec![RentShare { tenant: rental.tenant, share_bps: 10_000 }];
+        rental.co_tenants_paid = 1;
+        assert_eq!(rental.take_abatement(10_000), 0);
+
+        rental.co_tenants_paid = 0;
+        assert_eq!(rental.take_abatement(10_000), 2_500);
+        assert_eq!(rental.pending_abatement_bps, 0);
+        assert_eq!(rental.take_abatement(10_000), 0);
+    }
+}
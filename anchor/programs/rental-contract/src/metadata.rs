@@ -0,0 +1,254 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{hash, keccak};
+
+use crate::{HashAlgorithm, Listing, MetadataAnchor};
+
+/// 鏈上描述長度上限 (bytes)，需與 `Listing.description` 的 `max_len` 一致
+pub const MAX_DESCRIPTION_LEN: usize = 500;
+/// 中繼資料 URI 長度上限 (bytes)，需與 `MetadataAnchor.uri` 的 `max_len` 一致
+pub const MAX_METADATA_URI_LEN: usize = 200;
+/// 目前的中繼資料文件版本
+pub const METADATA_SCHEMA_VERSION: u8 = 1;
+/// 允許的 URI 格式
+pub const METADATA_URI_SCHEMES: [&str; 3] = ["ipfs://", "ar://", "https://"];
+
+/// 檢查 URI 是否為允許的格式
+pub fn is_valid_uri(uri: &str) -> bool {
+    uri.len() <= MAX_METADATA_URI_LEN
+        && !uri.chars().any(char::is_whitespace)
+        && METADATA_URI_SCHEMES
+            .iter()
+            .any(|scheme| uri.len() > scheme.len() && uri.starts_with(scheme))
+}
+
+impl MetadataAnchor {
+    /// 驗證 URI 格式與雜湊值
+    pub fn is_valid(&self) -> bool {
+        is_valid_uri(&self.uri) && self.content_hash != [0u8; 32]
+    }
+
+    /// 以指定演算法計算文件雜湊
+    pub fn digest(algorithm: HashAlgorithm, document: &[u8]) -> [u8; 32] {
+        match algorithm {
+            HashAlgorithm::Sha256 => hash::hash(document).to_bytes(),
+            HashAlgorithm::Keccak256 => keccak::hash(document).to_bytes(),
+        }
+    }
+
+    /// 比對取得的文件原始內容是否與鏈上雜湊一致
+    pub fn matches(&self, document: &[u8]) -> bool {
+        Self::digest(self.hash_algorithm, document) == self.content_hash
+    }
+}
+
+impl Listing {
+    /// 依實際描述長度與中繼資料計算帳戶空間 (含 discriminator)
+    ///
+    /// 未使用的描述與中繼資料容量不計入，中繼資料模式下描述留空即可大幅降低租金。
+    pub fn space(description: &str, metadata: Option<&MetadataAnchor>) -> usize {
+        let unused_description = MAX_DESCRIPTION_LEN.saturating_sub(description.len());
+        let unused_metadata = match metadata {
+            Some(metadata) => MAX_METADATA_URI_LEN.saturating_sub(metadata.uri.len()),
+            None => MetadataAnchor::INIT_SPACE,
+        };
+        8 + Listing::INIT_SPACE - unused_description - unused_metadata
+    }
+}
+
+/// 房源中繼資料文件中的媒體檔案 (照片、格局圖)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "metadata", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetadataMedia {
+    pub uri: String,
+    /// MIME 類型，例如 `image/jpeg`
+    pub content_type: String,
+}
+
+/// 房源中繼資料文件 (JSON)，URI 與雜湊記錄於 `Listing.metadata`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "metadata", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListingMetadata {
+    pub version: u8,
+    pub title: String,
+    pub description: String,
+    #[cfg_attr(feature = "metadata", serde(default))]
+    pub photos: Vec<MetadataMedia>,
+    #[cfg_attr(feature = "metadata", serde(default))]
+    pub floor_plan: Option<MetadataMedia>,
+    #[cfg_attr(feature = "metadata", serde(default))]
+    pub amenities: Vec<String>,
+}
+
+/// 中繼資料文件驗證錯誤 (供鏈下索引服務使用)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MetadataError {
+    /// 文件內容與鏈上雜湊不符
+    HashMismatch,
+    /// 文件不是有效的 JSON
+    InvalidJson,
+    /// 不支援的文件版本
+    UnsupportedVersion,
+    /// 文件標題與鏈上標題不符
+    TitleMismatch,
+    /// 媒體檔案 URI 或類型無效
+    InvalidMedia,
+}
+
+impl ListingMetadata {
+    /// 驗證文件內容是否符合格式，並與鏈上房源標題一致
+    pub fn validate(&self, listing_title: &str) -> std::result::Result<(), MetadataError> {
+        if self.version != METADATA_SCHEMA_VERSION {
+            return Err(MetadataError::UnsupportedVersion);
+        }
+        if self.title != listing_title {
+            return Err(MetadataError::TitleMismatch);
+        }
+
+        let media_valid = |media: &MetadataMedia| {
+            is_valid_uri(&media.uri)
+                && (media.content_type.starts_with("image/") || media.content_type == "application/pdf")
+        };
+        if !self.photos.iter().all(media_valid) || !self.floor_plan.iter().all(media_valid) {
+            return Err(MetadataError::InvalidMedia);
+        }
+        Ok(())
+    }
+
+    /// 驗證取得的文件原始內容與鏈上雜湊一致後解析並驗證格式
+    #[cfg(feature = "metadata")]
+    pub fn from_verified_json(
+        document: &[u8],
+        anchor: &MetadataAnchor,
+        listing_title: &str,
+    ) -> std::result::Result<Self, MetadataError> {
+        if !anchor.matches(document) {
+            return Err(MetadataError::HashMismatch);
+        }
+
+        let metadata: Self =
+            serde_json::from_slice(document).map_err(|_| MetadataError::InvalidJson)?;
+        metadata.validate(listing_title)?;
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchor_for(document: &[u8], hash_algorithm: HashAlgorithm) -> MetadataAnchor {
+        MetadataAnchor {
+            uri: "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi".to_string(),
+            content_hash: MetadataAnchor::digest(hash_algorithm, document),
+            hash_algorithm,
+        }
+    }
+
+    fn sample_metadata() -> ListingMetadata {
+        ListingMetadata {
+            version: METADATA_SCHEMA_VERSION,
+            title: "台北信義區精緻套房".to_string(),
+            description: "位於信義區精華地段，近捷運站".to_string(),
+            photos: vec![MetadataMedia {
+                uri: "ar://photo-1".to_string(),
+                content_type: "image/jpeg".to_string(),
+            }],
+            floor_plan: Some(MetadataMedia {
+                uri: "https://example.com/plan.pdf".to_string(),
+                content_type: "application/pdf".to_string(),
+            }),
+            amenities: vec!["冷氣".to_string()],
+        }
+    }
+
+    #[test]
+    fn digests_known_vectors() {
+        let sha256 = MetadataAnchor::digest(HashAlgorithm::Sha256, b"abc");
+        assert_eq!(sha256[..4], [0xba, 0x78, 0x16, 0xbf]);
+
+        let keccak = MetadataAnchor::digest(HashAlgorithm::Keccak256, b"");
+        assert_eq!(keccak[..4], [0xc5, 0xd2, 0x46, 0x01]);
+    }
+
+    #[test]
+    fn matches_document_hash() {
+        let document = br#"{"version":1,"title":"test"}"#;
+        for algorithm in [HashAlgorithm::Sha256, HashAlgorithm::Keccak256] {
+            let anchor = anchor_for(document, algorithm);
+            assert!(anchor.is_valid());
+            assert!(anchor.matches(document));
+            assert!(!anchor.matches(br#"{"version":1,"title":"tampered"}"#));
+        }
+    }
+
+    #[test]
+    fn validates_uri() {
+        assert!(is_valid_uri("ipfs://bafy"));
+        assert!(is_valid_uri("https://example.com/listing.json"));
+        assert!(!is_valid_uri("http://example.com/listing.json"));
+        assert!(!is_valid_uri("ipfs://"));
+        assert!(!is_valid_uri("ar://has space"));
+        assert!(!is_valid_uri(&format!("ar://{}", "a".repeat(MAX_METADATA_URI_LEN))));
+    }
+
+    #[test]
+    fn validates_document() {
+        let metadata = sample_metadata();
+        assert_eq!(metadata.validate("台北信義區精緻套房"), Ok(()));
+        assert_eq!(metadata.validate("其他房源"), Err(MetadataError::TitleMismatch));
+
+        let mut invalid = metadata.clone();
+        invalid.version = 2;
+        assert_eq!(invalid.validate(&metadata.title), Err(MetadataError::UnsupportedVersion));
+
+        let mut invalid = metadata.clone();
+        invalid.photos[0].content_type = "text/html".to_string();
+        assert_eq!(invalid.validate(&metadata.title), Err(MetadataError::InvalidMedia));
+
+        let mut invalid = metadata.clone();
+        invalid.floor_plan = Some(MetadataMedia {
+            uri: "ftp://example.com/plan.pdf".to_string(),
+            content_type: "application/pdf".to_string(),
+        });
+        assert_eq!(invalid.validate(&metadata.title), Err(MetadataError::InvalidMedia));
+    }
+
+    #[test]
+    fn metadata_mode_reduces_space() {
+        let anchor = anchor_for(b"{}", HashAlgorithm::Sha256);
+        let inline = Listing::space(&"房".repeat(160), None);
+        let metadata = Listing::space("", Some(&anchor));
+        assert!(metadata < inline);
+        assert_eq!(
+            Listing::space(&"a".repeat(MAX_DESCRIPTION_LEN), None) + MetadataAnchor::INIT_SPACE,
+            8 + Listing::INIT_SPACE
+        );
+    }
+
+    #[cfg(feature = "metadata")]
+    #[test]
+    fn parses_verified_json() {
+        let document = serde_json::to_vec(&sample_metadata()).unwrap();
+        let anchor = anchor_for(&document, HashAlgorithm::Keccak256);
+        let title = "台北信義區精緻套房";
+
+        assert_eq!(
+            ListingMetadata::from_verified_json(&document, &anchor, title),
+            Ok(sample_metadata())
+        );
+
+        let mut tampered = document.clone();
+        tampered.push(b' ');
+        assert_eq!(
+            ListingMetadata::from_verified_json(&tampered, &anchor, title),
+            Err(MetadataError::HashMismatch)
+        );
+
+        let not_json = b"not json";
+        let anchor = anchor_for(not_json, HashAlgorithm::Sha256);
+        assert_eq!(
+            ListingMetadata::from_verified_json(not_json, &anchor, title),
+            Err(MetadataError::InvalidJson)
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+
+use crate::{Listing, Rental, RentalError, SignedOffer};
+
+/// 要約簽署訊息前綴，避免與其他用途的簽章混用
+pub const OFFER_MESSAGE_PREFIX: &[u8] = b"rental_contract:offer:v1";
+
+// Ed25519 預編譯指令資料格式：簽章數量 (u8)、填充 (u8)，接著每個簽章 7 個 u16 偏移量
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+/// 偏移量指向 Ed25519 指令本身的資料
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+impl SignedOffer {
+    /// 房東簽署的訊息：前綴 + 要約的 Borsh 序列化
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = OFFER_MESSAGE_PREFIX.to_vec();
+        message.extend(self.try_to_vec()?);
+        Ok(message)
+    }
+
+    /// 檢查要約內容：房源、指定租客、條款、有效期限與 nonce
+    pub fn check(&self, listing_key: &Pubkey, offer_nonce: u64, tenant: &Pubkey, now: i64) -> Result<()> {
+        require!(
+            self.listing == *listing_key && !matches!(self.tenant, Some(key) if key != *tenant),
+            RentalError::InvalidOffer
+        );
+        require!(
+            self.price > 0 && self.contract_length > 0 && self.lease_version > 0,
+            RentalError::InvalidOffer
+        );
+        require!(self.expires_at > now, RentalError::OfferExpired);
+        require!(self.nonce > offer_nonce, RentalError::OfferNonceUsed);
+        Ok(())
+    }
+
+    /// 檢查要約並確認交易中前一個指令為房東對此要約的 Ed25519 簽章
+    pub fn verify(
+        &self,
+        listing_key: &Pubkey,
+        listing: &Listing,
+        tenant: &Pubkey,
+        instructions: Option<&AccountInfo>,
+        now: i64,
+    ) -> Result<()> {
+        self.check(listing_key, listing.offer_nonce, tenant, now)?;
+        let instructions = instructions.ok_or(RentalError::InvalidOfferSignature)?;
+        verify_ed25519_instruction(
+            &load_preceding_instruction(instructions)?,
+            &listing.authority,
+            &self.message()?,
+        )
+    }
+}
+
+impl Rental {
+    /// 以要約附帶的租約文件作為雙方簽署紀錄 (租客以交易簽署即視為同意)
+    pub fn record_offer(&mut self, offer: &SignedOffer, now: i64) {
+        self.lease_document_hash = offer.lease_document_hash;
+        self.lease_version = offer.lease_version;
+        self.lease_signed_at = now;
+    }
+}
+
+/// 讀取交易中位於目前指令前一個的指令
+fn load_preceding_instruction(instructions: &AccountInfo) -> Result<Instruction> {
+    let index = load_current_index_checked(instructions)?;
+    require!(index > 0, RentalError::InvalidOfferSignature);
+    Ok(load_instruction_at_checked(index as usize - 1, instructions)?)
+}
+
+/// 驗證 Ed25519 預編譯指令僅含一個簽章，且簽署者與訊息皆相符
+///
+/// 簽章本身由預編譯程式驗證，失敗時整筆交易即被拒絕。
+pub fn verify_ed25519_instruction(ix: &Instruction, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    require!(
+        ix.program_id == ed25519_program::ID && ix.accounts.is_empty(),
+        RentalError::InvalidOfferSignature
+    );
+    let data = &ix.data;
+    require!(
+        data.len() >= SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN && data[0] == 1,
+        RentalError::InvalidOfferSignature
+    );
+
+    let read_u16 = |index: usize| {
+        let offset = SIGNATURE_OFFSETS_START + index * 2;
+        u16::from_le_bytes([data[offset], data[offset + 1]])
+    };
+    let (signature_ix, pubkey_offset, pubkey_ix) = (read_u16(1), read_u16(2) as usize, read_u16(3));
+    let (message_offset, message_size, message_ix) =
+        (read_u16(4) as usize, read_u16(5) as usize, read_u16(6));
+
+    // 簽章、公鑰與訊息皆須位於 Ed25519 指令本身，避免引用其他指令的資料
+    require!(
+        signature_ix == CURRENT_INSTRUCTION
+            && pubkey_ix == CURRENT_INSTRUCTION
+            && message_ix == CURRENT_INSTRUCTION,
+        RentalError::InvalidOfferSignature
+    );
+    require!(
+        data.get(pubkey_offset..pubkey_offset + PUBKEY_LEN) == Some(signer.as_ref())
+            && data.get(message_offset..message_offset + message_size) == Some(message),
+        RentalError::InvalidOfferSignature
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    /// 依 Ed25519 預編譯格式組出指令 (簽章內容不影響此處檢查)
+    fn ed25519_ix(signer: &Pubkey, message: &[u8], index: u16) -> Instruction {
+        let pubkey_offset = SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN;
+        let signature_offset = pubkey_offset + PUBKEY_LEN;
+        let message_offset = signature_offset + 64;
+        let mut data = vec![1u8, 0];
+        for value in [
+            signature_offset as u16,
+            index,
+            pubkey_offset as u16,
+            index,
+            message_offset as u16,
+            message.len() as u16,
+            index,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0u8; 64]);
+        data.extend_from_slice(message);
+        Instruction { program_id: ed25519_program::ID, accounts: vec![], data }
+    }
+
+    fn offer(listing: Pubkey) -> SignedOffer {
+        SignedOffer {
+            listing,
+            tenant: None,
+            price: 25_000,
+            contract_length: 12,
+            expires_at: NOW + 3_600,
+            nonce: 1,
+            lease_document_hash: [3u8; 32],
+            lease_version: 1,
+        }
+    }
+
+    #[test]
+    fn verifies_matching_signature_instruction() {
+        let signer = Pubkey::new_unique();
+        let message = offer(Pubkey::new_unique()).message().unwrap();
+        assert!(message.starts_with(OFFER_MESSAGE_PREFIX));
+        assert!(verify_ed25519_instruction(&ed25519_ix(&signer, &message, CURRENT_INSTRUCTION), &signer, &message).is_ok());
+    }
+
+    #[test]
+    fn checks_offer_terms() {
+        let (listing, tenant) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut offer = offer(listing);
+        assert!(offer.check(&listing, 0, &tenant, NOW).is_ok());
+        assert_eq!(offer.check(&Pubkey::new_unique(), 0, &tenant, NOW), Err(RentalError::InvalidOffer.into()));
+        assert_eq!(offer.check(&listing, 0, &tenant, NOW + 3_600), Err(RentalError::OfferExpired.into()));
+        assert_eq!(offer.check(&listing, 1, &tenant, NOW), Err(RentalError::OfferNonceUsed.into()));
+
+        offer.tenant = Some(tenant);
+        assert!(offer.check(&listing, 0, &tenant, NOW).is_ok());
+        assert_eq!(offer.check(&listing, 0, &Pubkey::new_unique(), NOW), Err(RentalError::InvalidOffer.into()));
+
+        offer.price = 0;
+        assert_eq!(offer.check(&listing, 0, &tenant, NOW), Err(RentalError::InvalidOffer.into()));
+    }
+
+    #[test]
+    fn rejects_wrong_signer_message_or_program() {
+        let signer = Pubkey::new_unique();
+        let message = offer(Pubkey::new_unique()).message().unwrap();
+        let ix = ed25519_ix(&signer, &message, CURRENT_INSTRUCTION);
+        let invalid = Err(RentalError::InvalidOfferSignature.into());
+
+        assert_eq!(verify_ed25519_instruction(&ix, &Pubkey::new_unique(), &message), invalid);
+        assert_eq!(verify_ed25519_instruction(&ix, &signer, b"other"), invalid);
+
+        let mut wrong_program = ix.clone();
+        wrong_program.program_id = Pubkey::new_unique();
+        assert_eq!(verify_ed25519_instruction(&wrong_program, &signer, &message), invalid);
+
+        // 引用其他指令資料的簽章不予接受
+        let external = ed25519_ix(&signer, &message, 0);
+        assert_eq!(verify_ed25519_instruction(&external, &signer, &message), invalid);
+
+        let mut truncated = ix;
+        truncated.data.truncate(40);
+        assert_eq!(verify_ed25519_instruction(&truncated, &signer, &message), invalid);
+    }
+}
@@ -1,45 +1,41 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::keccak;
 
+use crate::{Address, Coordinates, PriceBand};
+
+/// 台灣範圍 (微度)
+const TAIWAN_MIN_LATITUDE: i32 = 21_800_000;
+const TAIWAN_MAX_LATITUDE: i32 = 26_500_000;
+const TAIWAN_MIN_LONGITUDE: i32 = 118_000_000;
+const TAIWAN_MAX_LONGITUDE: i32 = 122_100_000;
+
 /// Switchboard 預言機驗證器
 pub struct OracleValidator;
 
 impl OracleValidator {
-    /// 驗證房源位置資訊
-    pub fn verify_location(location: &str) -> bool {
+    /// 驗證房源地址
+    pub fn verify_address(address: &Address) -> bool {
         // 這裡可以整合 Switchboard 的地理位置驗證
-        // 目前簡單檢查是否包含有效的地址格式
-        location.contains("市") && location.contains("區")
+        // 目前檢查行政區域代碼、郵遞區號與欄位格式
+        address.is_valid()
+    }
+
+    /// 驗證座標是否位於台灣 (含澎湖、金門、馬祖) 範圍內
+    pub fn verify_coordinates(coordinates: &Coordinates) -> bool {
+        // 這裡可以整合 Switchboard 的地理編碼服務比對地址與座標
+        (TAIWAN_MIN_LATITUDE..=TAIWAN_MAX_LATITUDE).contains(&coordinates.latitude)
+            && (TAIWAN_MIN_LONGITUDE..=TAIWAN_MAX_LONGITUDE).contains(&coordinates.longitude)
     }
 
     /// 驗證租金價格合理性
-    pub fn verify_price(price: u64, location: &str) -> bool {
-        // 根據地區驗證租金價格是否合理
-        // 這裡可以整合 Switchboard 的價格預言機
-        let price_in_sol = price as f64 / 1_000_000_000.0; // 轉換為 SOL
-        
-        match location {
-            loc if loc.contains("信義") || loc.contains("大安") => {
-                // 台北精華區租金範圍
-                price_in_sol >= 20.0 && price_in_sol <= 100.0
-            }
-            loc if loc.contains("台北") => {
-                // 台北一般區域租金範圍
-                price_in_sol >= 10.0 && price_in_sol <= 50.0
-            }
-            loc if loc.contains("台中") => {
-                // 台中租金範圍
-                price_in_sol >= 5.0 && price_in_sol <= 30.0
-            }
-            loc if loc.contains("高雄") => {
-                // 高雄租金範圍
-                price_in_sol >= 3.0 && price_in_sol <= 20.0
-            }
-            _ => {
-                // 其他地區
-                price_in_sol >= 1.0 && price_in_sol <= 50.0
-            }
+    pub fn verify_price(monthly_rent: u64, size: u32, price_band: &PriceBand) -> bool {
+        // 月租金已換算為區間的計價幣別 (分)，依坪數換算每坪租金後比對區間
+        if size == 0 {
+            return false;
         }
+        let price_per_ping = monthly_rent / size as u64;
+        
+        price_per_ping >= price_band.min_price_per_ping && price_per_ping <= price_band.max_price_per_ping
     }
 
     /// 驗證房源基本資訊
@@ -89,6 +85,26 @@ pub enum OracleError {
     PriceVerificationFailed,
     #[msg("房源資訊驗證失敗")]
     ListingInfoVerificationFailed,
+    #[msg("房源座標驗證失敗")]
+    CoordinatesVerificationFailed,
     #[msg("預言機回應驗證失敗")]
     OracleResponseVerificationFailed,
+    #[msg("價格預言機帳戶無效")]
+    InvalidPriceFeed,
+    #[msg("價格預言機尚未設定")]
+    PriceFeedNotConfigured,
+    #[msg("價格報價已過期")]
+    StalePrice,
+    #[msg("價格信賴區間過大")]
+    PriceConfidenceTooWide,
+    #[msg("價格換算失敗")]
+    PriceConversionFailed,
+    #[msg("租金區間設定無效")]
+    InvalidPriceBand,
+    #[msg("租金區間與房源地區不符")]
+    PriceBandMismatch,
+    #[msg("法幣計價僅適用於 SOL 支付的房源")]
+    FiatPriceRequiresSol,
+    #[msg("價格預言機設定無效")]
+    InvalidPriceFeedConfig,
 } 
\ No newline at end of file
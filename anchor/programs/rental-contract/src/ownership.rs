@@ -0,0 +1,211 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer};
+
+use crate::escrow;
+use crate::{Listing, OwnerShare, RentalError};
+
+/// 每個房源最多共有人數
+pub const MAX_OWNERS: usize = 5;
+/// 持分總和 (基點)
+pub const TOTAL_OWNER_BPS: u16 = 10_000;
+
+/// 檢查共有人設定：2 ~ `MAX_OWNERS` 位、持分皆大於 0 且總和為 10,000 基點、不得重複
+pub fn validate_owners(owners: &[OwnerShare]) -> Result<()> {
+    require!(
+        (2..=MAX_OWNERS).contains(&owners.len()),
+        RentalError::InvalidOwnerShares
+    );
+    let mut total: u32 = 0;
+    for (index, share) in owners.iter().enumerate() {
+        require!(share.share_bps > 0, RentalError::InvalidOwnerShares);
+        require!(
+            owners[..index].iter().all(|other| other.owner != share.owner),
+            RentalError::InvalidOwnerShares
+        );
+        total += share.share_bps as u32;
+    }
+    require!(total == TOTAL_OWNER_BPS as u32, RentalError::InvalidOwnerShares);
+    Ok(())
+}
+
+/// 依持分拆分金額，最後一位負擔捨入差額使總和等於原金額
+pub fn split_by_shares(owners: &[OwnerShare], amount: u64) -> Vec<u64> {
+    let mut remaining = amount;
+    owners
+        .iter()
+        .enumerate()
+        .map(|(index, share)| {
+            let portion = if index + 1 == owners.len() {
+                remaining
+            } else {
+                (amount as u128 * share.share_bps as u128 / TOTAL_OWNER_BPS as u128) as u64
+            };
+            remaining -= portion;
+            portion
+        })
+        .collect()
+}
+
+impl Listing {
+    /// 是否為多人共有 (租金依持分分配)
+    pub fn is_co_owned(&self) -> bool {
+        !self.owners.is_empty()
+    }
+
+    /// 計算簽署者代表的持分；未設定共有人時由房源管理者代表全部持分
+    pub fn consent_bps(&self, signers: &[Pubkey]) -> u16 {
+        if !self.is_co_owned() {
+            return if signers.contains(&self.authority) { TOTAL_OWNER_BPS } else { 0 };
+        }
+        self.owners
+            .iter()
+            .filter(|share| signers.contains(&share.owner))
+            .map(|share| share.share_bps)
+            .sum()
+    }
+}
+
+/// 取出依共有人順序排列的收款帳戶
+fn owner_recipients<'a, 'info>(
+    owners: &[OwnerShare],
+    recipients: &'a [AccountInfo<'info>],
+) -> Result<&'a [AccountInfo<'info>]> {
+    recipients
+        .get(..owners.len())
+        .ok_or_else(|| error!(RentalError::InvalidOwnerAccount))
+}
+
+/// 自簽署者 (租客) 以 SOL 依持分支付給各共有人錢包
+pub fn pay_owners_sol<'info>(
+    from: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    owners: &[OwnerShare],
+    recipients: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let recipients = owner_recipients(owners, recipients)?;
+    for ((share, recipient), portion) in owners.iter().zip(recipients).zip(split_by_shares(owners, amount)) {
+        require_keys_eq!(recipient.key(), share.owner, RentalError::InvalidOwnerAccount);
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: from.clone(),
+                    to: recipient.clone(),
+                },
+            ),
+            portion,
+        )?;
+    }
+    Ok(())
+}
+
+/// 自程式擁有的託管帳戶 (租約) 依持分轉出 lamports 給各共有人錢包
+pub fn release_to_owners(
+    from: &AccountInfo,
+    owners: &[OwnerShare],
+    recipients: &[AccountInfo],
+    amount: u64,
+) -> Result<()> {
+    let recipients = owner_recipients(owners, recipients)?;
+    for ((share, recipient), portion) in owners.iter().zip(recipients).zip(split_by_shares(owners, amount)) {
+        require_keys_eq!(recipient.key(), share.owner, RentalError::InvalidOwnerAccount);
+        escrow::release_lamports(from, recipient, portion)?;
+    }
+    Ok(())
+}
+
+/// 自代幣帳戶依持分轉出 USDT 至各共有人持有的代幣帳戶 (託管帳戶須傳入租約 PDA 簽署種子)
+pub fn pay_owners_usdt<'info>(
+    from: &Account<'info, TokenAccount>,
+    authority: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    owners: &[OwnerShare],
+    recipients: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let recipients = owner_recipients(owners, recipients)?;
+    for ((share, recipient), portion) in owners.iter().zip(recipients).zip(split_by_shares(owners, amount)) {
+        require_keys_eq!(*recipient.owner, token::ID, RentalError::InvalidOwnerAccount);
+        let token_account = TokenAccount::try_deserialize(&mut &recipient.try_borrow_data()?[..])?;
+        require!(
+            token_account.owner == share.owner && token_account.mint == from.mint,
+            RentalError::InvalidOwnerAccount
+        );
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                Transfer {
+                    from: from.to_account_info(),
+                    to: recipient.clone(),
+                    authority: authority.clone(),
+                },
+                signer_seeds,
+            ),
+            portion,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share(owner: Pubkey, share_bps: u16) -> OwnerShare {
+        OwnerShare { owner, share_bps }
+    }
+
+    #[test]
+    fn validates_owner_sets() {
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(validate_owners(&[share(first, 5_000), share(second, 5_000)]).is_ok());
+
+        let invalid = Err(RentalError::InvalidOwnerShares.into());
+        assert_eq!(validate_owners(&[share(first, 10_000)]), invalid);
+        assert_eq!(validate_owners(&[share(first, 5_000), share(second, 4_999)]), invalid);
+        assert_eq!(validate_owners(&[share(first, 10_000), share(second, 0)]), invalid);
+        assert_eq!(validate_owners(&[share(first, 5_000), share(first, 5_000)]), invalid);
+    }
+
+    #[test]
+    fn splits_amount_by_shares() {
+        let owners = [
+            share(Pubkey::new_unique(), 5_000),
+            share(Pubkey::new_unique(), 3_333),
+            share(Pubkey::new_unique(), 1_667),
+        ];
+        assert_eq!(split_by_shares(&owners, 1_000), vec![500, 333, 167]);
+        assert_eq!(split_by_shares(&owners, 1), vec![0, 0, 1]);
+        assert_eq!(split_by_shares(&owners, 0), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn releases_escrow_to_owners_in_order() {
+        let program = crate::ID;
+        let system = anchor_lang::system_program::ID;
+        let (escrow_key, first, second) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let owners = [share(first, 7_000), share(second, 3_000)];
+        let (mut escrow_lamports, mut first_lamports, mut second_lamports) = (1_000u64, 0u64, 0u64);
+        let (mut escrow_data, mut first_data, mut second_data) = (vec![0u8; 8], vec![], vec![]);
+        let escrow = AccountInfo::new(&escrow_key, false, true, &mut escrow_lamports, &mut escrow_data, &program, false, 0);
+        let recipients = [
+            AccountInfo::new(&first, false, true, &mut first_lamports, &mut first_data, &system, false, 0),
+            AccountInfo::new(&second, false, true, &mut second_lamports, &mut second_data, &system, false, 0),
+        ];
+
+        assert_eq!(
+            release_to_owners(&escrow, &owners, &recipients[..1], 100),
+            Err(RentalError::InvalidOwnerAccount.into())
+        );
+        assert_eq!(
+            release_to_owners(&escrow, &owners, &[recipients[1].clone(), recipients[0].clone()], 100),
+            Err(RentalError::InvalidOwnerAccount.into())
+        );
+
+        release_to_owners(&escrow, &owners, &recipients, 1_000).unwrap();
+        assert_eq!((recipients[0].lamports(), recipients[1].lamports()), (700, 300));
+        assert_eq!(escrow.lamports(), 0);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{CancellationPolicy, PaymentMethod, Rental, RentalStatus};
+
+/// 全額退款 (基點)
+pub const FULL_REFUND_BPS: u16 = 10_000;
+
+/// 起租日：約定入住日尚未到達時以入住日為準，否則為當下
+pub fn start_date(move_in_date: i64, now: i64) -> i64 {
+    move_in_date.max(now)
+}
+
+impl CancellationPolicy {
+    /// 檢查退款比例不超過 10,000 基點且通知期限不為負
+    pub fn is_valid(&self) -> bool {
+        self.full_refund_notice >= 0 && self.late_refund_bps <= FULL_REFUND_BPS
+    }
+
+    /// 租客於起租前取消可退還的比例 (基點)：於通知期限前取消全額退款，否則依 `late_refund_bps`
+    pub fn refund_bps(&self, start_date: i64, now: i64) -> u16 {
+        if start_date - now >= self.full_refund_notice {
+            FULL_REFUND_BPS
+        } else {
+            self.late_refund_bps
+        }
+    }
+}
+
+impl Rental {
+    /// 設定租期；起租日尚未到達時為待起租 (`Pending`)，否則立即生效
+    pub fn schedule(&mut self, start_date: i64, contract_length: u8, now: i64) {
+        self.start_date = start_date;
+        self.end_date = start_date + (contract_length as i64 * 30 * 24 * 60 * 60);
+        self.status = if start_date > now {
+            RentalStatus::Pending
+        } else {
+            RentalStatus::Active
+        };
+    }
+
+    /// 起租前託管的款項：SOL 為託管押金與首月租金，USDT 為託管於租金託管代幣帳戶的押金與首月租金
+    pub fn pending_escrow(&self) -> u64 {
+        match self.payment_method {
+            PaymentMethod::Sol => self.escrow_balance + self.prepaid_rent,
+            PaymentMethod::Usdt if self.prepaid_rent > 0 => self.deposit + self.prepaid_rent,
+            PaymentMethod::Usdt => 0,
+        }
+    }
+
+    /// 起租前取消時拆分託管款項為 (退還租客, 歸房東)；房東方取消一律全額退還
+    pub fn cancellation_split(&self, by_landlord: bool, now: i64) -> (u64, u64) {
+        let held = self.pending_escrow();
+        let refund_bps = if by_landlord {
+            FULL_REFUND_BPS
+        } else {
+            self.cancellation_policy.refund_bps(self.start_date, now)
+        };
+        let refund = (held as u128 * refund_bps as u128 / FULL_REFUND_BPS as u128) as u64;
+        (refund, held - refund)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn pending_rental(policy: CancellationPolicy) -> Rental {
+        Rental {
+            escrow_balance: 2_000,
+            prepaid_rent: 1_000,
+            cancellation_policy: policy,
+            ..Rental::test_fixture()
+        }
+    }
+
+    #[test]
+    fn schedules_future_start_as_pending() {
+        let mut rental = pending_rental(CancellationPolicy::default());
+        rental.schedule(start_date(NOW + 10 * DAY, NOW), 12, NOW);
+        assert!(rental.status == RentalStatus::Pending);
+        assert_eq!(rental.end_date, NOW + 10 * DAY + 360 * DAY);
+
+        rental.schedule(start_date(NOW - DAY, NOW), 12, NOW);
+        assert!(rental.status == RentalStatus::Active);
+        assert_eq!(rental.start_date, NOW);
+    }
+
+    #[test]
+    fn splits_pre_start_cancellation_refund() {
+        let policy = CancellationPolicy { full_refund_notice: 7 * DAY, late_refund_bps: 5_000 };
+        assert!(policy.is_valid());
+        assert!(!CancellationPolicy { late_refund_bps: 10_001, ..policy }.is_valid());
+
+        let mut rental = pending_rental(policy);
+        rental.schedule(NOW + 10 * DAY, 12, NOW);
+        assert_eq!(rental.cancellation_split(false, NOW), (3_000, 0));
+        assert_eq!(rental.cancellation_split(false, NOW + 5 * DAY), (1_500, 1_500));
+        assert_eq!(rental.cancellation_split(true, NOW + 5 * DAY), (3_000, 0));
+
+        // 預設規則：起租前取消皆全額退款
+        rental.cancellation_policy = CancellationPolicy::default();
+        assert_eq!(rental.cancellation_split(false, NOW + 9 * DAY), (3_000, 0));
+
+        rental.payment_method = PaymentMethod::Usdt;
+        rental.escrow_balance = 0;
+        assert_eq!(rental.pending_escrow(), 3_000);
+        rental.prepaid_rent = 0;
+        assert_eq!(rental.pending_escrow(), 0);
+    }
+}
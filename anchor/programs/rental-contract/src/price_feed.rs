@@ -0,0 +1,476 @@
+use anchor_lang::prelude::*;
+
+use crate::oracle::OracleError;
+use crate::{FiatCurrency, FiatPrice, PaymentMethod, Platform, PriceQuote};
+
+/// Pyth v2 價格帳戶格式常數
+const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+const PYTH_VERSION_2: u32 = 2;
+const PYTH_ACCOUNT_TYPE_PRICE: u32 = 3;
+const PYTH_STATUS_TRADING: u32 = 1;
+
+/// 價格帳戶欄位偏移 (對應 pyth-sdk-solana `PriceAccount` 的 repr(C) 排列)
+const OFFSET_MAGIC: usize = 0;
+const OFFSET_VERSION: usize = 4;
+const OFFSET_ACCOUNT_TYPE: usize = 8;
+const OFFSET_EXPO: usize = 20;
+const OFFSET_TIMESTAMP: usize = 96;
+const OFFSET_AGG_PRICE: usize = 208;
+const OFFSET_AGG_CONF: usize = 216;
+const OFFSET_AGG_STATUS: usize = 224;
+const PRICE_ACCOUNT_MIN_LEN: usize = 240;
+
+const LAMPORTS_PER_SOL: u128 = 1_000_000_000;
+const USDT_UNITS: u128 = 1_000_000;
+const CENTS_PER_UNIT: u128 = 100;
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Pyth 價格預言機讀取與法幣換算
+pub struct PriceFeedValidator;
+
+impl PriceFeedValidator {
+    /// 檢查平台報價限制：報價時效須大於 0 秒、信賴區間上限不超過 10,000 基點
+    pub fn is_valid_limits(max_age: u64, max_conf_bps: u16) -> bool {
+        max_age > 0 && max_conf_bps as u128 <= BPS_DENOMINATOR
+    }
+
+    /// 讀取平台設定的價格帳戶，並套用平台的時效與信賴區間限制
+    pub fn load_platform_quote(
+        price_account: Option<&AccountInfo>,
+        expected_feed: &Pubkey,
+        platform: &Platform,
+        current_time: i64,
+    ) -> Result<PriceQuote> {
+        require!(*expected_feed != Pubkey::default(), OracleError::PriceFeedNotConfigured);
+        let price_account = price_account.ok_or(OracleError::PriceFeedNotConfigured)?;
+        require_keys_eq!(*price_account.key, *expected_feed, OracleError::InvalidPriceFeed);
+
+        Self::load_quote(
+            price_account,
+            current_time,
+            platform.max_price_age,
+            platform.max_price_conf_bps,
+        )
+    }
+
+    /// 載入房源租金換算所需的報價 (SOL/USD, USD/TWD)
+    ///
+    /// 非法幣計價的 SOL 房源需要 SOL/USD；價格分桶一律以新台幣計，因此除非租金與
+    /// 租金區間皆為新台幣，否則需要 USD/TWD。
+    pub fn load_rent_quotes(
+        payment_method: &PaymentMethod,
+        fiat_price: Option<&FiatPrice>,
+        band_currency: FiatCurrency,
+        sol_usd_feed: Option<&AccountInfo>,
+        usd_twd_feed: Option<&AccountInfo>,
+        platform: &Platform,
+        current_time: i64,
+    ) -> Result<(Option<PriceQuote>, Option<PriceQuote>)> {
+        let source_currency = fiat_price.map_or(FiatCurrency::Usd, |fiat| fiat.currency);
+
+        let sol_usd = if fiat_price.is_none() && *payment_method == PaymentMethod::Sol {
+            Some(Self::load_platform_quote(
+                sol_usd_feed,
+                &platform.sol_usd_price_feed,
+                platform,
+                current_time,
+            )?)
+        } else {
+            None
+        };
+
+        let usd_twd = if source_currency != FiatCurrency::Twd || band_currency != FiatCurrency::Twd {
+            Some(Self::load_platform_quote(
+                usd_twd_feed,
+                &platform.usd_twd_price_feed,
+                platform,
+                current_time,
+            )?)
+        } else {
+            None
+        };
+
+        Ok((sol_usd, usd_twd))
+    }
+
+    /// 讀取 Pyth 價格帳戶並檢查時效與信賴區間
+    pub fn load_quote(
+        price_account: &AccountInfo,
+        current_time: i64,
+        max_age: u64,
+        max_conf_bps: u16,
+    ) -> Result<PriceQuote> {
+        let data = price_account
+            .try_borrow_data()
+            .map_err(|_| error!(OracleError::InvalidPriceFeed))?;
+        let quote = Self::parse_price_account(&data)?;
+
+        // 報價時間與鏈上時間差距超過上限即視為過期
+        let age = current_time.abs_diff(quote.publish_time);
+        require!(age <= max_age, OracleError::StalePrice);
+
+        // 信賴區間 (conf / price) 不可超過設定的基點
+        require!(
+            (quote.conf as u128) * BPS_DENOMINATOR <= (quote.price as u128) * max_conf_bps as u128,
+            OracleError::PriceConfidenceTooWide
+        );
+
+        Ok(quote)
+    }
+
+    /// 解析 Pyth v2 價格帳戶原始資料，僅接受交易中 (Trading) 的正報價
+    pub fn parse_price_account(data: &[u8]) -> Result<PriceQuote> {
+        require!(data.len() >= PRICE_ACCOUNT_MIN_LEN, OracleError::InvalidPriceFeed);
+        require!(read_u32(data, OFFSET_MAGIC) == PYTH_MAGIC, OracleError::InvalidPriceFeed);
+        require!(read_u32(data, OFFSET_VERSION) == PYTH_VERSION_2, OracleError::InvalidPriceFeed);
+        require!(
+            read_u32(data, OFFSET_ACCOUNT_TYPE) == PYTH_ACCOUNT_TYPE_PRICE,
+            OracleError::InvalidPriceFeed
+        );
+        require!(
+            read_u32(data, OFFSET_AGG_STATUS) == PYTH_STATUS_TRADING,
+            OracleError::StalePrice
+        );
+
+        let price = read_i64(data, OFFSET_AGG_PRICE);
+        require!(price > 0, OracleError::InvalidPriceFeed);
+
+        Ok(PriceQuote {
+            price,
+            conf: read_u64(data, OFFSET_AGG_CONF),
+            expo: read_i32(data, OFFSET_EXPO),
+            publish_time: read_i64(data, OFFSET_TIMESTAMP),
+        })
+    }
+
+    /// 依房源押金與租金的比例 (以 lamports 標示) 換算法幣計價的押金
+    pub fn peg_deposit(fiat_price: &FiatPrice, price: u64, deposit: u64) -> Result<FiatPrice> {
+        require!(price > 0, OracleError::PriceConversionFailed);
+        let amount = fiat_price.amount as u128 * deposit as u128 / price as u128;
+        Ok(FiatPrice {
+            amount: u64::try_from(amount).map_err(|_| error!(OracleError::PriceConversionFailed))?,
+            currency: fiat_price.currency,
+        })
+    }
+
+    /// 將法幣租金換算為 lamports
+    ///
+    /// `sol_usd` 為 SOL/USD 報價；租金以新台幣計價時需另外提供 USD/TWD 報價。
+    pub fn fiat_to_lamports(
+        fiat_price: &FiatPrice,
+        sol_usd: &PriceQuote,
+        usd_twd: Option<&PriceQuote>,
+    ) -> Result<u64> {
+        // 以分數 num / den 累積換算，最後一次除法避免中途捨入
+        let mut num = (fiat_price.amount as u128)
+            .checked_mul(LAMPORTS_PER_SOL)
+            .ok_or(OracleError::PriceConversionFailed)?;
+        let mut den = CENTS_PER_UNIT;
+
+        if fiat_price.currency == FiatCurrency::Twd {
+            // 新台幣 -> 美元：除以 USD/TWD
+            let usd_twd = usd_twd.ok_or(OracleError::PriceFeedNotConfigured)?;
+            (num, den) = divide_by_quote(num, den, usd_twd)?;
+        }
+
+        // 美元 -> SOL：除以 SOL/USD
+        (num, den) = divide_by_quote(num, den, sol_usd)?;
+
+        u64::try_from(num / den).map_err(|_| error!(OracleError::PriceConversionFailed))
+    }
+
+    /// 將房源月租換算為指定法幣 (分)
+    ///
+    /// 法幣計價房源直接使用其法幣租金；否則依支付幣別換算：SOL 需要 SOL/USD 報價，
+    /// USDT 視為 1:1 美元。來源與目標幣別不同時需提供 USD/TWD 報價。
+    /// USDT 租約依 `price` 收取，不接受法幣計價。
+    pub fn rent_to_fiat(
+        price: u64,
+        payment_method: &PaymentMethod,
+        fiat_price: Option<&FiatPrice>,
+        target: FiatCurrency,
+        sol_usd: Option<&PriceQuote>,
+        usd_twd: Option<&PriceQuote>,
+    ) -> Result<u64> {
+        let (mut num, mut den, source) = match (fiat_price, payment_method) {
+            (Some(fiat_price), PaymentMethod::Sol) => (fiat_price.amount as u128, 1, fiat_price.currency),
+            (Some(_), PaymentMethod::Usdt) => return err!(OracleError::FiatPriceRequiresSol),
+            (None, PaymentMethod::Sol) => {
+                // lamports -> 美分：乘以 SOL/USD
+                let sol_usd = sol_usd.ok_or(OracleError::PriceFeedNotConfigured)?;
+                let (num, den) = multiply_by_quote(
+                    price as u128 * CENTS_PER_UNIT,
+                    LAMPORTS_PER_SOL,
+                    sol_usd,
+                )?;
+                (num, den, FiatCurrency::Usd)
+            }
+            (None, PaymentMethod::Usdt) => {
+                (price as u128 * CENTS_PER_UNIT, USDT_UNITS, FiatCurrency::Usd)
+            }
+        };
+
+        if source != target {
+            let usd_twd = usd_twd.ok_or(OracleError::PriceFeedNotConfigured)?;
+            (num, den) = match target {
+                FiatCurrency::Twd => multiply_by_quote(num, den, usd_twd)?,
+                FiatCurrency::Usd => divide_by_quote(num, den, usd_twd)?,
+            };
+        }
+
+        u64::try_from(num / den).map_err(|_| error!(OracleError::PriceConversionFailed))
+    }
+}
+
+/// 將 num / den 乘以報價 (price * 10^expo)
+fn multiply_by_quote(num: u128, den: u128, quote: &PriceQuote) -> Result<(u128, u128)> {
+    let scale = 10u128
+        .checked_pow(quote.expo.unsigned_abs())
+        .ok_or(OracleError::PriceConversionFailed)?;
+    let num = num
+        .checked_mul(quote.price as u128)
+        .ok_or(OracleError::PriceConversionFailed)?;
+
+    let scaled = if quote.expo < 0 {
+        (Some(num), den.checked_mul(scale))
+    } else {
+        (num.checked_mul(scale), Some(den))
+    };
+
+    match scaled {
+        (Some(num), Some(den)) => Ok((num, den)),
+        _ => err!(OracleError::PriceConversionFailed),
+    }
+}
+
+/// 將 num / den 除以報價 (price * 10^expo)
+fn divide_by_quote(num: u128, den: u128, quote: &PriceQuote) -> Result<(u128, u128)> {
+    let scale = 10u128
+        .checked_pow(quote.expo.unsigned_abs())
+        .ok_or(OracleError::PriceConversionFailed)?;
+    let den = den
+        .checked_mul(quote.price as u128)
+        .ok_or(OracleError::PriceConversionFailed)?;
+
+    let scaled = if quote.expo < 0 {
+        (num.checked_mul(scale), Some(den))
+    } else {
+        (Some(num), den.checked_mul(scale))
+    };
+
+    match scaled {
+        (Some(num), Some(den)) => Ok((num, den)),
+        _ => err!(OracleError::PriceConversionFailed),
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_i32(data: &[u8], offset: usize) -> i32 {
+    i32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUBLISH_TIME: i64 = 1_700_000_000;
+
+    /// 在本地組出一個 Pyth v2 價格帳戶
+    fn price_account_data(price: i64, conf: u64, expo: i32, status: u32) -> Vec<u8> {
+        let mut data = vec![0u8; 3312];
+        data[OFFSET_MAGIC..OFFSET_MAGIC + 4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+        data[OFFSET_VERSION..OFFSET_VERSION + 4].copy_from_slice(&PYTH_VERSION_2.to_le_bytes());
+        data[OFFSET_ACCOUNT_TYPE..OFFSET_ACCOUNT_TYPE + 4]
+            .copy_from_slice(&PYTH_ACCOUNT_TYPE_PRICE.to_le_bytes());
+        data[OFFSET_EXPO..OFFSET_EXPO + 4].copy_from_slice(&expo.to_le_bytes());
+        data[OFFSET_TIMESTAMP..OFFSET_TIMESTAMP + 8].copy_from_slice(&PUBLISH_TIME.to_le_bytes());
+        data[OFFSET_AGG_PRICE..OFFSET_AGG_PRICE + 8].copy_from_slice(&price.to_le_bytes());
+        data[OFFSET_AGG_CONF..OFFSET_AGG_CONF + 8].copy_from_slice(&conf.to_le_bytes());
+        data[OFFSET_AGG_STATUS..OFFSET_AGG_STATUS + 4].copy_from_slice(&status.to_le_bytes());
+        data
+    }
+
+    fn load(data: &mut [u8], current_time: i64) -> Result<PriceQuote> {
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let info = AccountInfo::new(&key, false, false, &mut lamports, data, &owner, false, 0);
+        PriceFeedValidator::load_quote(&info, current_time, 60, 100)
+    }
+
+    fn sol_usd_150() -> PriceQuote {
+        PriceQuote { price: 15_000_000_000, conf: 1_000_000, expo: -8, publish_time: PUBLISH_TIME }
+    }
+
+    #[test]
+    fn loads_fresh_trading_price() {
+        let mut data = price_account_data(15_000_000_000, 5_000_000, -8, PYTH_STATUS_TRADING);
+        let quote = load(&mut data, PUBLISH_TIME + 30).unwrap();
+        assert_eq!(quote.price, 15_000_000_000);
+        assert_eq!(quote.conf, 5_000_000);
+        assert_eq!(quote.expo, -8);
+        assert_eq!(quote.publish_time, PUBLISH_TIME);
+    }
+
+    #[test]
+    fn rejects_stale_price() {
+        let mut data = price_account_data(15_000_000_000, 5_000_000, -8, PYTH_STATUS_TRADING);
+        assert_eq!(
+            load(&mut data, PUBLISH_TIME + 61).unwrap_err(),
+            error!(OracleError::StalePrice)
+        );
+    }
+
+    #[test]
+    fn rejects_non_trading_status() {
+        let mut data = price_account_data(15_000_000_000, 5_000_000, -8, 2);
+        assert_eq!(
+            load(&mut data, PUBLISH_TIME).unwrap_err(),
+            error!(OracleError::StalePrice)
+        );
+    }
+
+    #[test]
+    fn rejects_wide_confidence() {
+        // 信賴區間 1.5 美元 = 報價的 100 bps，剛好通過；再大一點即失敗
+        let mut data = price_account_data(15_000_000_000, 150_000_000, -8, PYTH_STATUS_TRADING);
+        assert!(load(&mut data, PUBLISH_TIME).is_ok());
+
+        let mut data = price_account_data(15_000_000_000, 150_000_001, -8, PYTH_STATUS_TRADING);
+        assert_eq!(
+            load(&mut data, PUBLISH_TIME).unwrap_err(),
+            error!(OracleError::PriceConfidenceTooWide)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_account() {
+        let mut data = price_account_data(15_000_000_000, 5_000_000, -8, PYTH_STATUS_TRADING);
+        data[OFFSET_MAGIC] = 0;
+        assert_eq!(
+            load(&mut data, PUBLISH_TIME).unwrap_err(),
+            error!(OracleError::InvalidPriceFeed)
+        );
+
+        let mut short = vec![0u8; PRICE_ACCOUNT_MIN_LEN - 1];
+        assert_eq!(
+            load(&mut short, PUBLISH_TIME).unwrap_err(),
+            error!(OracleError::InvalidPriceFeed)
+        );
+    }
+
+    #[test]
+    fn converts_usd_cents_to_lamports() {
+        // 300 美元 @ 150 USD/SOL = 2 SOL
+        let rent = FiatPrice { amount: 30_000, currency: FiatCurrency::Usd };
+        let lamports = PriceFeedValidator::fiat_to_lamports(&rent, &sol_usd_150(), None).unwrap();
+        assert_eq!(lamports, 2_000_000_000);
+    }
+
+    #[test]
+    fn converts_twd_cents_to_lamports() {
+        // 9,600 新台幣 @ 32 TWD/USD = 300 美元 = 2 SOL
+        let usd_twd = PriceQuote { price: 3_200_000, conf: 100, expo: -5, publish_time: PUBLISH_TIME };
+        let rent = FiatPrice { amount: 960_000, currency: FiatCurrency::Twd };
+        let lamports =
+            PriceFeedValidator::fiat_to_lamports(&rent, &sol_usd_150(), Some(&usd_twd)).unwrap();
+        assert_eq!(lamports, 2_000_000_000);
+    }
+
+    #[test]
+    fn pegs_deposit_to_fiat_rent() {
+        // 房源標示押金為兩個月租金，月租 300 美元時押金為 600 美元
+        let rent = FiatPrice { amount: 30_000, currency: FiatCurrency::Usd };
+        let deposit = PriceFeedValidator::peg_deposit(&rent, 1_000_000_000, 2_000_000_000).unwrap();
+        assert_eq!(deposit.amount, 60_000);
+        assert!(deposit.currency == FiatCurrency::Usd);
+        assert_eq!(
+            PriceFeedValidator::peg_deposit(&rent, 0, 2_000_000_000).map(|deposit| deposit.amount),
+            Err(OracleError::PriceConversionFailed.into())
+        );
+    }
+
+    #[test]
+    fn converts_listing_rent_to_band_currency() {
+        let usd_twd = PriceQuote { price: 3_200_000, conf: 100, expo: -5, publish_time: PUBLISH_TIME };
+
+        // 2 SOL @ 150 USD/SOL = 300 美元 = 9,600 新台幣
+        let rent = PriceFeedValidator::rent_to_fiat(
+            2_000_000_000,
+            &PaymentMethod::Sol,
+            None,
+            FiatCurrency::Twd,
+            Some(&sol_usd_150()),
+            Some(&usd_twd),
+        )
+        .unwrap();
+        assert_eq!(rent, 960_000);
+
+        // 300 USDT 視為 300 美元
+        let rent = PriceFeedValidator::rent_to_fiat(
+            300_000_000,
+            &PaymentMethod::Usdt,
+            None,
+            FiatCurrency::Usd,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(rent, 30_000);
+
+        // 法幣計價房源直接使用法幣租金，不需要 SOL/USD 報價
+        let fiat = FiatPrice { amount: 960_000, currency: FiatCurrency::Twd };
+        let rent = PriceFeedValidator::rent_to_fiat(
+            1,
+            &PaymentMethod::Sol,
+            Some(&fiat),
+            FiatCurrency::Usd,
+            None,
+            Some(&usd_twd),
+        )
+        .unwrap();
+        assert_eq!(rent, 30_000);
+
+        // USDT 房源不可以法幣計價通過租金區間檢查
+        assert_eq!(
+            PriceFeedValidator::rent_to_fiat(
+                1,
+                &PaymentMethod::Usdt,
+                Some(&fiat),
+                FiatCurrency::Usd,
+                None,
+                Some(&usd_twd),
+            )
+            .unwrap_err(),
+            OracleError::FiatPriceRequiresSol.into()
+        );
+    }
+
+    #[test]
+    fn validates_platform_limits() {
+        assert!(PriceFeedValidator::is_valid_limits(60, 100));
+        assert!(PriceFeedValidator::is_valid_limits(1, 10_000));
+        assert!(!PriceFeedValidator::is_valid_limits(0, 100));
+        assert!(!PriceFeedValidator::is_valid_limits(60, 10_001));
+    }
+
+    #[test]
+    fn twd_requires_fx_quote() {
+        let rent = FiatPrice { amount: 960_000, currency: FiatCurrency::Twd };
+        assert_eq!(
+            PriceFeedValidator::fiat_to_lamports(&rent, &sol_usd_150(), None).unwrap_err(),
+            error!(OracleError::PriceFeedNotConfigured)
+        );
+    }
+}
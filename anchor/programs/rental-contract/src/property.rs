@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+
+use crate::{Address, Listing, Property, RentalError};
+
+impl Property {
+    /// 檢查單位地址位於此建物，且單位樓層與總樓層和建物一致
+    pub fn check_unit(&self, address: &Address, total_floors: u8) -> Result<()> {
+        require!(
+            address.county_code == self.address.county_code
+                && address.district_code == self.address.district_code
+                && address.street == self.address.street
+                && address.number == self.address.number,
+            RentalError::PropertyMismatch
+        );
+        require!(
+            total_floors == self.total_floors && address.floor > 0 && address.floor <= self.total_floors,
+            RentalError::InvalidUnitFloor
+        );
+        Ok(())
+    }
+
+    /// 記錄新上架的單位
+    pub fn add_unit(&mut self, floor: u8) {
+        self.total_units += 1;
+        self.top_unit_floor = self.top_unit_floor.max(floor);
+    }
+
+    /// 調整建物總樓層，不得低於已有單位的最高樓層
+    pub fn set_total_floors(&mut self, total_floors: u8) -> Result<()> {
+        require!(
+            total_floors > 0 && total_floors >= self.top_unit_floor,
+            RentalError::InvalidUnitFloor
+        );
+        self.total_floors = total_floors;
+        Ok(())
+    }
+
+    /// 出租率 (基點)，尚無單位時為 0
+    pub fn occupancy_bps(&self) -> u16 {
+        if self.total_units == 0 {
+            return 0;
+        }
+        (self.occupied_units as u32 * 10_000 / self.total_units as u32) as u16
+    }
+}
+
+/// 房源出租或重新開放時更新所屬建物的出租單位數
+///
+/// 獨立房源不需傳入建物；建物單位則須傳入 `Listing.property` 指向的建物帳戶。
+pub fn record_occupancy(listing: &Listing, property: Option<&mut Property>, occupied: bool) -> Result<()> {
+    if listing.property == Pubkey::default() {
+        return Ok(());
+    }
+    let property = property.ok_or(RentalError::PropertyMismatch)?;
+    if occupied {
+        property.occupied_units = (property.occupied_units + 1).min(property.total_units);
+    } else {
+        property.occupied_units = property.occupied_units.saturating_sub(1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn building() -> Property {
+        Property {
+            authority: Pubkey::new_unique(),
+            property_id: 1,
+            name: "信義大樓".to_string(),
+            address: Address {
+                county_code: 63000,
+                district_code: 63000020,
+                district_name: "信義區".to_string(),
+                street: "信義路五段".to_string(),
+                number: "7號".to_string(),
+                floor: 0,
+                unit: None,
+                postal_code: "110".to_string(),
+            },
+            coordinates: crate::Coordinates { latitude: 25_033_964, longitude: 121_564_468 },
+            total_floors: 12,
+            amenities: crate::Amenities::default(),
+            total_units: 0,
+            occupied_units: 0,
+            top_unit_floor: 0,
+            bump: 255,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    fn unit(property: &Property, floor: u8) -> Address {
+        Address {
+            floor,
+            unit: Some("之3".to_string()),
+            ..property.address.clone()
+        }
+    }
+
+    #[test]
+    fn checks_unit_belongs_to_building() {
+        let property = building();
+        assert!(property.check_unit(&unit(&property, 12), 12).is_ok());
+
+        let mut other_street = unit(&property, 3);
+        other_street.street = "松仁路".to_string();
+        assert_eq!(property.check_unit(&other_street, 12), Err(RentalError::PropertyMismatch.into()));
+
+        let invalid = Err(RentalError::InvalidUnitFloor.into());
+        assert_eq!(property.check_unit(&unit(&property, 13), 12), invalid);
+        assert_eq!(property.check_unit(&unit(&property, 0), 12), invalid);
+        assert_eq!(property.check_unit(&unit(&property, 3), 10), invalid);
+    }
+
+    #[test]
+    fn keeps_total_floors_above_units() {
+        let mut property = building();
+        property.add_unit(9);
+        property.add_unit(4);
+        assert_eq!(property.top_unit_floor, 9);
+        assert_eq!(property.set_total_floors(8), Err(RentalError::InvalidUnitFloor.into()));
+        property.set_total_floors(9).unwrap();
+        assert_eq!(property.total_floors, 9);
+    }
+
+    #[test]
+    fn tracks_occupancy() {
+        let mut property = building();
+        assert_eq!(property.occupancy_bps(), 0);
+        for floor in 1..=4 {
+            property.add_unit(floor);
+        }
+        property.occupied_units = 3;
+        assert_eq!(property.occupancy_bps(), 7_500);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::TenantProfile;
+
+/// 新租客的初始信用分數
+pub const INITIAL_REPUTATION_SCORE: u16 = 500;
+/// 信用分數上限
+pub const MAX_REPUTATION_SCORE: u16 = 1000;
+/// 付款寬限期 (秒)，超過到期日 5 天視為逾期
+pub const LATE_PAYMENT_GRACE_PERIOD: i64 = 5 * 24 * 60 * 60;
+
+const ON_TIME_PAYMENT_POINTS: i64 = 10;
+const LATE_PAYMENT_PENALTY: i64 = 30;
+const TENANT_TERMINATION_PENALTY: i64 = 50;
+const DEPOSIT_SETTLED_POINTS: i64 = 5;
+/// 押金全數扣除 (10,000 基點) 扣 100 分
+const DEDUCTION_BPS_PER_POINT: u64 = 100;
+
+impl TenantProfile {
+    /// 記錄一次付租，超過寬限期視為逾期
+    pub fn record_payment(&mut self, due_date: i64, paid_at: i64) {
+        if paid_at > due_date + LATE_PAYMENT_GRACE_PERIOD {
+            self.late_payments = self.late_payments.saturating_add(1);
+        } else {
+            self.on_time_payments = self.on_time_payments.saturating_add(1);
+        }
+        self.reputation_score = self.compute_score();
+    }
+
+    /// 記錄租約終止及終止方
+    pub fn record_termination(&mut self, by_tenant: bool) {
+        if by_tenant {
+            self.tenant_terminations = self.tenant_terminations.saturating_add(1);
+        } else {
+            self.landlord_terminations = self.landlord_terminations.saturating_add(1);
+        }
+        self.reputation_score = self.compute_score();
+    }
+
+    /// 記錄押金結算與扣除金額
+    pub fn record_deposit_settlement(&mut self, deposit: u64, deduction: u64) {
+        let deduction_bps = if deposit == 0 {
+            0
+        } else {
+            (deduction.min(deposit) as u128 * 10_000 / deposit as u128) as u64
+        };
+        self.deposits_settled = self.deposits_settled.saturating_add(1);
+        self.deposit_deduction_bps = self.deposit_deduction_bps.saturating_add(deduction_bps);
+        self.reputation_score = self.compute_score();
+    }
+
+    /// 依付款、終止與押金紀錄計算信用分數 (0 ~ 1000)
+    pub fn compute_score(&self) -> u16 {
+        let score = INITIAL_REPUTATION_SCORE as i64
+            + self.on_time_payments as i64 * ON_TIME_PAYMENT_POINTS
+            - self.late_payments as i64 * LATE_PAYMENT_PENALTY
+            - self.tenant_terminations as i64 * TENANT_TERMINATION_PENALTY
+            + self.deposits_settled as i64 * DEPOSIT_SETTLED_POINTS
+            - (self.deposit_deduction_bps / DEDUCTION_BPS_PER_POINT).min(i64::MAX as u64) as i64;
+        score.clamp(0, MAX_REPUTATION_SCORE as i64) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::Pubkey;
+
+    const DUE: i64 = 1_700_000_000;
+
+    fn profile() -> TenantProfile {
+        TenantProfile {
+            wallet: Pubkey::new_unique(),
+            reputation_score: INITIAL_REPUTATION_SCORE,
+            attestation: None,
+            on_time_payments: 0,
+            late_payments: 0,
+            tenant_terminations: 0,
+            landlord_terminations: 0,
+            deposits_settled: 0,
+            deposit_deduction_bps: 0,
+            created_at: 0,
+            updated_at: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn records_on_time_and_late_payments() {
+        let mut profile = profile();
+        profile.record_payment(DUE, DUE + LATE_PAYMENT_GRACE_PERIOD);
+        assert_eq!(profile.on_time_payments, 1);
+        assert_eq!(profile.reputation_score, 510);
+
+        profile.record_payment(DUE, DUE + LATE_PAYMENT_GRACE_PERIOD + 1);
+        assert_eq!(profile.late_payments, 1);
+        assert_eq!(profile.reputation_score, 480);
+    }
+
+    #[test]
+    fn penalizes_only_tenant_terminations() {
+        let mut profile = profile();
+        profile.record_termination(false);
+        assert_eq!(profile.landlord_terminations, 1);
+        assert_eq!(profile.reputation_score, INITIAL_REPUTATION_SCORE);
+
+        profile.record_termination(true);
+        assert_eq!(profile.tenant_terminations, 1);
+        assert_eq!(profile.reputation_score, 450);
+    }
+
+    #[test]
+    fn scales_deposit_deductions() {
+        let mut profile = profile();
+        profile.record_deposit_settlement(1_000, 0);
+        assert_eq!(profile.reputation_score, 505);
+
+        // 扣除一半押金扣 50 分
+        profile.record_deposit_settlement(1_000, 500);
+        assert_eq!(profile.deposit_deduction_bps, 5_000);
+        assert_eq!(profile.reputation_score, 460);
+
+        // 扣除金額超過押金以全額計
+        profile.record_deposit_settlement(1_000, 5_000);
+        assert_eq!(profile.deposit_deduction_bps, 15_000);
+    }
+
+    #[test]
+    fn clamps_score() {
+        let mut profile = profile();
+        profile.late_payments = 100;
+        assert_eq!(profile.compute_score(), 0);
+
+        profile.late_payments = 0;
+        profile.on_time_payments = 1_000;
+        assert_eq!(profile.compute_score(), MAX_REPUTATION_SCORE);
+    }
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+use crate::{Listing, RentalError, Reservation};
+
+/// 預留期間上限 (秒)
+pub const MAX_RESERVATION_PERIOD: i64 = 14 * 24 * 60 * 60;
+
+/// 檢查預留條件：停用時費用與期間皆為 0，啟用時費用介於 1 ~ 押金、期間介於 1 秒 ~ `MAX_RESERVATION_PERIOD`
+pub fn is_valid_terms(fee: u64, period: i64, deposit: u64) -> bool {
+    (fee == 0 && period == 0) || (fee > 0 && fee <= deposit && period > 0 && period <= MAX_RESERVATION_PERIOD)
+}
+
+impl Listing {
+    /// 房源是否由未過期的預留保留中
+    pub fn is_reserved(&self, now: i64) -> bool {
+        self.reserved_until > now
+    }
+
+    /// 檢查房源未由他人保留；保留過期後即不再阻擋，不必等待退款
+    pub fn check_not_reserved(&self, tenant: &Pubkey, now: i64) -> Result<()> {
+        require!(
+            !self.is_reserved(now) || self.reserved_by == *tenant,
+            RentalError::ListingReserved
+        );
+        Ok(())
+    }
+
+    /// 清除指定租客的保留狀態
+    pub fn clear_reservation(&mut self, tenant: &Pubkey) {
+        if self.reserved_by == *tenant {
+            self.reserved_by = Pubkey::default();
+            self.reserved_until = 0;
+        }
+    }
+}
+
+impl Reservation {
+    /// 預留轉為租約時可抵付押金的預留費用，須為同一租客且預留尚未過期
+    pub fn credit(&self, tenant: &Pubkey, now: i64) -> Result<u64> {
+        require!(self.tenant == *tenant, RentalError::InvalidReservation);
+        require!(now < self.expires_at, RentalError::ReservationExpired);
+        Ok(self.fee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn validates_reservation_terms() {
+        assert!(is_valid_terms(0, 0, 1_000));
+        assert!(is_valid_terms(200, 3 * 24 * 60 * 60, 1_000));
+        assert!(is_valid_terms(1_000, MAX_RESERVATION_PERIOD, 1_000));
+
+        assert!(!is_valid_terms(0, 60, 1_000));
+        assert!(!is_valid_terms(200, 0, 1_000));
+        assert!(!is_valid_terms(1_001, 60, 1_000));
+        assert!(!is_valid_terms(200, MAX_RESERVATION_PERIOD + 1, 1_000));
+    }
+
+    #[test]
+    fn credits_unexpired_reservation_to_its_tenant() {
+        let reservation = Reservation {
+            listing: Pubkey::new_unique(),
+            tenant: Pubkey::new_unique(),
+            fee: 300,
+            expires_at: NOW + 60,
+            created_at: NOW,
+            bump: 255,
+        };
+        assert_eq!(reservation.credit(&reservation.tenant, NOW), Ok(300));
+        assert_eq!(
+            reservation.credit(&Pubkey::new_unique(), NOW),
+            Err(RentalError::InvalidReservation.into())
+        );
+        assert_eq!(
+            reservation.credit(&reservation.tenant, NOW + 60),
+            Err(RentalError::ReservationExpired.into())
+        );
+    }
+}
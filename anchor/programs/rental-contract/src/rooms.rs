@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+
+use crate::{Listing, RentalError, RoomUnit};
+
+/// 每個房源最多分租房間數
+pub const MAX_ROOM_UNITS: usize = 8;
+
+/// 檢查分租房間設定：編號不得重複、坪數與租金皆大於 0，且房間總坪數不超過房源坪數
+pub fn validate_rooms(rooms: &[RoomUnit], listing_size: u32) -> Result<()> {
+    require!(rooms.len() <= MAX_ROOM_UNITS, RentalError::InvalidRoomUnits);
+    let mut total_size: u64 = 0;
+    for (index, room) in rooms.iter().enumerate() {
+        require!(room.size > 0 && room.price > 0, RentalError::InvalidRoomUnits);
+        require!(
+            rooms[..index].iter().all(|other| other.room_id != room.room_id),
+            RentalError::InvalidRoomUnits
+        );
+        total_size += room.size as u64;
+    }
+    require!(total_size <= listing_size as u64, RentalError::InvalidRoomUnits);
+    Ok(())
+}
+
+impl Listing {
+    /// 是否有房間出租中 (此時不可整層出租或變更房間設定)
+    pub fn has_occupied_rooms(&self) -> bool {
+        self.room_units.iter().any(|room| room.rental != Pubkey::default())
+    }
+
+    /// 取得可租的房間
+    pub fn vacant_room(&self, room_id: u8) -> Result<&RoomUnit> {
+        let room = self
+            .room_units
+            .iter()
+            .find(|room| room.room_id == room_id)
+            .ok_or(RentalError::InvalidRoomUnits)?;
+        require!(room.rental == Pubkey::default(), RentalError::RoomNotAvailable);
+        Ok(room)
+    }
+
+    /// 將房間標記為由指定租約承租
+    pub fn occupy_room(&mut self, room_id: u8, rental: Pubkey) -> Result<()> {
+        self.vacant_room(room_id)?;
+        if let Some(room) = self.room_units.iter_mut().find(|room| room.room_id == room_id) {
+            room.rental = rental;
+        }
+        Ok(())
+    }
+
+    /// 租約結束後重新開放房間
+    pub fn release_room(&mut self, room_id: u8, rental: &Pubkey) {
+        if let Some(room) = self
+            .room_units
+            .iter_mut()
+            .find(|room| room.room_id == room_id && room.rental == *rental)
+        {
+            room.rental = Pubkey::default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room(room_id: u8, size: u32) -> RoomUnit {
+        RoomUnit {
+            room_id,
+            size,
+            price: 8_000,
+            deposit: 16_000,
+            private_bathroom: room_id == 1,
+            rental: Pubkey::default(),
+        }
+    }
+
+    #[test]
+    fn validates_room_sets() {
+        assert!(validate_rooms(&[room(1, 6), room(2, 4)], 20).is_ok());
+        assert!(validate_rooms(&[], 20).is_ok());
+
+        let invalid = Err(RentalError::InvalidRoomUnits.into());
+        assert_eq!(validate_rooms(&[room(1, 6), room(1, 4)], 20), invalid);
+        assert_eq!(validate_rooms(&[room(1, 12), room(2, 10)], 20), invalid);
+        assert_eq!(validate_rooms(&[room(1, 0)], 20), invalid);
+
+        let mut free = room(1, 6);
+        free.price = 0;
+        assert_eq!(validate_rooms(&[free], 20), invalid);
+
+        let too_many: Vec<RoomUnit> = (0..=MAX_ROOM_UNITS as u8).map(|id| room(id, 1)).collect();
+        assert_eq!(validate_rooms(&too_many, 20), invalid);
+    }
+}
@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+
+use crate::{RentalError, ShortStayTerms, StayCalendar};
+
+/// 一天的秒數，短租以 UTC 日 (`unix_timestamp / SECONDS_PER_DAY`) 為單位
+pub const SECONDS_PER_DAY: i64 = 86_400;
+/// 可預訂日曆涵蓋的天數 (自 `StayCalendar.start_day` 起)
+pub const CALENDAR_DAYS: usize = 368;
+/// 日曆位元組數
+pub const CALENDAR_BYTES: usize = CALENDAR_DAYS / 8;
+
+/// 時間戳記所在的 UTC 日
+pub fn day_of(timestamp: i64) -> u32 {
+    (timestamp / SECONDS_PER_DAY) as u32
+}
+
+impl ShortStayTerms {
+    /// 檢查每晚價格大於 0，且最短與最長晚數介於 1 ~ `CALENDAR_DAYS`
+    pub fn is_valid(&self) -> bool {
+        self.nightly_price > 0
+            && self.min_nights > 0
+            && self.min_nights <= self.max_nights
+            && self.max_nights as usize <= CALENDAR_DAYS
+    }
+
+    /// 計算住宿總價，晚數須介於最短與最長晚數之間
+    pub fn total_price(&self, nights: u16) -> Result<u64> {
+        require!(
+            (self.min_nights..=self.max_nights).contains(&nights),
+            RentalError::InvalidStayDates
+        );
+        self.nightly_price
+            .checked_mul(nights as u64)
+            .ok_or_else(|| error!(RentalError::InvalidStayDates))
+    }
+}
+
+impl StayCalendar {
+    fn is_booked(&self, offset: usize) -> bool {
+        self.booked[offset / 8] & (1 << (offset % 8)) != 0
+    }
+
+    fn set_booked(&mut self, offset: usize, booked: bool) {
+        if booked {
+            self.booked[offset / 8] |= 1 << (offset % 8);
+        } else {
+            self.booked[offset / 8] &= !(1 << (offset % 8));
+        }
+    }
+
+    /// 將日曆起點推進至今日，已過去的日期自位元圖移出
+    pub fn advance(&mut self, today: u32) {
+        if today <= self.start_day {
+            return;
+        }
+        let shift = (today - self.start_day) as usize;
+        for offset in 0..CALENDAR_DAYS {
+            let booked = offset + shift < CALENDAR_DAYS && self.is_booked(offset + shift);
+            self.set_booked(offset, booked);
+        }
+        self.start_day = today;
+    }
+
+    /// 日曆上是否已無預訂的夜晚
+    pub fn is_clear(&self) -> bool {
+        self.booked.iter().all(|byte| *byte == 0)
+    }
+
+    /// 入住日相對日曆起點的位移，住宿期間須完整落在日曆範圍內
+    fn offset_of(&self, check_in_day: u32, nights: u16) -> Result<usize> {
+        require!(
+            check_in_day >= self.start_day && nights > 0,
+            RentalError::InvalidStayDates
+        );
+        let offset = (check_in_day - self.start_day) as usize;
+        require!(
+            offset + nights as usize <= CALENDAR_DAYS,
+            RentalError::InvalidStayDates
+        );
+        Ok(offset)
+    }
+
+    /// 預訂入住日起的連續夜晚，與既有預訂重疊時回傳 `StayUnavailable`
+    pub fn reserve(&mut self, check_in_day: u32, nights: u16) -> Result<()> {
+        let offset = self.offset_of(check_in_day, nights)?;
+        let range = offset..offset + nights as usize;
+        require!(
+            range.clone().all(|day| !self.is_booked(day)),
+            RentalError::StayUnavailable
+        );
+        for day in range {
+            self.set_booked(day, true);
+        }
+        Ok(())
+    }
+
+    /// 取消預訂，重新開放尚在日曆範圍內的夜晚
+    pub fn release(&mut self, check_in_day: u32, nights: u16) {
+        let end_day = check_in_day + nights as u32;
+        for day in check_in_day.max(self.start_day)..end_day {
+            let offset = (day - self.start_day) as usize;
+            if offset < CALENDAR_DAYS {
+                self.set_booked(offset, false);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TODAY: u32 = 19_700;
+
+    fn calendar() -> StayCalendar {
+        StayCalendar {
+            listing: Pubkey::new_unique(),
+            start_day: TODAY,
+            booked: [0; CALENDAR_BYTES],
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn prices_stays_within_night_limits() {
+        let terms = ShortStayTerms { nightly_price: 1_500, min_nights: 2, max_nights: 14 };
+        assert!(terms.is_valid());
+        assert_eq!(terms.total_price(3), Ok(4_500));
+        assert_eq!(terms.total_price(1), Err(RentalError::InvalidStayDates.into()));
+        assert_eq!(terms.total_price(15), Err(RentalError::InvalidStayDates.into()));
+
+        assert!(!ShortStayTerms { nightly_price: 0, ..terms }.is_valid());
+        assert!(!ShortStayTerms { min_nights: 0, ..terms }.is_valid());
+        assert!(!ShortStayTerms { min_nights: 15, ..terms }.is_valid());
+    }
+
+    #[test]
+    fn rejects_overlapping_stays() {
+        let mut calendar = calendar();
+        calendar.reserve(TODAY + 3, 4).unwrap();
+
+        let unavailable = Err(RentalError::StayUnavailable.into());
+        assert_eq!(calendar.reserve(TODAY + 6, 2), unavailable);
+        assert_eq!(calendar.reserve(TODAY + 1, 3), unavailable);
+        // 退房當天可接續入住
+        calendar.reserve(TODAY + 7, 2).unwrap();
+        calendar.reserve(TODAY + 1, 2).unwrap();
+
+        let invalid = Err(RentalError::InvalidStayDates.into());
+        assert_eq!(calendar.reserve(TODAY - 1, 1), invalid);
+        assert_eq!(calendar.reserve(TODAY + CALENDAR_DAYS as u32 - 1, 2), invalid);
+
+        calendar.release(TODAY + 3, 4);
+        calendar.reserve(TODAY + 4, 2).unwrap();
+    }
+
+    #[test]
+    fn advances_calendar_window() {
+        let mut calendar = calendar();
+        calendar.reserve(TODAY + 1, 3).unwrap();
+        calendar.reserve(TODAY + 10, 1).unwrap();
+
+        calendar.advance(TODAY + 2);
+        assert_eq!(calendar.start_day, TODAY + 2);
+        assert_eq!(calendar.reserve(TODAY + 3, 1), Err(RentalError::StayUnavailable.into()));
+        calendar.reserve(TODAY + 4, 6).unwrap();
+
+        calendar.advance(TODAY + 11);
+        assert!(calendar.is_clear());
+        calendar.advance(TODAY + 5);
+        assert_eq!(calendar.start_day, TODAY + 11);
+    }
+}
@@ -0,0 +1,257 @@
+use anchor_lang::prelude::*;
+
+use crate::address::{
+    MAX_DISTRICT_NAME_LEN, MAX_NUMBER_LEN, MAX_POSTAL_CODE_LEN, MAX_STREET_LEN, MAX_UNIT_LEN,
+};
+use crate::amenities::{MAX_CUSTOM_TAGS, MAX_CUSTOM_TAG_LEN};
+use crate::metadata::{is_valid_uri, MAX_DESCRIPTION_LEN, MAX_METADATA_URI_LEN};
+use crate::{Address, Amenities, MetadataAnchor, RentalError};
+
+/// 房源標題長度上限 (bytes)，需與 `Listing.title` 的 `max_len` 一致
+pub const MAX_TITLE_LEN: usize = 100;
+/// 調整與延期原因長度上限 (bytes)
+pub const MAX_REASON_LEN: usize = 200;
+
+/// 指令字串參數驗證，於修改任何狀態前呼叫
+pub struct InputValidator;
+
+impl InputValidator {
+    /// 驗證房源標題與描述
+    pub fn validate_listing_text(title: &str, description: &str) -> Result<()> {
+        require!(!title.trim().is_empty(), RentalError::EmptyField);
+        require!(title.len() <= MAX_TITLE_LEN, RentalError::TitleTooLong);
+        Self::validate_description(description)
+    }
+
+    /// 驗證房源描述 (可留空)
+    pub fn validate_description(description: &str) -> Result<()> {
+        require!(description.len() <= MAX_DESCRIPTION_LEN, RentalError::DescriptionTooLong);
+        Ok(())
+    }
+
+    /// 驗證中繼資料 URI 長度
+    pub fn validate_metadata(metadata: Option<&MetadataAnchor>) -> Result<()> {
+        if let Some(metadata) = metadata {
+            Self::validate_uri(&metadata.uri)?;
+        }
+        Ok(())
+    }
+
+    /// 驗證 URI 長度
+    pub fn validate_uri(uri: &str) -> Result<()> {
+        require!(!uri.is_empty(), RentalError::EmptyField);
+        require!(uri.len() <= MAX_METADATA_URI_LEN, RentalError::MetadataUriTooLong);
+        Ok(())
+    }
+
+    /// 驗證房源文件的儲存 URI 與內容雜湊
+    pub fn validate_document(uri: &str, content_hash: &[u8; 32]) -> Result<()> {
+        Self::validate_uri(uri)?;
+        require!(
+            is_valid_uri(uri) && *content_hash != [0u8; 32],
+            RentalError::InvalidDocument
+        );
+        Ok(())
+    }
+
+    /// 驗證地址各欄位長度
+    pub fn validate_address(address: &Address) -> Result<()> {
+        require!(
+            !address.district_name.trim().is_empty()
+                && !address.street.trim().is_empty()
+                && !address.number.trim().is_empty()
+                && !address.postal_code.is_empty(),
+            RentalError::EmptyField
+        );
+        require!(
+            address.district_name.len() <= MAX_DISTRICT_NAME_LEN
+                && address.street.len() <= MAX_STREET_LEN
+                && address.number.len() <= MAX_NUMBER_LEN
+                && address.postal_code.len() <= MAX_POSTAL_CODE_LEN
+                && !matches!(&address.unit, Some(unit) if unit.len() > MAX_UNIT_LEN),
+            RentalError::AddressFieldTooLong
+        );
+        Ok(())
+    }
+
+    /// 驗證設施配備位元與自訂標籤
+    pub fn validate_amenities(amenities: &Amenities, custom_tags: &[String]) -> Result<()> {
+        require!(amenities.is_valid(), RentalError::InvalidAmenities);
+        require!(custom_tags.len() <= MAX_CUSTOM_TAGS, RentalError::TooManyAmenities);
+        for tag in custom_tags {
+            require!(!tag.trim().is_empty(), RentalError::EmptyField);
+            require!(tag.len() <= MAX_CUSTOM_TAG_LEN, RentalError::TagTooLong);
+        }
+        Ok(())
+    }
+
+    /// 驗證調整或延期原因
+    pub fn validate_reason(reason: &str) -> Result<()> {
+        require!(!reason.trim().is_empty(), RentalError::EmptyField);
+        require!(reason.len() <= MAX_REASON_LEN, RentalError::ReasonTooLong);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HashAlgorithm;
+
+    fn address() -> Address {
+        Address {
+            county_code: 63000,
+            district_code: 63000020,
+            district_name: "信義區".to_string(),
+            street: "信義路五段".to_string(),
+            number: "7號".to_string(),
+            floor: 12,
+            unit: None,
+            postal_code: "110".to_string(),
+        }
+    }
+
+    fn metadata_with_uri(uri: String) -> MetadataAnchor {
+        MetadataAnchor {
+            uri,
+            content_hash: [1u8; 32],
+            hash_algorithm: HashAlgorithm::Sha256,
+        }
+    }
+
+    #[test]
+    fn title_boundaries() {
+        let description = "";
+        assert!(InputValidator::validate_listing_text(&"a".repeat(MAX_TITLE_LEN), description).is_ok());
+        assert_eq!(
+            InputValidator::validate_listing_text(&"a".repeat(MAX_TITLE_LEN + 1), description),
+            Err(RentalError::TitleTooLong.into())
+        );
+        assert_eq!(
+            InputValidator::validate_listing_text("  ", description),
+            Err(RentalError::EmptyField.into())
+        );
+    }
+
+    #[test]
+    fn description_boundaries() {
+        assert!(InputValidator::validate_listing_text("套房", &"a".repeat(MAX_DESCRIPTION_LEN)).is_ok());
+        assert_eq!(
+            InputValidator::validate_listing_text("套房", &"a".repeat(MAX_DESCRIPTION_LEN + 1)),
+            Err(RentalError::DescriptionTooLong.into())
+        );
+        assert!(InputValidator::validate_description("").is_ok());
+    }
+
+    #[test]
+    fn metadata_uri_boundaries() {
+        assert!(InputValidator::validate_metadata(None).is_ok());
+        let uri = format!("ar://{}", "a".repeat(MAX_METADATA_URI_LEN - 5));
+        assert!(InputValidator::validate_metadata(Some(&metadata_with_uri(uri.clone()))).is_ok());
+        assert_eq!(
+            InputValidator::validate_metadata(Some(&metadata_with_uri(format!("{}a", uri)))),
+            Err(RentalError::MetadataUriTooLong.into())
+        );
+        assert_eq!(
+            InputValidator::validate_metadata(Some(&metadata_with_uri(String::new()))),
+            Err(RentalError::EmptyField.into())
+        );
+    }
+
+    #[test]
+    fn document_boundaries() {
+        let hash = [1u8; 32];
+        let uri = format!("ar://{}", "a".repeat(MAX_METADATA_URI_LEN - 5));
+        assert!(InputValidator::validate_document(&uri, &hash).is_ok());
+        assert_eq!(
+            InputValidator::validate_document(&format!("{}a", uri), &hash),
+            Err(RentalError::MetadataUriTooLong.into())
+        );
+        assert_eq!(InputValidator::validate_document("", &hash), Err(RentalError::EmptyField.into()));
+        assert_eq!(
+            InputValidator::validate_document("http://example.com/deed.pdf", &hash),
+            Err(RentalError::InvalidDocument.into())
+        );
+        assert_eq!(
+            InputValidator::validate_document("ipfs://bafy", &[0u8; 32]),
+            Err(RentalError::InvalidDocument.into())
+        );
+    }
+
+    #[test]
+    fn address_boundaries() {
+        assert!(InputValidator::validate_address(&address()).is_ok());
+
+        let mut valid = address();
+        valid.street = "a".repeat(MAX_STREET_LEN);
+        valid.number = "a".repeat(MAX_NUMBER_LEN);
+        valid.unit = Some("a".repeat(MAX_UNIT_LEN));
+        assert!(InputValidator::validate_address(&valid).is_ok());
+
+        let mut too_long = address();
+        too_long.street = "a".repeat(MAX_STREET_LEN + 1);
+        assert_eq!(
+            InputValidator::validate_address(&too_long),
+            Err(RentalError::AddressFieldTooLong.into())
+        );
+
+        let mut too_long = address();
+        too_long.unit = Some("a".repeat(MAX_UNIT_LEN + 1));
+        assert_eq!(
+            InputValidator::validate_address(&too_long),
+            Err(RentalError::AddressFieldTooLong.into())
+        );
+
+        let mut too_long = address();
+        too_long.postal_code = "1".repeat(MAX_POSTAL_CODE_LEN + 1);
+        assert_eq!(
+            InputValidator::validate_address(&too_long),
+            Err(RentalError::AddressFieldTooLong.into())
+        );
+
+        let mut empty = address();
+        empty.number = " ".to_string();
+        assert_eq!(InputValidator::validate_address(&empty), Err(RentalError::EmptyField.into()));
+    }
+
+    #[test]
+    fn amenities_boundaries() {
+        let amenities = Amenities { bits: Amenities::WASHER };
+        let tags = vec!["tag".to_string(); MAX_CUSTOM_TAGS];
+        assert!(InputValidator::validate_amenities(&amenities, &tags).is_ok());
+        assert!(InputValidator::validate_amenities(&amenities, &[]).is_ok());
+        assert_eq!(
+            InputValidator::validate_amenities(&amenities, &vec!["tag".to_string(); MAX_CUSTOM_TAGS + 1]),
+            Err(RentalError::TooManyAmenities.into())
+        );
+        assert_eq!(
+            InputValidator::validate_amenities(&Amenities { bits: 1 << 16 }, &[]),
+            Err(RentalError::InvalidAmenities.into())
+        );
+    }
+
+    #[test]
+    fn custom_tag_boundaries() {
+        let amenities = Amenities::default();
+        assert!(InputValidator::validate_amenities(&amenities, &["a".repeat(MAX_CUSTOM_TAG_LEN)]).is_ok());
+        // 6 個中文字為 18 bytes
+        assert_eq!(
+            InputValidator::validate_amenities(&amenities, &["近公園與夜市".to_string()]),
+            Err(RentalError::TagTooLong.into())
+        );
+        assert_eq!(
+            InputValidator::validate_amenities(&amenities, &[" ".to_string()]),
+            Err(RentalError::EmptyField.into())
+        );
+    }
+
+    #[test]
+    fn reason_boundaries() {
+        assert!(InputValidator::validate_reason(&"a".repeat(MAX_REASON_LEN)).is_ok());
+        assert_eq!(
+            InputValidator::validate_reason(&"a".repeat(MAX_REASON_LEN + 1)),
+            Err(RentalError::ReasonTooLong.into())
+        );
+        assert_eq!(InputValidator::validate_reason(""), Err(RentalError::EmptyField.into()));
+    }
+}
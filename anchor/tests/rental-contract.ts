@@ -3,6 +3,46 @@ import { Program } from "@coral-xyz/anchor";
 import { RentalContract } from "../target/types/rental_contract";
 import { PublicKey, Keypair, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
 import { expect } from "chai";
+import { createHash } from "crypto";
+
+// 設施配備位元，與合約 amenities 模組一致
+const AMENITY_AIR_CONDITIONING = 1 << 0;
+const AMENITY_WASHER = 1 << 1;
+const AMENITY_REFRIGERATOR = 1 << 3;
+const AMENITY_INTERNET = 1 << 5;
+const AMENITY_TV = 1 << 6;
+const AMENITY_ELEVATOR = 1 << 8;
+const LISTING_AMENITIES_OFFSET = 8 + 32 + 1 + 8;
+
+// geohash 編碼 (座標單位為微度)，與合約 geohash 模組一致
+const GEOHASH_BASE32 = "0123456789bcdefghjkmnpqrstuvwxyz";
+function encodeGeohash(latitude: number, longitude: number, precision: number): string {
+  const lat = [-90, 90];
+  const lon = [-180, 180];
+  let hash = "";
+  let bits = 0;
+  let value = 0;
+  let even = true;
+  while (hash.length < precision) {
+    const range = even ? lon : lat;
+    const coordinate = (even ? longitude : latitude) / 1_000_000;
+    const mid = (range[0] + range[1]) / 2;
+    value <<= 1;
+    if (coordinate >= mid) {
+      value |= 1;
+      range[0] = mid;
+    } else {
+      range[1] = mid;
+    }
+    even = !even;
+    if (++bits === 5) {
+      hash += GEOHASH_BASE32[value];
+      bits = 0;
+      value = 0;
+    }
+  }
+  return hash;
+}
 
 describe("rental-contract", () => {
   const provider = anchor.AnchorProvider.env();
@@ -23,6 +63,58 @@ describe("rental-contract", () => {
     program.programId
   );
 
+  // 台北市信義區行政區代碼
+  const regionCode = 63000020;
+
+  // 地區租金區間 PDA
+  const regionCodeBuffer = Buffer.alloc(4);
+  regionCodeBuffer.writeUInt32LE(regionCode);
+  const [priceBandPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("price_band"), regionCodeBuffer],
+    program.programId
+  );
+
+  // 可租房源索引 PDA (行政區 / 新台幣價格分桶)
+  const listingIndexPda = (kind: number, key: number, page: number) => {
+    const keyBuffer = Buffer.alloc(4);
+    keyBuffer.writeUInt32LE(key);
+    const pageBuffer = Buffer.alloc(2);
+    pageBuffer.writeUInt16LE(page);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("listing_index"), Buffer.from([kind]), keyBuffer, pageBuffer],
+      program.programId
+    )[0];
+  };
+  // 月租 24,000 元落在第 4 桶 (20,000 ~ 25,000)
+  const priceBucket = 4;
+  const districtIndexPda = listingIndexPda(0, regionCode, 0);
+  const priceIndexPda = listingIndexPda(1, priceBucket, 0);
+
+  // 房東身分檔案與認證機構 PDA
+  const [landlordProfilePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("landlord_profile"), landlordKeypair.publicKey.toBuffer()],
+    program.programId
+  );
+  const [tenantProfilePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("tenant_profile"), tenantKeypair.publicKey.toBuffer()],
+    program.programId
+  );
+  const [attesterPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("attester"), provider.wallet.publicKey.toBuffer()],
+    program.programId
+  );
+
+  // 租約文件 PDA (房源、租客、版本)
+  const leasePda = (listing: PublicKey, tenant: PublicKey, version: number) => {
+    const versionBuffer = Buffer.alloc(2);
+    versionBuffer.writeUInt16LE(version);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("lease"), listing.toBuffer(), tenant.toBuffer(), versionBuffer],
+      program.programId
+    )[0];
+  };
+  const leaseHash = createHash("sha256").update("台北市大安區住宅租賃契約 v1").digest();
+
   before(async () => {
     // 為測試帳戶提供 SOL
     const signature1 = await provider.connection.requestAirdrop(
@@ -62,6 +154,192 @@ describe("rental-contract", () => {
     }
   });
 
+  it("設定價格預言機", async () => {
+    try {
+      // Pyth SOL/USD 與 USD/TWD 價格帳戶
+      const solUsdPriceFeed = new PublicKey("H6ARHf6YXhGYeQfUzQNGk6rDNnLBQKrenN712K4AQJEG");
+      const usdTwdPriceFeed = Keypair.generate().publicKey;
+
+      await program.methods
+        .configurePriceFeeds(solUsdPriceFeed, usdTwdPriceFeed, new anchor.BN(60), 200)
+        .accounts({
+          platform: platformPda,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const platformAccount = await program.account.platform.fetch(platformPda);
+      expect(platformAccount.solUsdPriceFeed.toString()).to.equal(solUsdPriceFeed.toString());
+      expect(platformAccount.usdTwdPriceFeed.toString()).to.equal(usdTwdPriceFeed.toString());
+      expect(platformAccount.maxPriceAge.toNumber()).to.equal(60);
+      expect(platformAccount.maxPriceConfBps).to.equal(200);
+
+      // 非平台管理員不可修改設定
+      try {
+        await program.methods
+          .configurePriceFeeds(solUsdPriceFeed, usdTwdPriceFeed, new anchor.BN(3600), 10_000)
+          .accounts({
+            platform: platformPda,
+            authority: landlordKeypair.publicKey,
+          })
+          .signers([landlordKeypair])
+          .rpc();
+        expect.fail("非管理員不應能設定價格預言機");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("Unauthorized");
+      }
+
+      // 報價時效須大於 0 秒，信賴區間上限不得超過 10,000 基點
+      for (const [maxAge, maxConfBps] of [[0, 200], [60, 10_001]]) {
+        try {
+          await program.methods
+            .configurePriceFeeds(solUsdPriceFeed, usdTwdPriceFeed, new anchor.BN(maxAge), maxConfBps)
+            .accounts({
+              platform: platformPda,
+              authority: provider.wallet.publicKey,
+            })
+            .rpc();
+          expect.fail("應拒絕無效的報價限制");
+        } catch (error) {
+          expect(error.error.errorCode.code).to.equal("InvalidPriceFeedConfig");
+        }
+      }
+
+      console.log("✅ 價格預言機設定成功");
+    } catch (error) {
+      console.error("❌ 價格預言機設定失敗:", error);
+      throw error;
+    }
+  });
+
+  it("建立地區租金區間", async () => {
+    try {
+      // 每坪月租 500 ~ 3,000 元
+      await program.methods
+        .createPriceBand(regionCode, { twd: {} }, new anchor.BN(50_000), new anchor.BN(300_000))
+        .accounts({
+          priceBand: priceBandPda,
+          platform: platformPda,
+          verifier: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const priceBandAccount = await program.account.priceBand.fetch(priceBandPda);
+      expect(priceBandAccount.regionCode).to.equal(regionCode);
+      expect(priceBandAccount.minPricePerPing.toNumber()).to.equal(50_000);
+      expect(priceBandAccount.maxPricePerPing.toNumber()).to.equal(300_000);
+
+      // 非驗證者不可修改租金區間
+      try {
+        await program.methods
+          .updatePriceBand({ twd: {} }, new anchor.BN(0), new anchor.BN(100_000_000))
+          .accounts({
+            priceBand: priceBandPda,
+            platform: platformPda,
+            verifier: landlordKeypair.publicKey,
+          })
+          .signers([landlordKeypair])
+          .rpc();
+        expect.fail("非驗證者不應能修改租金區間");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("Unauthorized");
+      }
+
+      console.log("✅ 地區租金區間建立成功");
+    } catch (error) {
+      console.error("❌ 地區租金區間建立失敗:", error);
+      throw error;
+    }
+  });
+
+  it("建立房源索引", async () => {
+    try {
+      await program.methods
+        .createListingIndex({ district: {} }, regionCode, 0)
+        .accounts({
+          listingIndex: districtIndexPda,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .createListingIndex({ priceBucket: {} }, priceBucket, 0)
+        .accounts({
+          listingIndex: priceIndexPda,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const districtIndex = await program.account.listingIndex.fetch(districtIndexPda);
+      expect(districtIndex.key).to.equal(regionCode);
+      expect(districtIndex.listings).to.be.empty;
+
+      console.log("✅ 房源索引建立成功");
+    } catch (error) {
+      console.error("❌ 房源索引建立失敗:", error);
+      throw error;
+    }
+  });
+
+  it("房東 KYC 認證", async () => {
+    try {
+      await program.methods
+        .addAttester(provider.wallet.publicKey)
+        .accounts({
+          attester: attesterPda,
+          platform: platformPda,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .createLandlordProfile()
+        .accounts({
+          profile: landlordProfilePda,
+          wallet: landlordKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 365 * 24 * 60 * 60);
+      const evidenceHash = Array.from(createHash("sha256").update("身分證明文件").digest());
+      await program.methods
+        .attestLandlord(2, expiresAt, evidenceHash)
+        .accounts({
+          profile: landlordProfilePda,
+          attester: attesterPda,
+          issuer: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      // 要求房東通過 KYC 認證後才能上架
+      await program.methods
+        .configureKycPolicy(true, false, 1)
+        .accounts({
+          platform: platformPda,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const profile = await program.account.landlordProfile.fetch(landlordProfilePda);
+      expect(profile.attestation.level).to.equal(2);
+      expect(profile.attestation.issuer.toString()).to.equal(provider.wallet.publicKey.toString());
+
+      const platformAccount = await program.account.platform.fetch(platformPda);
+      expect(platformAccount.kycPolicy.requireLandlord).to.be.true;
+
+      console.log("✅ 房東 KYC 認證成功");
+    } catch (error) {
+      console.error("❌ 房東 KYC 認證失敗:", error);
+      throw error;
+    }
+  });
+
   it("上架房源", async () => {
     try {
       const [listingPda] = PublicKey.findProgramAddressSync(
@@ -75,37 +353,70 @@ describe("rental-contract", () => {
 
       const title = "台北信義區精緻套房";
       const description = "位於信義區精華地段，近捷運站，生活機能便利";
-      const location = "台北市信義區信義路五段";
+      const address = {
+        countyCode: 63000, // 臺北市
+        districtCode: regionCode,
+        districtName: "信義區",
+        street: "信義路五段",
+        number: "7號",
+        floor: 12,
+        unit: null,
+        postalCode: "110",
+      };
       const price = new anchor.BN(25 * LAMPORTS_PER_SOL); // 25 SOL
       const deposit = new anchor.BN(50 * LAMPORTS_PER_SOL); // 50 SOL
       const size = 15;
       const rooms = 1;
       const bathrooms = 1;
-      const floor = 12;
       const totalFloors = 20;
       const contractLength = 12;
-      const moveInDate = new anchor.BN(Math.floor(Date.now() / 1000) + 30 * 24 * 60 * 60); // 30天後
-      const amenities = ["冷氣", "冰箱", "洗衣機", "網路", "電視"];
+      const moveInDate = new anchor.BN(Math.floor(Date.now() / 1000)); // 可立即入住 (租約立即生效)
+      const amenities = {
+        bits: AMENITY_AIR_CONDITIONING | AMENITY_REFRIGERATOR | AMENITY_WASHER | AMENITY_INTERNET | AMENITY_TV,
+      };
+      const customTags = ["近信義商圈"];
+      const fiatPrice = { amount: new anchor.BN(2_400_000), currency: { twd: {} } }; // 月租 24,000 元
+      const coordinates = { latitude: 25_033_964, longitude: 121_564_472 };
+
+      const [geoIndexPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("geo_index"),
+          Buffer.from(encodeGeohash(coordinates.latitude, coordinates.longitude, 5)),
+          listingPda.toBuffer()
+        ],
+        program.programId
+      );
 
       await program.methods
         .createListing(
           title,
           description,
-          location,
+          null, // 不使用鏈下中繼資料
+          address,
+          coordinates,
+          { sol: {} },
           price,
           deposit,
           size,
           rooms,
           bathrooms,
-          floor,
           totalFloors,
           contractLength,
           moveInDate,
-          amenities
+          amenities,
+          customTags,
+          fiatPrice
         )
         .accounts({
           listing: listingPda,
           platform: platformPda,
+          priceBand: priceBandPda,
+          geoIndex: geoIndexPda,
+          districtIndex: districtIndexPda,
+          priceIndex: priceIndexPda,
+          solUsdPriceFeed: null,
+          usdTwdPriceFeed: null,
+          landlordProfile: landlordProfilePda,
           authority: landlordKeypair.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -114,6 +425,33 @@ describe("rental-contract", () => {
 
       const listingAccount = await program.account.listing.fetch(listingPda);
       expect(listingAccount.title).to.equal(title);
+      expect(listingAccount.location).to.equal("110 臺北市信義區信義路五段7號12樓");
+      expect(listingAccount.address.districtCode).to.equal(regionCode);
+      expect(listingAccount.floor).to.equal(12);
+      expect(Buffer.from(listingAccount.geohash).toString()).to.equal("wsqqqm2");
+      expect(listingAccount.amenities.bits).to.equal(amenities.bits);
+      expect(listingAccount.customTags).to.deep.equal(customTags);
+
+      // 以設施配備位元組合查詢房源
+      const amenitiesBytes = Buffer.alloc(4);
+      amenitiesBytes.writeUInt32LE(amenities.bits);
+      const withAmenities = await program.account.listing.all([
+        { memcmp: { offset: LISTING_AMENITIES_OFFSET, bytes: anchor.utils.bytes.bs58.encode(amenitiesBytes) } },
+      ]);
+      expect(withAmenities.map((listing) => listing.publicKey.toString())).to.include(listingPda.toString());
+
+      // 以 geohash 前綴查詢同一格網內的房源
+      const nearby = await program.account.geoIndex.all([
+        { memcmp: { offset: 8, bytes: anchor.utils.bytes.bs58.encode(Buffer.from("wsqqq")) } },
+      ]);
+      expect(nearby.map((index) => index.account.listing.toString())).to.include(listingPda.toString());
+
+      // 可租房源索引
+      expect(listingAccount.priceBucket).to.equal(priceBucket);
+      const districtIndex = await program.account.listingIndex.fetch(districtIndexPda);
+      expect(districtIndex.listings.map((key) => key.toString())).to.include(listingPda.toString());
+      const priceIndex = await program.account.listingIndex.fetch(priceIndexPda);
+      expect(priceIndex.listings.map((key) => key.toString())).to.include(listingPda.toString());
       expect(listingAccount.price.toString()).to.equal(price.toString());
       expect(listingAccount.isAvailable).to.be.true;
       expect(listingAccount.isVerified).to.be.false;
@@ -125,6 +463,211 @@ describe("rental-contract", () => {
     }
   });
 
+  it("建立多單位建物", async () => {
+    try {
+      const propertyId = new anchor.BN(1);
+      const [propertyPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("property"), landlordKeypair.publicKey.toBuffer(), propertyId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      const address = {
+        countyCode: 63000, // 臺北市
+        districtCode: regionCode,
+        districtName: "信義區",
+        street: "信義路五段",
+        number: "7號",
+        floor: 12, // 建物地址不含樓層，建立時忽略
+        unit: null,
+        postalCode: "110",
+      };
+      const sharedAmenities = { bits: AMENITY_ELEVATOR };
+
+      await program.methods
+        .createProperty(
+          propertyId,
+          "信義路五段大樓",
+          address,
+          { latitude: 25_033_964, longitude: 121_564_472 },
+          20,
+          sharedAmenities
+        )
+        .accounts({
+          property: propertyPda,
+          authority: landlordKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      let property = await program.account.property.fetch(propertyPda);
+      expect(property.address.floor).to.equal(0);
+      expect(property.totalFloors).to.equal(20);
+      expect(property.totalUnits).to.equal(0);
+
+      // 總樓層須大於 0 且不低於已上架單位的最高樓層
+      try {
+        await program.methods
+          .updateProperty("信義路五段大樓", 0, sharedAmenities)
+          .accounts({
+            property: propertyPda,
+            authority: landlordKeypair.publicKey,
+          })
+          .signers([landlordKeypair])
+          .rpc();
+        expect.fail("應拒絕無效的總樓層");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("InvalidUnitFloor");
+      }
+
+      await program.methods
+        .updateProperty("信義路五段商辦住宅", 21, sharedAmenities)
+        .accounts({
+          property: propertyPda,
+          authority: landlordKeypair.publicKey,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      property = await program.account.property.fetch(propertyPda);
+      expect(property.name).to.equal("信義路五段商辦住宅");
+      expect(property.totalFloors).to.equal(21);
+
+      console.log("✅ 多單位建物建立成功");
+    } catch (error) {
+      console.error("❌ 多單位建物建立失敗:", error);
+      throw error;
+    }
+  });
+
+  it("切換鏈下中繼資料", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+
+      const document = JSON.stringify({
+        version: 1,
+        title: "台北信義區精緻套房",
+        description: "位於信義區精華地段，近捷運站，生活機能便利",
+        photos: [{ uri: "ipfs://bafybeiphoto", content_type: "image/jpeg" }],
+        floor_plan: null,
+        amenities: ["冷氣", "冰箱", "洗衣機", "網路", "電視"],
+      });
+      const metadata = {
+        uri: "ipfs://bafybeimetadata",
+        contentHash: Array.from(createHash("sha256").update(document).digest()),
+        hashAlgorithm: { sha256: {} },
+      };
+
+      const before = await provider.connection.getAccountInfo(listingPda);
+
+      // 中繼資料模式下鏈上描述須留空
+      try {
+        await program.methods
+          .updateListingMetadata("仍保留描述", metadata)
+          .accounts({
+            listing: listingPda,
+            authority: landlordKeypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([landlordKeypair])
+          .rpc();
+        expect.fail("中繼資料模式不應保留鏈上描述");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("InvalidMetadata");
+      }
+
+      await program.methods
+        .updateListingMetadata("", metadata)
+        .accounts({
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      const listingAccount = await program.account.listing.fetch(listingPda);
+      expect(listingAccount.description).to.equal("");
+      expect(listingAccount.metadata.uri).to.equal(metadata.uri);
+      expect(Buffer.from(listingAccount.metadata.contentHash)).to.deep.equal(
+        createHash("sha256").update(document).digest()
+      );
+
+      const after = await provider.connection.getAccountInfo(listingPda);
+      expect(after.data.length).to.be.lessThan(before.data.length);
+
+      console.log("✅ 鏈下中繼資料設定成功");
+    } catch (error) {
+      console.error("❌ 鏈下中繼資料設定失敗:", error);
+      throw error;
+    }
+  });
+
+  it("雙方簽署租約", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+      const lease = leasePda(listingPda, tenantKeypair.publicKey, 1);
+
+      await program.methods
+        .proposeLease(tenantKeypair.publicKey, [...leaseHash], 1)
+        .accounts({
+          lease,
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      // 租客簽署的文件雜湊須與房東提出的一致
+      try {
+        await program.methods
+          .signLease([...Buffer.alloc(32, 1)], 1)
+          .accounts({
+            lease,
+            tenant: tenantKeypair.publicKey,
+          })
+          .signers([tenantKeypair])
+          .rpc();
+        expect.fail("應拒絕不符的租約文件");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("LeaseMismatch");
+      }
+
+      await program.methods
+        .signLease([...leaseHash], 1)
+        .accounts({
+          lease,
+          tenant: tenantKeypair.publicKey,
+        })
+        .signers([tenantKeypair])
+        .rpc();
+
+      const leaseAccount = await program.account.leaseAgreement.fetch(lease);
+      expect(leaseAccount.landlordSignedAt.toNumber()).to.be.greaterThan(0);
+      expect(leaseAccount.tenantSignedAt.toNumber()).to.be.greaterThan(0);
+
+      console.log("✅ 租約簽署成功");
+    } catch (error) {
+      console.error("❌ 租約簽署失敗:", error);
+      throw error;
+    }
+  });
+
   it("租用房源", async () => {
     try {
       const [listingPda] = PublicKey.findProgramAddressSync(
@@ -147,12 +690,31 @@ describe("rental-contract", () => {
 
       const rentalId = new anchor.BN(1);
 
+      // 租客需先建立身分檔案以累積信用紀錄
+      await program.methods
+        .createTenantProfile()
+        .accounts({
+          profile: tenantProfilePda,
+          wallet: tenantKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([tenantKeypair])
+        .rpc();
+
+      const tenantProfile = await program.account.tenantProfile.fetch(tenantProfilePda);
+      expect(tenantProfile.reputationScore).to.equal(500);
+
       await program.methods
-        .rentProperty(rentalId)
+        .rentProperty(rentalId, null, null)
         .accounts({
           rental: rentalPda,
           listing: listingPda,
           platform: platformPda,
+          districtIndex: districtIndexPda,
+          priceIndex: priceIndexPda,
+          landlordProfile: landlordProfilePda,
+          tenantProfile: tenantProfilePda,
+          lease: leasePda(listingPda, tenantKeypair.publicKey, 1),
           landlord: landlordKeypair.publicKey,
           tenant: tenantKeypair.publicKey,
           tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
@@ -165,10 +727,16 @@ describe("rental-contract", () => {
       expect(rentalAccount.rentalId.toString()).to.equal(rentalId.toString());
       expect(rentalAccount.landlord.toString()).to.equal(landlordKeypair.publicKey.toString());
       expect(rentalAccount.tenant.toString()).to.equal(tenantKeypair.publicKey.toString());
+      expect(Buffer.from(rentalAccount.leaseDocumentHash)).to.deep.equal(leaseHash);
+      expect(rentalAccount.leaseVersion).to.equal(1);
 
       const listingAccount = await program.account.listing.fetch(listingPda);
       expect(listingAccount.isAvailable).to.be.false;
 
+      // 已出租房源自索引移除
+      const districtIndex = await program.account.listingIndex.fetch(districtIndexPda);
+      expect(districtIndex.listings.map((key) => key.toString())).to.not.include(listingPda.toString());
+
       console.log("✅ 房源租用成功");
     } catch (error) {
       console.error("❌ 房源租用失敗:", error);
@@ -207,7 +775,7 @@ describe("rental-contract", () => {
     }
   });
 
-  it("驗證房源", async () => {
+  it("設定合租租客", async () => {
     try {
       const [listingPda] = PublicKey.findProgramAddressSync(
         [
@@ -217,27 +785,92 @@ describe("rental-contract", () => {
         ],
         program.programId
       );
+      const [rentalPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("rental"), listingPda.toBuffer(), tenantKeypair.publicKey.toBuffer()],
+        program.programId
+      );
+      const roommateKeypair = Keypair.generate();
+
+      // 分攤比例總和須為 10,000 基點
+      try {
+        await program.methods
+          .configureCoTenants([
+            { tenant: tenantKeypair.publicKey, shareBps: 6000 },
+            { tenant: roommateKeypair.publicKey, shareBps: 3000 },
+          ])
+          .accounts({
+            rental: rentalPda,
+            authority: landlordKeypair.publicKey,
+            tenant: tenantKeypair.publicKey,
+          })
+          .remainingAccounts([{ pubkey: roommateKeypair.publicKey, isWritable: false, isSigner: true }])
+          .signers([landlordKeypair, tenantKeypair, roommateKeypair])
+          .rpc();
+        expect.fail("應拒絕總和不為 10,000 的分攤比例");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("InvalidRentShares");
+      }
 
       await program.methods
-        .verifyListing()
+        .configureCoTenants([
+          { tenant: tenantKeypair.publicKey, shareBps: 6000 },
+          { tenant: roommateKeypair.publicKey, shareBps: 4000 },
+        ])
         .accounts({
-          listing: listingPda,
-          platform: platformPda,
-          authority: provider.wallet.publicKey,
+          rental: rentalPda,
+          authority: landlordKeypair.publicKey,
+          tenant: tenantKeypair.publicKey,
         })
+        .remainingAccounts([{ pubkey: roommateKeypair.publicKey, isWritable: false, isSigner: true }])
+        .signers([landlordKeypair, tenantKeypair, roommateKeypair])
         .rpc();
 
-      const listingAccount = await program.account.listing.fetch(listingPda);
-      expect(listingAccount.isVerified).to.be.true;
+      let rentalAccount = await program.account.rental.fetch(rentalPda);
+      expect(rentalAccount.coTenants.length).to.equal(2);
+      expect(rentalAccount.coTenants[1].shareBps).to.equal(4000);
 
-      console.log("✅ 房源驗證成功");
+      // 調整既有合租租客的分攤比例須經其簽署
+      try {
+        await program.methods
+          .configureCoTenants([
+            { tenant: tenantKeypair.publicKey, shareBps: 8000 },
+            { tenant: roommateKeypair.publicKey, shareBps: 2000 },
+          ])
+          .accounts({
+            rental: rentalPda,
+            authority: landlordKeypair.publicKey,
+            tenant: tenantKeypair.publicKey,
+          })
+          .signers([landlordKeypair, tenantKeypair])
+          .rpc();
+        expect.fail("未經合租租客簽署不應變更其分攤比例");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("Unauthorized");
+      }
+
+      // 恢復為單一租客 (被移除的合租租客須簽署)
+      await program.methods
+        .configureCoTenants([])
+        .accounts({
+          rental: rentalPda,
+          authority: landlordKeypair.publicKey,
+          tenant: tenantKeypair.publicKey,
+        })
+        .remainingAccounts([{ pubkey: roommateKeypair.publicKey, isWritable: false, isSigner: true }])
+        .signers([landlordKeypair, tenantKeypair, roommateKeypair])
+        .rpc();
+
+      rentalAccount = await program.account.rental.fetch(rentalPda);
+      expect(rentalAccount.coTenants.length).to.equal(0);
+
+      console.log("✅ 合租設定成功");
     } catch (error) {
-      console.error("❌ 房源驗證失敗:", error);
+      console.error("❌ 合租設定失敗:", error);
       throw error;
     }
   });
 
-  it("終止租約", async () => {
+  it("設定租金調漲", async () => {
     try {
       const [listingPda] = PublicKey.findProgramAddressSync(
         [
@@ -247,39 +880,1244 @@ describe("rental-contract", () => {
         ],
         program.programId
       );
-
       const [rentalPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("rental"),
-          listingPda.toBuffer(),
-          tenantKeypair.publicKey.toBuffer()
-        ],
+        [Buffer.from("rental"), listingPda.toBuffer(), tenantKeypair.publicKey.toBuffer()],
         program.programId
       );
 
+      // 調漲比例須介於 1 ~ 2,000 基點
+      try {
+        await program.methods
+          .setRentEscalation({ percent: { bps: 2500 } }, 12)
+          .accounts({
+            rental: rentalPda,
+            authority: landlordKeypair.publicKey,
+            tenant: tenantKeypair.publicKey,
+          })
+          .signers([landlordKeypair, tenantKeypair])
+          .rpc();
+        expect.fail("應拒絕超過上限的調漲比例");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("InvalidEscalation");
+      }
+
+      // 每 12 個月調漲 3%，由雙方共同簽署
       await program.methods
-        .terminateRental()
+        .setRentEscalation({ percent: { bps: 300 } }, 12)
         .accounts({
           rental: rentalPda,
-          listing: listingPda,
-          landlord: landlordKeypair.publicKey,
+          authority: landlordKeypair.publicKey,
           tenant: tenantKeypair.publicKey,
-          authority: tenantKeypair.publicKey,
-          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlordKeypair, tenantKeypair])
+        .rpc();
+
+      let rentalAccount = await program.account.rental.fetch(rentalPda);
+      expect(rentalAccount.escalation.kind.percent.bps).to.equal(300);
+      expect(rentalAccount.escalation.intervalMonths).to.equal(12);
+      expect(rentalAccount.escalation.tier).to.equal(0);
+
+      // 取消調漲條款
+      await program.methods
+        .setRentEscalation(null, 0)
+        .accounts({
+          rental: rentalPda,
+          authority: landlordKeypair.publicKey,
+          tenant: tenantKeypair.publicKey,
+        })
+        .signers([landlordKeypair, tenantKeypair])
+        .rpc();
+
+      rentalAccount = await program.account.rental.fetch(rentalPda);
+      expect(rentalAccount.escalation).to.be.null;
+
+      console.log("✅ 租金調漲設定成功");
+    } catch (error) {
+      console.error("❌ 租金調漲設定失敗:", error);
+      throw error;
+    }
+  });
+
+  it("帳單開立與繳費", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+      const [rentalPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("rental"), listingPda.toBuffer(), tenantKeypair.publicKey.toBuffer()],
+        program.programId
+      );
+      const billPda = (billId: number) => {
+        const id = Buffer.alloc(4);
+        id.writeUInt32LE(billId);
+        return PublicKey.findProgramAddressSync(
+          [Buffer.from("bill"), rentalPda.toBuffer(), id],
+          program.programId
+        )[0];
+      };
+      const dueDate = new anchor.BN(Math.floor(Date.now() / 1000) + 14 * 24 * 60 * 60);
+      const meterHash = Array.from(createHash("sha256").update("電表讀數 1234 度").digest());
+
+      // 開立電費帳單
+      await program.methods
+        .issueBill({ electricity: {} }, new anchor.BN(0.05 * LAMPORTS_PER_SOL), dueDate, meterHash)
+        .accounts({
+          bill: billPda(0),
+          rental: rentalPda,
+          authority: landlordKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      // 租客提出爭議後仍可繳納
+      await program.methods
+        .disputeBill()
+        .accounts({ bill: billPda(0), rental: rentalPda, tenant: tenantKeypair.publicKey })
+        .signers([tenantKeypair])
+        .rpc();
+      let bill = await program.account.bill.fetch(billPda(0));
+      expect(bill.status).to.deep.equal({ disputed: {} });
+
+      const landlordBalance = await provider.connection.getBalance(landlordKeypair.publicKey);
+      await program.methods
+        .payBill()
+        .accounts({
+          bill: billPda(0),
+          rental: rentalPda,
+          landlord: landlordKeypair.publicKey,
+          tenant: tenantKeypair.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
         })
         .signers([tenantKeypair])
         .rpc();
+      bill = await program.account.bill.fetch(billPda(0));
+      expect(bill.status).to.deep.equal({ paid: {} });
+      expect(await provider.connection.getBalance(landlordKeypair.publicKey)).to.equal(
+        landlordBalance + 0.05 * LAMPORTS_PER_SOL
+      );
+
+      // 已繳納的帳單不可重複繳納
+      try {
+        await program.methods
+          .payBill()
+          .accounts({
+            bill: billPda(0),
+            rental: rentalPda,
+            landlord: landlordKeypair.publicKey,
+            tenant: tenantKeypair.publicKey,
+            tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([tenantKeypair])
+          .rpc();
+        expect.fail("不應重複繳納帳單");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("InvalidBillStatus");
+      }
+
+      // 開立管理費帳單後由房東免除
+      await program.methods
+        .issueBill({ managementFee: {} }, new anchor.BN(0.02 * LAMPORTS_PER_SOL), dueDate, meterHash)
+        .accounts({
+          bill: billPda(1),
+          rental: rentalPda,
+          authority: landlordKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+      await program.methods
+        .waiveBill()
+        .accounts({ bill: billPda(1), rental: rentalPda, authority: landlordKeypair.publicKey })
+        .signers([landlordKeypair])
+        .rpc();
+      bill = await program.account.bill.fetch(billPda(1));
+      expect(bill.status).to.deep.equal({ waived: {} });
 
       const rentalAccount = await program.account.rental.fetch(rentalPda);
-      expect(rentalAccount.status).to.equal(2); // Terminated
+      expect(rentalAccount.billCount).to.equal(2);
+
+      console.log("✅ 帳單開立與繳費成功");
+    } catch (error) {
+      console.error("❌ 帳單開立與繳費失敗:", error);
+      throw error;
+    }
+  });
+
+  it("報修與修繕進度", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+      const [rentalPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("rental"), listingPda.toBuffer(), tenantKeypair.publicKey.toBuffer()],
+        program.programId
+      );
+      const ticketId = Buffer.alloc(4);
+      ticketId.writeUInt32LE(0);
+      const [ticketPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("maintenance"), rentalPda.toBuffer(), ticketId],
+        program.programId
+      );
 
+      // 房源設定報修逾期減免 (僅適用之後建立的租約)
+      await program.methods
+        .setMaintenanceAbatement(2500)
+        .accounts({ listing: listingPda, authority: landlordKeypair.publicKey })
+        .signers([landlordKeypair])
+        .rpc();
       const listingAccount = await program.account.listing.fetch(listingPda);
-      expect(listingAccount.isAvailable).to.be.true;
+      expect(listingAccount.maintenanceAbatementBps).to.equal(2500);
 
-      console.log("✅ 租約終止成功");
+      // 租客開立報修單
+      const descriptionHash = Array.from(createHash("sha256").update("浴室水管漏水").digest());
+      await program.methods
+        .openMaintenanceTicket({ plumbing: {} }, { high: {} }, descriptionHash)
+        .accounts({
+          ticket: ticketPda,
+          rental: rentalPda,
+          tenant: tenantKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([tenantKeypair])
+        .rpc();
+
+      const updateTicket = async (status: object, scheduledFor: number) => {
+        await program.methods
+          .updateMaintenanceTicket(status as any, new anchor.BN(scheduledFor))
+          .accounts({ ticket: ticketPda, rental: rentalPda, authority: landlordKeypair.publicKey })
+          .signers([landlordKeypair])
+          .rpc();
+      };
+      const confirmTicket = async (accepted: boolean) => {
+        await program.methods
+          .confirmMaintenanceTicket(accepted)
+          .accounts({ ticket: ticketPda, rental: rentalPda, tenant: tenantKeypair.publicKey })
+          .signers([tenantKeypair])
+          .rpc();
+      };
+
+      await updateTicket({ acknowledged: {} }, 0);
+      await updateTicket({ scheduled: {} }, Math.floor(Date.now() / 1000) + 24 * 60 * 60);
+      await updateTicket({ resolved: {} }, 0);
+
+      // 租客不接受修繕結果時退回受理狀態
+      await confirmTicket(false);
+      let ticket = await program.account.maintenanceTicket.fetch(ticketPda);
+      expect(ticket.status).to.deep.equal({ acknowledged: {} });
+
+      await updateTicket({ resolved: {} }, 0);
+      await confirmTicket(true);
+      ticket = await program.account.maintenanceTicket.fetch(ticketPda);
+      expect(ticket.status).to.deep.equal({ confirmed: {} });
+
+      // 此租約建立時未設定減免，不可申請
+      try {
+        await program.methods
+          .claimMaintenanceAbatement()
+          .accounts({ ticket: ticketPda, rental: rentalPda })
+          .rpc();
+        expect.fail("未設定減免的租約不應能申請減免");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("InvalidAbatement");
+      }
+
+      const rentalAccount = await program.account.rental.fetch(rentalPda);
+      expect(rentalAccount.ticketCount).to.equal(1);
+
+      console.log("✅ 報修流程成功");
     } catch (error) {
-      console.error("❌ 租約終止失敗:", error);
+      console.error("❌ 報修流程失敗:", error);
+      throw error;
+    }
+  });
+
+  it("新增與移除房源文件", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+
+      const documentPda = (contentHash: Buffer) =>
+        PublicKey.findProgramAddressSync(
+          [Buffer.from("listing_document"), listingPda.toBuffer(), contentHash],
+          program.programId
+        )[0];
+
+      const certificateHash = createHash("sha256").update("權狀影本").digest();
+      await program.methods
+        .addListingDocument(
+          Array.from(certificateHash),
+          { ownershipCertificate: {} },
+          "ipfs://bafybeicertificate",
+          { sha256: {} }
+        )
+        .accounts({
+          document: documentPda(certificateHash),
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      // 非房東不可新增文件
+      const photoHash = createHash("sha256").update("客廳照片").digest();
+      try {
+        await program.methods
+          .addListingDocument(Array.from(photoHash), { photo: {} }, "ar://living-room", { sha256: {} })
+          .accounts({
+            document: documentPda(photoHash),
+            listing: listingPda,
+            authority: tenantKeypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([tenantKeypair])
+          .rpc();
+        expect.fail("非房東不應能新增房源文件");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("Unauthorized");
+      }
+
+      await program.methods
+        .addListingDocument(Array.from(photoHash), { photo: {} }, "ar://living-room", { sha256: {} })
+        .accounts({
+          document: documentPda(photoHash),
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      // 以 memcmp 查詢房源的所有文件
+      const documents = await program.account.listingDocument.all([
+        { memcmp: { offset: 8, bytes: listingPda.toBase58() } },
+      ]);
+      expect(documents).to.have.lengthOf(2);
+
+      await program.methods
+        .removeListingDocument()
+        .accounts({
+          document: documentPda(photoHash),
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      const removed = await provider.connection.getAccountInfo(documentPda(photoHash));
+      expect(removed).to.be.null;
+
+      console.log("✅ 房源文件管理成功");
+    } catch (error) {
+      console.error("❌ 房源文件管理失敗:", error);
+      throw error;
+    }
+  });
+
+  it("委託代管", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+      const [rentalPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("rental"),
+          listingPda.toBuffer(),
+          tenantKeypair.publicKey.toBuffer()
+        ],
+        program.programId
+      );
+
+      const managerKeypair = Keypair.generate();
+      const [delegationPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("delegation"), listingPda.toBuffer(), managerKeypair.publicKey.toBuffer()],
+        program.programId
+      );
+
+      // 僅授權更新房源 (權限位元 1)，30 天後到期
+      const expiresAt = Math.floor(Date.now() / 1000) + 30 * 24 * 60 * 60;
+      await program.methods
+        .grantDelegation(managerKeypair.publicKey, 1, 0, new anchor.BN(expiresAt))
+        .accounts({
+          delegation: delegationPda,
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      const delegation = await program.account.delegation.fetch(delegationPda);
+      expect(delegation.manager.toString()).to.equal(managerKeypair.publicKey.toString());
+      expect(delegation.permissions).to.equal(1);
+
+      // 代管人可更新房源條件
+      await program.methods
+        .setTenantRequirements(0, false)
+        .accounts({
+          listing: listingPda,
+          delegation: delegationPda,
+          authority: managerKeypair.publicKey,
+        })
+        .signers([managerKeypair])
+        .rpc();
+
+      // 未授權管理租約，不可調整租約
+      try {
+        await program.methods
+          .adjustRental(new anchor.BN(1), new anchor.BN(expiresAt), "代管人調整租金")
+          .accounts({
+            rental: rentalPda,
+            delegation: delegationPda,
+            authority: managerKeypair.publicKey,
+          })
+          .signers([managerKeypair])
+          .rpc();
+        expect.fail("未授權的代管人不應能調整租約");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("PermissionDenied");
+      }
+
+      // 撤銷後代管人即失去權限
+      await program.methods
+        .revokeDelegation()
+        .accounts({
+          delegation: delegationPda,
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      try {
+        await program.methods
+          .setTenantRequirements(0, false)
+          .accounts({
+            listing: listingPda,
+            authority: managerKeypair.publicKey,
+          })
+          .signers([managerKeypair])
+          .rpc();
+        expect.fail("撤銷後代管人不應能更新房源");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("Unauthorized");
+      }
+
+      console.log("✅ 委託代管成功");
+    } catch (error) {
+      console.error("❌ 委託代管失敗:", error);
+      throw error;
+    }
+  });
+
+  it("驗證房源", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+
+      const certificateHash = createHash("sha256").update("權狀影本").digest();
+      const [ownershipDocumentPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("listing_document"), listingPda.toBuffer(), certificateHash],
+        program.programId
+      );
+
+      await program.methods
+        .verifyListing()
+        .accounts({
+          listing: listingPda,
+          platform: platformPda,
+          ownershipDocument: ownershipDocumentPda,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const listingAccount = await program.account.listing.fetch(listingPda);
+      expect(listingAccount.isVerified).to.be.true;
+
+      const ownershipDocument = await program.account.listingDocument.fetch(ownershipDocumentPda);
+      expect(ownershipDocument.isVerified).to.be.true;
+      expect(ownershipDocument.verifiedBy.toString()).to.equal(provider.wallet.publicKey.toString());
+
+      console.log("✅ 房源驗證成功");
+    } catch (error) {
+      console.error("❌ 房源驗證失敗:", error);
+      throw error;
+    }
+  });
+
+  it("設定房源共有人", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+      const coOwnerKeypair = Keypair.generate();
+      const signer = (keypair: Keypair) => ({ pubkey: keypair.publicKey, isWritable: false, isSigner: true });
+
+      // 尚未設定共有人時由房源管理者同意
+      await program.methods
+        .setListingOwners([
+          { owner: landlordKeypair.publicKey, shareBps: 6000 },
+          { owner: coOwnerKeypair.publicKey, shareBps: 4000 },
+        ])
+        .accounts({ listing: listingPda })
+        .remainingAccounts([signer(landlordKeypair)])
+        .signers([landlordKeypair])
+        .rpc();
+
+      let listingAccount = await program.account.listing.fetch(listingPda);
+      expect(listingAccount.owners.length).to.equal(2);
+
+      // 未達過半持分不可變更
+      try {
+        await program.methods
+          .setListingOwners([])
+          .accounts({ listing: listingPda })
+          .remainingAccounts([signer(coOwnerKeypair)])
+          .signers([coOwnerKeypair])
+          .rpc();
+        expect.fail("應要求過半數持分同意");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("OwnerConsentRequired");
+      }
+
+      // 恢復為單一房東
+      await program.methods
+        .setListingOwners([])
+        .accounts({ listing: listingPda })
+        .remainingAccounts([signer(landlordKeypair)])
+        .signers([landlordKeypair])
+        .rpc();
+
+      listingAccount = await program.account.listing.fetch(listingPda);
+      expect(listingAccount.owners.length).to.equal(0);
+
+      console.log("✅ 房源共有人設定成功");
+    } catch (error) {
+      console.error("❌ 房源共有人設定失敗:", error);
+      throw error;
+    }
+  });
+
+  it("終止租約", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+
+      const [rentalPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("rental"),
+          listingPda.toBuffer(),
+          tenantKeypair.publicKey.toBuffer()
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .terminateRental()
+        .accounts({
+          rental: rentalPda,
+          listing: listingPda,
+          districtIndex: districtIndexPda,
+          priceIndex: priceIndexPda,
+          landlord: landlordKeypair.publicKey,
+          tenant: tenantKeypair.publicKey,
+          tenantProfile: tenantProfilePda,
+          authority: tenantKeypair.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([tenantKeypair])
+        .rpc();
+
+      const rentalAccount = await program.account.rental.fetch(rentalPda);
+      expect(rentalAccount.status).to.equal(2); // Terminated
+
+      const listingAccount = await program.account.listing.fetch(listingPda);
+      expect(listingAccount.isAvailable).to.be.true;
+
+      // 租客提前終止會記錄於信用紀錄
+      const tenantProfile = await program.account.tenantProfile.fetch(tenantProfilePda);
+      expect(tenantProfile.tenantTerminations).to.equal(1);
+      expect(tenantProfile.reputationScore).to.equal(450);
+      expect(rentalAccount.depositSettled).to.be.true;
+
+      console.log("✅ 租約終止成功");
+    } catch (error) {
+      console.error("❌ 租約終止失敗:", error);
+      throw error;
+    }
+  });
+
+  it("離線簽署要約租用", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+
+      const offerTenantKeypair = Keypair.generate();
+      const airdrop = await provider.connection.requestAirdrop(offerTenantKeypair.publicKey, 10 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(airdrop);
+
+      const [offerTenantProfilePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tenant_profile"), offerTenantKeypair.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .createTenantProfile()
+        .accounts({
+          profile: offerTenantProfilePda,
+          wallet: offerTenantKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([offerTenantKeypair])
+        .rpc();
+
+      // 房東離線簽署要約，租客單筆交易即可租用
+      const offer = {
+        listing: listingPda,
+        tenant: offerTenantKeypair.publicKey,
+        price: new anchor.BN(0.5 * LAMPORTS_PER_SOL),
+        contractLength: 6,
+        expiresAt: new anchor.BN(Math.floor(Date.now() / 1000) + 60 * 60),
+        nonce: new anchor.BN(1),
+        leaseDocumentHash: [...leaseHash],
+        leaseVersion: 1,
+      };
+      const message = Buffer.concat([
+        Buffer.from("rental_contract:offer:v1"),
+        program.coder.types.encode("SignedOffer", offer),
+      ]);
+      const ed25519Ix = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: landlordKeypair.secretKey,
+        message,
+      });
+
+      const [rentalPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("rental"), listingPda.toBuffer(), offerTenantKeypair.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .rentProperty(new anchor.BN(2), offer, null)
+        .accounts({
+          rental: rentalPda,
+          listing: listingPda,
+          platform: platformPda,
+          districtIndex: districtIndexPda,
+          priceIndex: priceIndexPda,
+          landlordProfile: landlordProfilePda,
+          tenantProfile: offerTenantProfilePda,
+          lease: null,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          landlord: landlordKeypair.publicKey,
+          tenant: offerTenantKeypair.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ed25519Ix])
+        .signers([offerTenantKeypair])
+        .rpc();
+
+      const rentalAccount = await program.account.rental.fetch(rentalPda);
+      expect(rentalAccount.price.toString()).to.equal(offer.price.toString());
+      expect(rentalAccount.contractLength).to.equal(6);
+      expect(rentalAccount.leaseVersion).to.equal(1);
+
+      const listingAccount = await program.account.listing.fetch(listingPda);
+      expect(listingAccount.offerNonce.toNumber()).to.equal(1);
+
+      // 租客終止以重新開放房源供後續測試使用
+      await program.methods
+        .terminateRental()
+        .accounts({
+          rental: rentalPda,
+          listing: listingPda,
+          districtIndex: districtIndexPda,
+          priceIndex: priceIndexPda,
+          landlord: landlordKeypair.publicKey,
+          tenant: offerTenantKeypair.publicKey,
+          tenantProfile: offerTenantProfilePda,
+          authority: offerTenantKeypair.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([offerTenantKeypair])
+        .rpc();
+
+      console.log("✅ 離線要約租用成功");
+    } catch (error) {
+      console.error("❌ 離線要約租用失敗:", error);
+      throw error;
+    }
+  });
+
+  it("設定分租房間", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+      const room = (roomId: number, size: number, privateBathroom: boolean) => ({
+        roomId,
+        size,
+        price: new anchor.BN(8 * LAMPORTS_PER_SOL),
+        deposit: new anchor.BN(16 * LAMPORTS_PER_SOL),
+        privateBathroom,
+        rental: PublicKey.default,
+      });
+
+      await program.methods
+        .configureRooms([room(1, 6, true), room(2, 4, false)])
+        .accounts({
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      let listingAccount = await program.account.listing.fetch(listingPda);
+      expect(listingAccount.roomUnits.length).to.equal(2);
+      expect(listingAccount.roomUnits[0].privateBathroom).to.be.true;
+
+      // 房間總坪數不得超過房源坪數
+      try {
+        await program.methods
+          .configureRooms([room(1, 10, true), room(2, 10, false)])
+          .accounts({
+            listing: listingPda,
+            authority: landlordKeypair.publicKey,
+          })
+          .signers([landlordKeypair])
+          .rpc();
+        expect.fail("應拒絕超過房源坪數的房間設定");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("InvalidRoomUnits");
+      }
+
+      // 恢復為整層出租供後續測試使用
+      await program.methods
+        .configureRooms([])
+        .accounts({
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      listingAccount = await program.account.listing.fetch(listingPda);
+      expect(listingAccount.roomUnits.length).to.equal(0);
+
+      console.log("✅ 分租房間設定成功");
+    } catch (error) {
+      console.error("❌ 分租房間設定失敗:", error);
+      throw error;
+    }
+  });
+
+  it("短租預訂", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+      const [calendarPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("calendar"), listingPda.toBuffer()],
+        program.programId
+      );
+      const terms = {
+        nightlyPrice: new anchor.BN(0.2 * LAMPORTS_PER_SOL),
+        minNights: 2,
+        maxNights: 14,
+      };
+
+      await program.methods
+        .enableShortStay(terms)
+        .accounts({
+          calendar: calendarPda,
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      const guestKeypair = Keypair.generate();
+      const airdrop = await provider.connection.requestAirdrop(guestKeypair.publicKey, 2 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(airdrop);
+
+      const bookingPda = (checkInDay: number) => {
+        const day = Buffer.alloc(4);
+        day.writeUInt32LE(checkInDay);
+        return PublicKey.findProgramAddressSync(
+          [Buffer.from("stay"), listingPda.toBuffer(), day],
+          program.programId
+        )[0];
+      };
+      const checkInDay = Math.floor(Date.now() / 1000 / 86400) + 7;
+
+      await program.methods
+        .bookStay(checkInDay, 3)
+        .accounts({
+          booking: bookingPda(checkInDay),
+          calendar: calendarPda,
+          listing: listingPda,
+          guest: guestKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([guestKeypair])
+        .rpc();
+
+      const booking = await program.account.stayBooking.fetch(bookingPda(checkInDay));
+      expect(booking.amount.toString()).to.equal(new anchor.BN(0.6 * LAMPORTS_PER_SOL).toString());
+
+      // 與既有預訂重疊的日期不可預訂
+      try {
+        await program.methods
+          .bookStay(checkInDay + 2, 2)
+          .accounts({
+            booking: bookingPda(checkInDay + 2),
+            calendar: calendarPda,
+            listing: listingPda,
+            guest: guestKeypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([guestKeypair])
+          .rpc();
+        expect.fail("應拒絕重疊的住宿日期");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("StayUnavailable");
+      }
+
+      // 入住前取消全額退款，並關閉短租模式供後續測試使用
+      await program.methods
+        .cancelStay()
+        .accounts({
+          booking: bookingPda(checkInDay),
+          calendar: calendarPda,
+          listing: listingPda,
+          guest: guestKeypair.publicKey,
+          authority: guestKeypair.publicKey,
+        })
+        .signers([guestKeypair])
+        .rpc();
+
+      await program.methods
+        .disableShortStay()
+        .accounts({
+          calendar: calendarPda,
+          listing: listingPda,
+          landlord: landlordKeypair.publicKey,
+          authority: landlordKeypair.publicKey,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      const listingAccount = await program.account.listing.fetch(listingPda);
+      expect(listingAccount.shortStay).to.be.null;
+
+      console.log("✅ 短租預訂成功");
+    } catch (error) {
+      console.error("❌ 短租預訂失敗:", error);
+      throw error;
+    }
+  });
+
+  it("預留房源與到期退還", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+      const [reservationPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("reservation"), listingPda.toBuffer()],
+        program.programId
+      );
+      const fee = new anchor.BN(LAMPORTS_PER_SOL);
+
+      await program.methods
+        .setReservationTerms(fee, new anchor.BN(2))
+        .accounts({
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      const reserverKeypair = Keypair.generate();
+      const airdrop = await provider.connection.requestAirdrop(reserverKeypair.publicKey, 3 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(airdrop);
+
+      await program.methods
+        .reserveListing()
+        .accounts({
+          reservation: reservationPda,
+          listing: listingPda,
+          tenant: reserverKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([reserverKeypair])
+        .rpc();
+
+      let listingAccount = await program.account.listing.fetch(listingPda);
+      expect(listingAccount.reservedBy.toString()).to.equal(reserverKeypair.publicKey.toString());
+
+      // 保留期間內其他租客不可申請
+      try {
+        await program.methods
+          .submitApplication(listingAccount.moveInDate, 12, null, new anchor.BN(0))
+          .accounts({
+            application: PublicKey.findProgramAddressSync(
+              [Buffer.from("application"), listingPda.toBuffer(), tenantKeypair.publicKey.toBuffer()],
+              program.programId
+            )[0],
+            listing: listingPda,
+            tenantProfile: tenantProfilePda,
+            tenant: tenantKeypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([tenantKeypair])
+          .rpc();
+        expect.fail("應拒絕已預留房源的申請");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("ListingReserved");
+      }
+
+      // 到期後任何人皆可退還預留費用並解除保留
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await program.methods
+        .releaseReservation()
+        .accounts({
+          reservation: reservationPda,
+          listing: listingPda,
+          tenant: reserverKeypair.publicKey,
+        })
+        .rpc();
+
+      listingAccount = await program.account.listing.fetch(listingPda);
+      expect(listingAccount.reservedUntil.toNumber()).to.equal(0);
+
+      // 關閉預留供後續測試使用
+      await program.methods
+        .setReservationTerms(new anchor.BN(0), new anchor.BN(0))
+        .accounts({
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      console.log("✅ 房源預留與退還成功");
+    } catch (error) {
+      console.error("❌ 房源預留失敗:", error);
+      throw error;
+    }
+  });
+
+  it("租屋申請與審核", async () => {
+    try {
+      const [listingPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("listing"),
+          platformPda.toBuffer(),
+          Buffer.from([0])
+        ],
+        program.programId
+      );
+
+      const applicantKeypair = Keypair.generate();
+      const airdrop = await provider.connection.requestAirdrop(applicantKeypair.publicKey, 10 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(airdrop);
+
+      const [applicantProfilePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tenant_profile"), applicantKeypair.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .createTenantProfile()
+        .accounts({
+          profile: applicantProfilePda,
+          wallet: applicantKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([applicantKeypair])
+        .rpc();
+
+      // 房東改為須經申請審核
+      await program.methods
+        .setTenantRequirements(400, true)
+        .accounts({
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      const applicationPda = (tenant: PublicKey) =>
+        PublicKey.findProgramAddressSync(
+          [Buffer.from("application"), listingPda.toBuffer(), tenant.toBuffer()],
+          program.programId
+        )[0];
+      const moveInDate = new anchor.BN(Math.floor(Date.now() / 1000) + 7 * 24 * 60 * 60);
+      const holdAmount = new anchor.BN(LAMPORTS_PER_SOL);
+
+      for (const [keypair, profile] of [
+        [tenantKeypair, tenantProfilePda],
+        [applicantKeypair, applicantProfilePda],
+      ] as [Keypair, PublicKey][]) {
+        await program.methods
+          .submitApplication(moveInDate, 12, null, holdAmount)
+          .accounts({
+            application: applicationPda(keypair.publicKey),
+            listing: listingPda,
+            tenantProfile: profile,
+            tenant: keypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([keypair])
+          .rpc();
+      }
+
+      const [rentalPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("rental"), listingPda.toBuffer(), applicantKeypair.publicKey.toBuffer()],
+        program.programId
+      );
+
+      // 接受前雙方須先簽署租約
+      const applicantLease = leasePda(listingPda, applicantKeypair.publicKey, 1);
+      await program.methods
+        .proposeLease(applicantKeypair.publicKey, [...leaseHash], 1)
+        .accounts({
+          lease: applicantLease,
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+      await program.methods
+        .signLease([...leaseHash], 1)
+        .accounts({
+          lease: applicantLease,
+          tenant: applicantKeypair.publicKey,
+        })
+        .signers([applicantKeypair])
+        .rpc();
+
+      // 其他申請人預留房源期間，房東不可接受他人的申請
+      const [reservationPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("reservation"), listingPda.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .setReservationTerms(new anchor.BN(LAMPORTS_PER_SOL), new anchor.BN(2))
+        .accounts({
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+      await program.methods
+        .reserveListing()
+        .accounts({
+          reservation: reservationPda,
+          listing: listingPda,
+          tenant: tenantKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([tenantKeypair])
+        .rpc();
+      try {
+        await program.methods
+          .acceptApplication()
+          .accounts({
+            application: applicationPda(applicantKeypair.publicKey),
+            rental: rentalPda,
+            listing: listingPda,
+            platform: platformPda,
+            districtIndex: districtIndexPda,
+            priceIndex: priceIndexPda,
+            tenantProfile: applicantProfilePda,
+            landlordProfile: landlordProfilePda,
+            lease: applicantLease,
+            tenant: applicantKeypair.publicKey,
+            authority: landlordKeypair.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([landlordKeypair])
+          .rpc();
+        expect.fail("應拒絕接受他人預留中房源的申請");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("ListingReserved");
+      }
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await program.methods
+        .releaseReservation()
+        .accounts({
+          reservation: reservationPda,
+          listing: listingPda,
+          tenant: tenantKeypair.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .setReservationTerms(new anchor.BN(0), new anchor.BN(0))
+        .accounts({
+          listing: listingPda,
+          authority: landlordKeypair.publicKey,
+        })
+        .signers([landlordKeypair])
+        .rpc();
+
+      const tenantBalanceBefore = await provider.connection.getBalance(tenantKeypair.publicKey);
+      // 接受申請，其他申請一併退還
+      await program.methods
+        .acceptApplication()
+        .accounts({
+          application: applicationPda(applicantKeypair.publicKey),
+          rental: rentalPda,
+          listing: listingPda,
+          platform: platformPda,
+          districtIndex: districtIndexPda,
+          priceIndex: priceIndexPda,
+          tenantProfile: applicantProfilePda,
+          landlordProfile: landlordProfilePda,
+          lease: applicantLease,
+          tenant: applicantKeypair.publicKey,
+          authority: landlordKeypair.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: applicationPda(tenantKeypair.publicKey), isWritable: true, isSigner: false },
+          { pubkey: tenantKeypair.publicKey, isWritable: true, isSigner: false },
+        ])
+        .signers([landlordKeypair])
+        .rpc();
+
+      let rentalAccount = await program.account.rental.fetch(rentalPda);
+      expect(rentalAccount.tenant.toString()).to.equal(applicantKeypair.publicKey.toString());
+      expect(rentalAccount.escrowBalance.toString()).to.equal(holdAmount.toString());
+
+      // 押金先以保證金計，不足部分於第一期租金時補足
+      expect(rentalAccount.deposit.toString()).to.equal(holdAmount.toString());
+      let listingAccount = await program.account.listing.fetch(listingPda);
+      expect(rentalAccount.depositDue.toString()).to.equal(listingAccount.deposit.sub(holdAmount).toString());
+
+      // 租約自申請的入住日起算，起租前為待起租
+      expect(rentalAccount.status).to.deep.equal({ pending: {} });
+      expect(rentalAccount.startDate.toString()).to.equal(moveInDate.toString());
+
+      listingAccount = await program.account.listing.fetch(listingPda);
+      expect(listingAccount.isAvailable).to.be.false;
+
+      const competing = await provider.connection.getAccountInfo(applicationPda(tenantKeypair.publicKey));
+      expect(competing).to.be.null;
+      const tenantBalanceAfter = await provider.connection.getBalance(tenantKeypair.publicKey);
+      expect(tenantBalanceAfter - tenantBalanceBefore).to.be.greaterThan(holdAmount.toNumber());
+
+      // 起租前尚不可啟用
+      try {
+        await program.methods
+          .activateRental()
+          .accounts({
+            rental: rentalPda,
+            listing: listingPda,
+            landlord: landlordKeypair.publicKey,
+            tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        expect.fail("起租日前不應能啟用租約");
+      } catch (error) {
+        expect(error.error.errorCode.code).to.equal("RentalNotStarted");
+      }
+
+      // 起租前租客取消：預設規則全額退還託管款項，且不計入信用紀錄
+      const applicantBalanceBefore = await provider.connection.getBalance(applicantKeypair.publicKey);
+      await program.methods
+        .terminateRental()
+        .accounts({
+          rental: rentalPda,
+          listing: listingPda,
+          districtIndex: districtIndexPda,
+          priceIndex: priceIndexPda,
+          landlord: landlordKeypair.publicKey,
+          tenant: applicantKeypair.publicKey,
+          tenantProfile: applicantProfilePda,
+          authority: applicantKeypair.publicKey,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([applicantKeypair])
+        .rpc();
+
+      rentalAccount = await program.account.rental.fetch(rentalPda);
+      expect(rentalAccount.status).to.deep.equal({ terminated: {} });
+      const applicantBalanceAfter = await provider.connection.getBalance(applicantKeypair.publicKey);
+      expect(applicantBalanceAfter - applicantBalanceBefore).to.be.greaterThan(holdAmount.toNumber() - 10_000);
+      const applicantProfile = await program.account.tenantProfile.fetch(applicantProfilePda);
+      expect(applicantProfile.tenantTerminations).to.equal(0);
+
+      listingAccount = await program.account.listing.fetch(listingPda);
+      expect(listingAccount.isAvailable).to.be.true;
+
+      console.log("✅ 租屋申請審核成功");
+    } catch (error) {
+      console.error("❌ 租屋申請審核失敗:", error);
       throw error;
     }
   });